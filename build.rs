@@ -0,0 +1,83 @@
+use std::env;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// With the `embed-inputs` feature, list every input, named input (such as the examples in
+/// `day_XX/example.txt`) and answers file below the input directory, in plain text or encrypted,
+/// so that `src/embedded.rs` can compile them into the binary. The directory defaults to
+/// `inputs/` and can be changed with `AOC_EMBED_DIR`.
+fn main() -> io::Result<()> {
+    println!("cargo:rerun-if-env-changed=AOC_EMBED_DIR");
+    if env::var_os("CARGO_FEATURE_EMBED_INPUTS").is_none() {
+        return Ok(());
+    }
+
+    let manifest_dir = PathBuf::from(env::var("CARGO_MANIFEST_DIR").unwrap());
+    let input_dir = match env::var_os("AOC_EMBED_DIR") {
+        Some(dir) => manifest_dir.join(dir),
+        None => manifest_dir.join("inputs"),
+    };
+    println!("cargo:rerun-if-changed={}", input_dir.display());
+
+    let mut files: Vec<PathBuf> = Vec::new();
+    if input_dir.is_dir() {
+        collect(&input_dir, &mut files)?;
+    }
+    files.sort();
+
+    // keys use forward slashes whatever the platform, e.g. "day_01/example.txt"
+    let key = |path: &Path| {
+        let components: Vec<String> = path
+            .strip_prefix(&input_dir)
+            .unwrap()
+            .components()
+            .map(|c| c.as_os_str().to_string_lossy().to_string())
+            .collect();
+        components.join("/")
+    };
+    let (encrypted, plain): (Vec<&PathBuf>, Vec<&PathBuf>) = files
+        .iter()
+        .partition(|p| p.extension().is_some_and(|ext| ext == "enc"));
+
+    let mut code = String::from("static FILES: &[(&str, &str)] = &[\n");
+    for path in plain.into_iter() {
+        println!("cargo:rerun-if-changed={}", path.display());
+        code.push_str(&format!(
+            "    ({:?}, include_str!({:?})),\n",
+            key(path),
+            path.display().to_string()
+        ));
+    }
+    code.push_str("];\n\nstatic ENCRYPTED: &[(&str, &[u8])] = &[\n");
+    for path in encrypted.into_iter() {
+        println!("cargo:rerun-if-changed={}", path.display());
+        code.push_str(&format!(
+            "    ({:?}, include_bytes!({:?})),\n",
+            key(path),
+            path.display().to_string()
+        ));
+    }
+    code.push_str("];\n");
+
+    let out = PathBuf::from(env::var("OUT_DIR").unwrap()).join("embedded_inputs.rs");
+    fs::write(out, code)
+}
+
+/// All text files below `dir`, i.e. the inputs and the answers, and the encrypted inputs.
+fn collect(dir: &Path, files: &mut Vec<PathBuf>) -> io::Result<()> {
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.is_dir() {
+            println!("cargo:rerun-if-changed={}", path.display());
+            collect(&path, files)?;
+        } else if path
+            .extension()
+            .is_some_and(|ext| ext == "txt" || ext == "toml" || ext == "enc")
+        {
+            files.push(path);
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,16 @@
+#!/bin/sh
+# Replay every saved crash in regressions/<target>/ through its fuzz target.
+# A target passes when none of its regression inputs panic.
+cd "$(dirname "$0")"
+
+status=0
+for dir in regressions/day*/; do
+    target=$(basename "$dir")
+    set -- "$dir"*
+    [ -e "$1" ] || continue
+
+    echo "# ---- $target ---- #"
+    cargo +nightly fuzz run "$target" "$@" || status=1
+done
+
+exit $status
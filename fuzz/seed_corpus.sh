@@ -0,0 +1,9 @@
+#!/bin/sh
+# Seed the corpus of every fuzz target with the matching puzzle input.
+cd "$(dirname "$0")"
+
+for input in ../inputs/day_*.txt; do
+    day=$(basename "$input" .txt | sed 's/day_/day/')
+    mkdir -p "corpus/$day"
+    cp "$input" "corpus/$day/input.txt"
+done
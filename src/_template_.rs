@@ -1,35 +1,21 @@
+use crate::parsing::parse_all;
+use crate::registry::Solver;
 use std::io;
 use std::io::prelude::*;
 
-pub fn solve(input: impl BufRead, part: u8) -> io::Result<()> {
-    let parsed_input = parse(input)?;
-
-    println!("# ---- DAY 05 ---- #");
-    let now = std::time::Instant::now();
-    let solution = match part {
-        0 => (part_1(&parsed_input), part_2(&parsed)),
-        1 => (part_1(&parsed_input), None),
-        2 => (None, part_2(&parsed_input)),
-        _ => unimplemented!(),
-    };
-    let time = now.elapsed().as_micros();
-
-    match solution.0 {
-        Some(x) => println!("Part 1: {}", x),
-        None => println!(),
-    }
-    match solution.1 {
-        Some(x) => println!("Part 2: {}", x),
-        None => println!(),
-    }
-
-    println!("Time elapsed: {} µs", time);
-
-    Ok(())
+pub fn solvers() -> Vec<Solver> {
+    vec![
+        Solver::new(XX, 1, |input, _| {
+            Ok(part_1(&parse(input.as_bytes())?).map(|x| x.to_string()))
+        }),
+        Solver::new(XX, 2, |input, _| {
+            Ok(part_2(&parse(input.as_bytes())?).map(|x| x.to_string()))
+        }),
+    ]
 }
 
-fn parse(input: impl BufRead) -> io::Result<_> {
-    unimplemented!();
+pub fn parse(input: impl BufRead) -> io::Result<_> {
+    parse_all(input, |cursor| unimplemented!())
 }
 
 fn part_1(_: &<T>) -> Option<i32> {
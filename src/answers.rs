@@ -0,0 +1,220 @@
+use std::collections::BTreeMap;
+use std::io;
+use std::path::Path;
+
+use crate::embedded;
+
+/// Name of the file holding the expected (i.e. accepted) answers, kept next to the inputs since
+/// the answers belong to them.
+pub const ANSWERS_FILE: &str = "answers.toml";
+
+/// The expected answers of each day and part, read from a file of the form
+///
+/// ```toml
+/// [day01]
+/// part1 = "69836"
+/// part2 = 207968
+///
+/// [day01.alice]       # answers to the named input day_01/alice.txt
+/// part1 = 71124
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct Answers(BTreeMap<(u8, Option<String>, u8), String>);
+
+/// The outcome of checking an answer against the expected one.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Verdict {
+    Correct,
+    Wrong(String),
+    Unknown,
+}
+
+impl Answers {
+    /// Load the answers stored in the input directory. A missing file holds no answers.
+    pub fn load(input_dir: &Path) -> io::Result<Self> {
+        let path = input_dir.join(ANSWERS_FILE);
+        let content = match embedded::read_to_string(input_dir, &path) {
+            Ok(content) => content,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Answers::default()),
+            Err(e) => return Err(e),
+        };
+
+        let invalid = |msg: String| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("Invalid answers file {}: {}", path.display(), msg),
+            )
+        };
+
+        let table: toml::value::Table =
+            toml::from_str(&content).map_err(|e| invalid(e.to_string()))?;
+        let mut answers = Answers::default();
+
+        for (day_key, parts) in table.into_iter() {
+            let day = day_key
+                .strip_prefix("day")
+                .and_then(|d| d.parse::<u8>().ok())
+                .ok_or_else(|| invalid(format!("unexpected key \"{}\"", day_key)))?;
+            let parts = match parts {
+                toml::Value::Table(parts) => parts,
+                _ => return Err(invalid(format!("\"{}\" is not a table", day_key))),
+            };
+
+            // the answers of named inputs are nested one level deeper
+            let mut entries: Vec<(Option<String>, String, toml::Value)> =
+                parts.into_iter().map(|(k, v)| (None, k, v)).collect();
+
+            while let Some((set, part_key, answer)) = entries.pop() {
+                let answer = match (answer, &set) {
+                    (toml::Value::Table(parts), None) => {
+                        let set = Some(part_key);
+                        entries.extend(parts.into_iter().map(|(k, v)| (set.clone(), k, v)));
+                        continue;
+                    }
+                    (toml::Value::String(s), _) => s,
+                    (v, _) => v.to_string(),
+                };
+                let part = part_key
+                    .strip_prefix("part")
+                    .and_then(|p| p.parse::<u8>().ok())
+                    .ok_or_else(|| invalid(format!("unexpected key \"{}\"", part_key)))?;
+
+                answers.0.insert((day, set, part), answer);
+            }
+        }
+
+        Ok(answers)
+    }
+
+    /// The expected answer to the `day_XX.txt` input.
+    pub fn get(&self, day: u8, part: u8) -> Option<&str> {
+        self.get_for(day, None, part)
+    }
+
+    /// The expected answer to one of the inputs of a day, `None` being the `day_XX.txt` input.
+    pub fn get_for(&self, day: u8, input_set: Option<&str>, part: u8) -> Option<&str> {
+        self.0
+            .get(&(day, input_set.map(|s| s.to_string()), part))
+            .map(|s| s.as_str())
+    }
+
+    /// Whether an answer to any of the inputs of the day is known.
+    pub fn has_any(&self, day: u8, part: u8) -> bool {
+        self.0.keys().any(|(d, _, p)| *d == day && *p == part)
+    }
+
+    pub fn verdict(
+        &self,
+        day: u8,
+        input_set: Option<&str>,
+        part: u8,
+        answer: Option<&str>,
+    ) -> Verdict {
+        match (self.get_for(day, input_set, part), answer) {
+            (None, _) => Verdict::Unknown,
+            (Some(expected), Some(answer)) if expected.trim() == answer.trim() => Verdict::Correct,
+            (Some(expected), _) => Verdict::Wrong(expected.to_string()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::env;
+    use std::fs;
+    use std::path::PathBuf;
+
+    /// An input directory of its own for each test, with an answers file holding `content`.
+    fn input_dir(name: &str, content: Option<&str>) -> PathBuf {
+        let dir = env::temp_dir().join(format!("aoc2022-answers-{}-{}", name, std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        if let Some(content) = content {
+            fs::write(dir.join(ANSWERS_FILE), content).unwrap();
+        }
+
+        dir
+    }
+
+    const ANSWERS: &str = r#"
+[day01]
+part1 = "69836"
+part2 = 207968
+
+[day01.alice]
+part1 = 71124
+
+[day10]
+part2 = """
+
+####.#..#
+"""
+"#;
+
+    #[test]
+    fn answers_of_every_input() {
+        let answers = Answers::load(&input_dir("load", Some(ANSWERS))).unwrap();
+
+        assert_eq!(answers.get(1, 1), Some("69836"));
+        assert_eq!(answers.get(1, 2), Some("207968"));
+        assert_eq!(answers.get_for(1, Some("alice"), 1), Some("71124"));
+        assert_eq!(answers.get_for(1, Some("alice"), 2), None);
+        assert_eq!(answers.get_for(1, Some("bob"), 1), None);
+        assert!(answers.has_any(1, 2));
+        assert!(!answers.has_any(2, 1));
+    }
+
+    #[test]
+    fn verdicts() {
+        let answers = Answers::load(&input_dir("verdicts", Some(ANSWERS))).unwrap();
+
+        assert_eq!(answers.verdict(1, None, 1, Some("69836")), Verdict::Correct);
+        assert_eq!(
+            answers.verdict(1, None, 1, Some("69835")),
+            Verdict::Wrong("69836".to_string())
+        );
+        // no answer at all is as wrong as a different one
+        assert_eq!(
+            answers.verdict(1, None, 1, None),
+            Verdict::Wrong("69836".to_string())
+        );
+        assert_eq!(answers.verdict(2, None, 1, Some("1")), Verdict::Unknown);
+        assert_eq!(answers.verdict(2, None, 1, None), Verdict::Unknown);
+
+        // answers of named inputs are only compared with those of the same input
+        assert_eq!(
+            answers.verdict(1, Some("alice"), 1, Some("71124")),
+            Verdict::Correct
+        );
+        assert_eq!(
+            answers.verdict(1, Some("alice"), 1, Some("69836")),
+            Verdict::Wrong("71124".to_string())
+        );
+
+        // multi-line answers are compared without their surrounding whitespace
+        assert_eq!(
+            answers.verdict(10, None, 2, Some("\n\n####.#..#")),
+            Verdict::Correct
+        );
+    }
+
+    #[test]
+    fn a_missing_file_holds_no_answers() {
+        let answers = Answers::load(&input_dir("missing", None)).unwrap();
+
+        assert_eq!(answers.verdict(1, None, 1, Some("1")), Verdict::Unknown);
+    }
+
+    #[test]
+    fn unexpected_keys_are_rejected() {
+        for (name, content) in [
+            ("bad-day", "[first]\npart1 = 1\n"),
+            ("bad-part", "[day01]\none = 1\n"),
+            ("not-a-table", "day01 = 1\n"),
+            ("not-toml", "[day01\n"),
+        ] {
+            let e = Answers::load(&input_dir(name, Some(content))).unwrap_err();
+            assert_eq!(e.kind(), io::ErrorKind::InvalidData, "{}", name);
+        }
+    }
+}
@@ -0,0 +1,280 @@
+use serde::{Deserialize, Serialize};
+use std::env;
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+use std::sync::OnceLock;
+use std::time::{Duration, UNIX_EPOCH};
+
+use crate::registry::{Params, Solver};
+
+/// An on-disk store of answers, with one file per entry. An entry is keyed by everything that can
+/// change the answer: the solver (day, part and variant), the parameters of its day, the content
+/// of the input and the binary that computed it.
+#[derive(Debug, Clone)]
+pub struct Cache {
+    dir: PathBuf,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Entry {
+    pub day: u8,
+    pub part: u8,
+    pub variant: String,
+    pub answer: Option<String>,
+    /// Time it took to compute the answer originally.
+    pub time_us: u64,
+}
+
+impl Cache {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Cache { dir: dir.into() }
+    }
+
+    pub fn get(&self, solver: &Solver, input: &str, params: &Params) -> Option<Entry> {
+        let path = self.entry_path(solver, input, params);
+        let entry: Entry = serde_json::from_str(&fs::read_to_string(path).ok()?).ok()?;
+
+        // guard against the (unlikely) case of a key collision
+        match entry.day == solver.day
+            && entry.part == solver.part
+            && entry.variant == solver.variant
+        {
+            true => Some(entry),
+            false => None,
+        }
+    }
+
+    pub fn put(
+        &self,
+        solver: &Solver,
+        input: &str,
+        params: &Params,
+        answer: &Option<String>,
+        time: Duration,
+    ) -> io::Result<()> {
+        let entry = Entry {
+            day: solver.day,
+            part: solver.part,
+            variant: solver.variant.to_string(),
+            answer: answer.clone(),
+            time_us: time.as_micros() as u64,
+        };
+
+        fs::create_dir_all(&self.dir)?;
+
+        // write to a temporary file first, so concurrent readers never see half an entry
+        let path = self.entry_path(solver, input, params);
+        let tmp = path.with_extension(format!("tmp{}", std::process::id()));
+        fs::write(&tmp, serde_json::to_string(&entry)?)?;
+        fs::rename(tmp, path)
+    }
+
+    /// Remove all entries, returning how many were removed.
+    pub fn clear(&self) -> io::Result<usize> {
+        let entries = match fs::read_dir(&self.dir) {
+            Ok(entries) => entries,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(0),
+            Err(e) => return Err(e),
+        };
+
+        let mut removed: usize = 0;
+        for entry in entries {
+            let path = entry?.path();
+            if path.extension().is_some_and(|ext| ext == "json") {
+                fs::remove_file(path)?;
+                removed += 1;
+            }
+        }
+
+        Ok(removed)
+    }
+
+    fn entry_path(&self, solver: &Solver, input: &str, params: &Params) -> PathBuf {
+        // only the parameters of the solver's own day can affect its answer
+        let prefix = format!("day{:0>2}.", solver.day);
+        let params: String = params
+            .iter()
+            .filter(|(k, _)| k.starts_with(&prefix))
+            .map(|(k, v)| format!("{}={};", k, v))
+            .collect();
+
+        let key = format!(
+            "{}|{}|{}|{}|{:016x}|{:016x}",
+            solver.day,
+            solver.part,
+            solver.variant,
+            params,
+            fnv1a(input.as_bytes()),
+            build_id()
+        );
+
+        self.dir.join(format!(
+            "day{:0>2}-{}-{:016x}.json",
+            solver.day,
+            solver.part,
+            fnv1a(key.as_bytes())
+        ))
+    }
+}
+
+/// The default cache location, following the XDG convention.
+pub fn default_dir() -> PathBuf {
+    match env::var_os("XDG_CACHE_HOME") {
+        Some(dir) if !dir.is_empty() => PathBuf::from(dir).join("aoc2022"),
+        _ => match env::var_os("HOME") {
+            Some(home) => PathBuf::from(home).join(".cache").join("aoc2022"),
+            None => PathBuf::from(".aoc-cache"),
+        },
+    }
+}
+
+/// Identifies the running binary by its size and modification time, so that rebuilding with
+/// different code invalidates the cached answers.
+fn build_id() -> u64 {
+    static BUILD_ID: OnceLock<u64> = OnceLock::new();
+
+    *BUILD_ID.get_or_init(|| {
+        let metadata = env::current_exe().and_then(fs::metadata);
+        let modified = metadata
+            .as_ref()
+            .ok()
+            .and_then(|m| m.modified().ok())
+            .and_then(|t| t.duration_since(UNIX_EPOCH).ok());
+
+        match (metadata, modified) {
+            (Ok(m), Some(t)) => fnv1a(format!("{}|{}", m.len(), t.as_nanos()).as_bytes()),
+            // without a way to identify the build, fall back to the package version
+            _ => fnv1a(env!("CARGO_PKG_VERSION").as_bytes()),
+        }
+    })
+}
+
+/// 64-bit FNV-1a, a simple and stable (across builds and platforms) hash.
+pub fn fnv1a(bytes: &[u8]) -> u64 {
+    let mut hash: u64 = 0xcbf29ce484222325;
+    for &b in bytes {
+        hash ^= b as u64;
+        hash = hash.wrapping_mul(0x100000001b3);
+    }
+
+    hash
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn solver(day: u8, part: u8, variant: &'static str) -> Solver {
+        Solver::variant(day, part, variant, |_, _| Ok(None))
+    }
+
+    fn params(pairs: &[(&str, &str)]) -> Params {
+        let mut params = Params::new();
+        for (k, v) in pairs {
+            params.insert(k, v);
+        }
+
+        params
+    }
+
+    fn cache(name: &str) -> Cache {
+        let dir = env::temp_dir().join(format!("aoc2022-cache-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+
+        Cache::new(dir)
+    }
+
+    #[test]
+    fn answers_come_back() {
+        let cache = cache("round-trip");
+        let solver = solver(7, 1, "default");
+        let answer = Some("95437".to_string());
+
+        assert!(cache.get(&solver, "input", &Params::new()).is_none());
+        cache
+            .put(
+                &solver,
+                "input",
+                &Params::new(),
+                &answer,
+                Duration::from_micros(42),
+            )
+            .unwrap();
+
+        let entry = cache.get(&solver, "input", &Params::new()).unwrap();
+        assert_eq!(entry.answer, answer);
+        assert_eq!(entry.time_us, 42);
+        assert_eq!(
+            (entry.day, entry.part, entry.variant.as_str()),
+            (7, 1, "default")
+        );
+    }
+
+    #[test]
+    fn the_key_covers_everything_that_changes_the_answer() {
+        let cache = cache("key");
+        let own = params(&[("day07.threshold", "10")]);
+        cache
+            .put(
+                &solver(7, 1, "default"),
+                "input",
+                &own,
+                &None,
+                Duration::ZERO,
+            )
+            .unwrap();
+
+        let hit = |solver: Solver, input: &str, params: &Params| {
+            cache.get(&solver, input, params).is_some()
+        };
+        assert!(hit(solver(7, 1, "default"), "input", &own));
+
+        // another input, parameter value, part, day or variant is another entry
+        assert!(!hit(solver(7, 1, "default"), "input\n", &own));
+        assert!(!hit(
+            solver(7, 1, "default"),
+            "input",
+            &params(&[("day07.threshold", "11")])
+        ));
+        assert!(!hit(solver(7, 1, "default"), "input", &Params::new()));
+        assert!(!hit(solver(7, 2, "default"), "input", &own));
+        assert!(!hit(solver(8, 1, "default"), "input", &own));
+        assert!(!hit(solver(7, 1, "fast"), "input", &own));
+
+        // the parameters of other days cannot change the answer
+        let other = params(&[("day07.threshold", "10"), ("day11.checkpoint", "x.json")]);
+        assert!(hit(solver(7, 1, "default"), "input", &other));
+    }
+
+    #[test]
+    fn clear_removes_every_entry() {
+        let cache = cache("clear");
+        assert_eq!(cache.clear().unwrap(), 0);
+
+        for part in 1..=2 {
+            cache
+                .put(
+                    &solver(1, part, "default"),
+                    "input",
+                    &Params::new(),
+                    &None,
+                    Duration::ZERO,
+                )
+                .unwrap();
+        }
+
+        assert_eq!(cache.clear().unwrap(), 2);
+        assert!(cache
+            .get(&solver(1, 1, "default"), "input", &Params::new())
+            .is_none());
+        assert_eq!(cache.clear().unwrap(), 0);
+    }
+
+    #[test]
+    fn fnv1a_is_the_reference_hash() {
+        assert_eq!(fnv1a(b""), 0xcbf29ce484222325);
+        assert_eq!(fnv1a(b"a"), 0xaf63dc4c8601ec8c);
+        assert_eq!(fnv1a(b"foobar"), 0x85944171f73967e8);
+    }
+}
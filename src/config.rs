@@ -0,0 +1,657 @@
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::env;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::time::Duration;
+
+use crate::cache;
+use crate::history::{self, Baseline};
+use crate::registry::{Params, Solver};
+use crate::runner::TimeLimits;
+use crate::vault;
+
+/// Name of the project-level configuration file, looked up in the working directory.
+pub const PROJECT_CONFIG: &str = "aoc.toml";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OutputFormat {
+    Text,
+    Json,
+}
+
+impl FromStr for OutputFormat {
+    type Err = io::Error;
+
+    fn from_str(s: &str) -> io::Result<Self> {
+        match s {
+            "text" => Ok(OutputFormat::Text),
+            "json" => Ok(OutputFormat::Json),
+            _ => Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("Invalid output format \"{}\" (expected text or json)", s),
+            )),
+        }
+    }
+}
+
+impl fmt::Display for OutputFormat {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            OutputFormat::Text => write!(f, "text"),
+            OutputFormat::Json => write!(f, "json"),
+        }
+    }
+}
+
+/// Where the effective value of a setting came from.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Source {
+    Default,
+    File(PathBuf),
+    CommandLine,
+}
+
+impl fmt::Display for Source {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Source::Default => write!(f, "default"),
+            Source::File(path) => write!(f, "{}", path.display()),
+            Source::CommandLine => write!(f, "command line"),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Setting<T> {
+    pub value: T,
+    pub source: Source,
+}
+
+impl<T> Setting<T> {
+    pub fn new(value: T, source: Source) -> Self {
+        Setting { value, source }
+    }
+
+    fn default(value: T) -> Self {
+        Setting::new(value, Source::Default)
+    }
+}
+
+/// The runner configuration. Values are layered, with later layers taking precedence:
+/// the built-in defaults, the project's `aoc.toml`, the user's `aoc.toml` and finally the flags
+/// given on the command line.
+#[derive(Debug, Clone)]
+pub struct Config {
+    pub input_dir: Setting<PathBuf>,
+    pub format: Setting<OutputFormat>,
+    pub jobs: Setting<usize>,
+    pub cache: Setting<bool>,
+    pub cache_dir: Setting<PathBuf>,
+    /// Whether to append the timings of every run to the history.
+    pub record: Setting<bool>,
+    pub history_file: Setting<PathBuf>,
+    /// Key to decrypt the encrypted inputs with.
+    pub key_file: Setting<PathBuf>,
+    /// Slowdown, in percent, beyond which the history flags a run as a regression.
+    pub regression_threshold: Setting<f64>,
+    pub regression_baseline: Setting<Baseline>,
+    /// Time limit of every part, where zero means no limit.
+    pub timeout: Setting<Duration>,
+    /// Time limits of single days (part 0) or parts, overriding `timeout`.
+    pub timeouts: BTreeMap<(u8, u8), Setting<Duration>>,
+    pub params: BTreeMap<String, Setting<String>>,
+    /// Command lines of the external solvers, keyed by (variant, day, part) with part 0 covering
+    /// both parts of the day.
+    pub external: BTreeMap<(String, u8, u8), Setting<Vec<String>>>,
+}
+
+/// The layout of an `aoc.toml` file. Every key is optional.
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ConfigFile {
+    input_dir: Option<PathBuf>,
+    format: Option<String>,
+    jobs: Option<usize>,
+    cache: Option<bool>,
+    cache_dir: Option<PathBuf>,
+    record: Option<bool>,
+    history_file: Option<PathBuf>,
+    key_file: Option<PathBuf>,
+    regression_threshold: Option<f64>,
+    regression_baseline: Option<String>,
+    timeout: Option<toml::Value>,
+    #[serde(default)]
+    timeouts: BTreeMap<String, toml::Value>,
+    #[serde(default)]
+    params: BTreeMap<String, toml::Value>,
+    #[serde(default)]
+    external: BTreeMap<String, toml::Value>,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config {
+            input_dir: Setting::default(PathBuf::from("inputs")),
+            format: Setting::default(OutputFormat::Text),
+            jobs: Setting::default(1),
+            cache: Setting::default(false),
+            cache_dir: Setting::default(cache::default_dir()),
+            record: Setting::default(false),
+            history_file: Setting::default(PathBuf::from(history::HISTORY_FILE)),
+            key_file: Setting::default(vault::default_key_file()),
+            regression_threshold: Setting::default(10.0),
+            regression_baseline: Setting::default(Baseline::Previous),
+            timeout: Setting::default(Duration::ZERO),
+            timeouts: BTreeMap::new(),
+            params: BTreeMap::new(),
+            external: BTreeMap::new(),
+        }
+    }
+}
+
+impl Config {
+    /// Load the defaults and apply the project and user configuration files, if they exist.
+    pub fn load() -> io::Result<Self> {
+        let mut config = Config::default();
+
+        let mut files = vec![PathBuf::from(PROJECT_CONFIG)];
+        if let Some(dir) = user_config_dir() {
+            files.push(dir.join(PROJECT_CONFIG));
+        }
+
+        for path in files.into_iter().filter(|p| p.is_file()) {
+            config.apply_file(&path)?;
+        }
+
+        Ok(config)
+    }
+
+    /// Apply the settings of a single configuration file on top of the current ones.
+    pub fn apply_file(&mut self, path: &Path) -> io::Result<()> {
+        let invalid = |e: &dyn fmt::Display| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("Invalid config file {}: {}", path.display(), e),
+            )
+        };
+
+        let file: ConfigFile =
+            toml::from_str(&fs::read_to_string(path)?).map_err(|e| invalid(&e))?;
+        let source = Source::File(path.to_path_buf());
+
+        // relative paths are relative to the directory of the file, not to the working directory
+        let dir = path.parent().unwrap_or(Path::new(""));
+
+        if let Some(input_dir) = file.input_dir {
+            self.input_dir = Setting::new(dir.join(input_dir), source.clone());
+        }
+        if let Some(format) = file.format {
+            self.format = Setting::new(format.parse().map_err(|e| invalid(&e))?, source.clone());
+        }
+        if let Some(jobs) = file.jobs {
+            self.jobs = Setting::new(jobs.max(1), source.clone());
+        }
+        if let Some(cache) = file.cache {
+            self.cache = Setting::new(cache, source.clone());
+        }
+        if let Some(cache_dir) = file.cache_dir {
+            self.cache_dir = Setting::new(dir.join(cache_dir), source.clone());
+        }
+        if let Some(record) = file.record {
+            self.record = Setting::new(record, source.clone());
+        }
+        if let Some(history_file) = file.history_file {
+            self.history_file = Setting::new(dir.join(history_file), source.clone());
+        }
+        if let Some(key_file) = file.key_file {
+            self.key_file = Setting::new(dir.join(key_file), source.clone());
+        }
+        if let Some(threshold) = file.regression_threshold {
+            self.regression_threshold = Setting::new(threshold, source.clone());
+        }
+        if let Some(baseline) = file.regression_baseline {
+            self.regression_baseline =
+                Setting::new(baseline.parse().map_err(|e| invalid(&e))?, source.clone());
+        }
+        if let Some(timeout) = file.timeout {
+            let timeout = seconds(&timeout).ok_or_else(|| invalid(&"invalid timeout"))?;
+            self.timeout = Setting::new(timeout, source.clone());
+        }
+
+        let mut timeouts: Vec<(String, toml::Value)> = file.timeouts.into_iter().collect();
+        while let Some((key, value)) = timeouts.pop() {
+            // [timeouts.day12] part2 = 5 is the same as "day12.part2" = 5
+            if let toml::Value::Table(table) = value {
+                timeouts.extend(
+                    table
+                        .into_iter()
+                        .map(|(k, v)| (format!("{}.{}", key, k), v)),
+                );
+                continue;
+            }
+
+            let day_part = parse_day_part_key(&key)
+                .ok_or_else(|| invalid(&format!("invalid timeout key \"{}\"", key)))?;
+            let timeout = seconds(&value)
+                .ok_or_else(|| invalid(&format!("invalid timeout for \"{}\"", key)))?;
+            self.timeouts
+                .insert(day_part, Setting::new(timeout, source.clone()));
+        }
+
+        let mut params: Vec<(String, toml::Value)> = file.params.into_iter().collect();
+        while let Some((key, value)) = params.pop() {
+            let value = match value {
+                // nested tables such as [params.day07] are flattened into dotted keys
+                toml::Value::Table(table) => {
+                    params.extend(
+                        table
+                            .into_iter()
+                            .map(|(k, v)| (format!("{}.{}", key, k), v)),
+                    );
+                    continue;
+                }
+                toml::Value::String(s) => s,
+                v => v.to_string(),
+            };
+            self.params.insert(key, Setting::new(value, source.clone()));
+        }
+
+        // [external.python] day01 = "python3 py/day01.py" declares the variant "python"
+        for (variant, value) in file.external.into_iter() {
+            let mut commands: Vec<(String, toml::Value)> = match value {
+                toml::Value::Table(table) => table.into_iter().collect(),
+                _ => return Err(invalid(&format!("external.{} is not a table", variant))),
+            };
+
+            while let Some((key, value)) = commands.pop() {
+                let command: Vec<String> = match value {
+                    toml::Value::Table(table) => {
+                        commands.extend(
+                            table
+                                .into_iter()
+                                .map(|(k, v)| (format!("{}.{}", key, k), v)),
+                        );
+                        continue;
+                    }
+                    // a string is split on whitespace only, without any shell quoting, so
+                    // quotes would end up in the arguments: those commands take the list form
+                    toml::Value::String(s) if s.contains(['"', '\'', '\\']) => {
+                        return Err(invalid(&format!(
+                            "the command for \"{}\" in external.{} has quotes or escapes, \
+                             give it as a list of arguments instead",
+                            key, variant
+                        )));
+                    }
+                    toml::Value::String(s) => s.split_whitespace().map(String::from).collect(),
+                    // or a list of arguments, passed as they are
+                    toml::Value::Array(array) => array
+                        .into_iter()
+                        .map(|v| match v {
+                            toml::Value::String(s) => Some(s),
+                            _ => None,
+                        })
+                        .collect::<Option<_>>()
+                        .unwrap_or_default(),
+                    _ => Vec::new(),
+                };
+
+                let (day, part) = parse_day_part_key(&key).ok_or_else(|| {
+                    invalid(&format!("invalid key \"{}\" in external.{}", key, variant))
+                })?;
+                if command.is_empty() {
+                    return Err(invalid(&format!(
+                        "invalid command for \"{}\" in external.{}",
+                        key, variant
+                    )));
+                }
+                self.external.insert(
+                    (variant.clone(), day, part),
+                    Setting::new(command, source.clone()),
+                );
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Set a parameter from a `key=value` pair given on the command line.
+    pub fn set_param(&mut self, pair: &str) -> io::Result<()> {
+        match pair.split_once('=') {
+            Some((key, value)) => {
+                self.params.insert(
+                    key.trim().to_string(),
+                    Setting::new(value.trim().to_string(), Source::CommandLine),
+                );
+                Ok(())
+            }
+            None => Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("Invalid parameter \"{}\" (expected key=value)", pair),
+            )),
+        }
+    }
+
+    /// Set the time limit of every part from a number of seconds given on the command line.
+    pub fn set_timeout(&mut self, secs: &str) -> io::Result<()> {
+        let timeout = secs
+            .parse::<f64>()
+            .ok()
+            .and_then(|s| Duration::try_from_secs_f64(s).ok())
+            .ok_or_else(|| {
+                io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    format!(
+                        "Invalid timeout \"{}\" (expected a number of seconds)",
+                        secs
+                    ),
+                )
+            })?;
+        self.timeout = Setting::new(timeout, Source::CommandLine);
+
+        Ok(())
+    }
+
+    /// The time limits in the form used by the runner.
+    pub fn time_limits(&self) -> TimeLimits {
+        let mut limits = TimeLimits::new(self.timeout.value);
+        for (&(day, part), setting) in self.timeouts.iter() {
+            limits.set(day, part, setting.value);
+        }
+
+        limits
+    }
+
+    /// The external solvers in the form kept by the registry. A command given for a whole day
+    /// solves both of its parts, unless a part has a command of its own.
+    pub fn external_solvers(&self) -> Vec<Solver> {
+        let mut commands: BTreeMap<(u8, u8, &str), &Vec<String>> = BTreeMap::new();
+        for ((variant, day, part), setting) in self.external.iter() {
+            let parts = match part {
+                0 => vec![1, 2],
+                _ => vec![*part],
+            };
+            for p in parts {
+                if *part != 0 || !self.external.contains_key(&(variant.clone(), *day, p)) {
+                    commands.insert((*day, p, variant.as_str()), &setting.value);
+                }
+            }
+        }
+
+        // the registry keeps solvers for the whole run, so their names and commands are leaked
+        commands
+            .into_iter()
+            .map(|((day, part, variant), command)| {
+                let variant: &'static str = Box::leak(variant.to_string().into_boxed_str());
+                let command: &'static [String] = Box::leak(command.clone().into_boxed_slice());
+                Solver::external(day, part, variant, command)
+            })
+            .collect()
+    }
+
+    /// The parameters in the form passed on to the solvers.
+    pub fn solver_params(&self) -> Params {
+        let mut params = Params::new();
+        for (key, setting) in self.params.iter() {
+            params.insert(key, &setting.value);
+        }
+
+        params
+    }
+
+    /// Render every setting together with its origin, one per line.
+    pub fn show(&self) -> String {
+        let mut lines: Vec<(String, String, &Source)> = vec![
+            (
+                "input_dir".to_string(),
+                format!("{:?}", self.input_dir.value.display().to_string()),
+                &self.input_dir.source,
+            ),
+            (
+                "format".to_string(),
+                format!("{:?}", self.format.value.to_string()),
+                &self.format.source,
+            ),
+            (
+                "jobs".to_string(),
+                self.jobs.value.to_string(),
+                &self.jobs.source,
+            ),
+            (
+                "cache".to_string(),
+                self.cache.value.to_string(),
+                &self.cache.source,
+            ),
+            (
+                "cache_dir".to_string(),
+                format!("{:?}", self.cache_dir.value.display().to_string()),
+                &self.cache_dir.source,
+            ),
+            (
+                "record".to_string(),
+                self.record.value.to_string(),
+                &self.record.source,
+            ),
+            (
+                "history_file".to_string(),
+                format!("{:?}", self.history_file.value.display().to_string()),
+                &self.history_file.source,
+            ),
+            (
+                "key_file".to_string(),
+                format!("{:?}", self.key_file.value.display().to_string()),
+                &self.key_file.source,
+            ),
+            (
+                "regression_threshold".to_string(),
+                self.regression_threshold.value.to_string(),
+                &self.regression_threshold.source,
+            ),
+            (
+                "regression_baseline".to_string(),
+                format!("{:?}", self.regression_baseline.value.to_string()),
+                &self.regression_baseline.source,
+            ),
+            (
+                "timeout".to_string(),
+                self.timeout.value.as_secs_f64().to_string(),
+                &self.timeout.source,
+            ),
+        ];
+
+        for (&(day, part), setting) in self.timeouts.iter() {
+            let key = match part {
+                0 => format!("timeouts.day{:0>2}", day),
+                _ => format!("timeouts.day{:0>2}.part{}", day, part),
+            };
+            lines.push((
+                key,
+                setting.value.as_secs_f64().to_string(),
+                &setting.source,
+            ));
+        }
+
+        for (key, setting) in self.params.iter() {
+            lines.push((
+                format!("params.{}", key),
+                format!("{:?}", setting.value),
+                &setting.source,
+            ));
+        }
+
+        for ((variant, day, part), setting) in self.external.iter() {
+            let key = match part {
+                0 => format!("external.{}.day{:0>2}", variant, day),
+                _ => format!("external.{}.day{:0>2}.part{}", variant, day, part),
+            };
+            lines.push((key, format!("{:?}", setting.value), &setting.source));
+        }
+
+        let width = lines
+            .iter()
+            .map(|(k, v, _)| k.len() + v.len())
+            .max()
+            .unwrap_or(0)
+            + 3;
+
+        let mut s = String::new();
+        for (key, value, source) in lines.into_iter() {
+            let setting = format!("{} = {}", key, value);
+            s.push_str(&format!(
+                "{:<width$}  # {}\n",
+                setting,
+                source,
+                width = width
+            ));
+        }
+
+        s
+    }
+}
+
+/// A duration given as a (possibly fractional) number of seconds.
+fn seconds(value: &toml::Value) -> Option<Duration> {
+    let secs = match value {
+        toml::Value::Integer(i) => *i as f64,
+        toml::Value::Float(f) => *f,
+        _ => return None,
+    };
+
+    Duration::try_from_secs_f64(secs).ok()
+}
+
+/// Parse a key of a time limit or an external solver, either `dayNN` or `dayNN.partN`, into a
+/// (day, part) pair with part 0 standing for the whole day.
+fn parse_day_part_key(key: &str) -> Option<(u8, u8)> {
+    let (day, part) = match key.split_once('.') {
+        Some((day, part)) => (day, part.strip_prefix("part")?.parse::<u8>().ok()?),
+        None => (key, 0),
+    };
+
+    Some((day.strip_prefix("day")?.parse::<u8>().ok()?, part))
+}
+
+/// The directory holding the user-level configuration, following the XDG convention.
+pub fn user_config_dir() -> Option<PathBuf> {
+    let base = match env::var_os("XDG_CONFIG_HOME") {
+        Some(dir) if !dir.is_empty() => PathBuf::from(dir),
+        _ => PathBuf::from(env::var_os("HOME")?).join(".config"),
+    };
+
+    Some(base.join("aoc2022"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A directory of its own for each test, with an `aoc.toml` holding `content`.
+    fn config_file(name: &str, content: &str) -> PathBuf {
+        let dir = env::temp_dir().join(format!("aoc2022-config-{}-{}", name, std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join(PROJECT_CONFIG);
+        fs::write(&path, content).unwrap();
+
+        path
+    }
+
+    #[test]
+    fn later_layers_override_earlier_ones() {
+        let project = config_file("project", "jobs = 4\nformat = \"json\"\ncache = true\n");
+        let user = config_file("user", "jobs = 8\ntimeout = 2.5\n");
+
+        let mut config = Config::default();
+        config.apply_file(&project).unwrap();
+        config.apply_file(&user).unwrap();
+
+        assert_eq!(config.jobs.value, 8);
+        assert_eq!(config.jobs.source, Source::File(user.clone()));
+        assert_eq!(config.format.value, OutputFormat::Json);
+        assert_eq!(config.format.source, Source::File(project.clone()));
+        assert!(config.cache.value);
+        assert_eq!(config.record.source, Source::Default);
+
+        config.set_timeout("1").unwrap();
+        config.set_param("day07.threshold=5").unwrap();
+        assert_eq!(config.timeout.value, Duration::from_secs(1));
+        assert_eq!(config.timeout.source, Source::CommandLine);
+        assert_eq!(config.params["day07.threshold"].value, "5");
+        assert_eq!(config.params["day07.threshold"].source, Source::CommandLine);
+    }
+
+    #[test]
+    fn relative_paths_are_relative_to_their_file() {
+        let path = config_file(
+            "paths",
+            "input_dir = \"puzzles\"\ncache_dir = \"/var/cache/aoc\"\nkey_file = \"../input.key\"\n",
+        );
+        let dir = path.parent().unwrap();
+
+        let mut config = Config::default();
+        config.apply_file(&path).unwrap();
+
+        assert_eq!(config.input_dir.value, dir.join("puzzles"));
+        assert_eq!(config.cache_dir.value, PathBuf::from("/var/cache/aoc"));
+        assert_eq!(config.key_file.value, dir.join("../input.key"));
+        // the defaults stay relative to the working directory
+        assert_eq!(
+            config.history_file.value,
+            PathBuf::from(history::HISTORY_FILE)
+        );
+    }
+
+    #[test]
+    fn tables_are_flattened_into_dotted_keys() {
+        let path = config_file(
+            "tables",
+            "[timeouts]\nday12 = 30\nday11.part2 = 0.5\n\n\
+             [params.day07]\nthreshold = 100\n\n\
+             [external.python]\nday01 = \"python3 day01.py\"\nday02.part1 = [\"python3\", \"my day02.py\"]\n",
+        );
+
+        let mut config = Config::default();
+        config.apply_file(&path).unwrap();
+
+        assert_eq!(config.timeouts[&(12, 0)].value, Duration::from_secs(30));
+        assert_eq!(config.timeouts[&(11, 2)].value, Duration::from_millis(500));
+        assert_eq!(config.params["day07.threshold"].value, "100");
+        assert_eq!(
+            config.external[&("python".to_string(), 1, 0)].value,
+            vec!["python3", "day01.py"]
+        );
+        assert_eq!(
+            config.external[&("python".to_string(), 2, 1)].value,
+            vec!["python3", "my day02.py"]
+        );
+    }
+
+    #[test]
+    fn invalid_files_are_rejected() {
+        for (name, content) in [
+            ("unknown-key", "colour = \"red\"\n"),
+            ("bad-format", "format = \"xml\"\n"),
+            ("bad-timeout-key", "[timeouts]\nday12.p2 = 3\n"),
+            ("bad-timeout", "timeout = \"soon\"\n"),
+            (
+                "quoted-command",
+                "[external.py]\nday01 = \"python3 'my day01.py'\"\n",
+            ),
+            ("empty-command", "[external.py]\nday01 = []\n"),
+        ] {
+            let path = config_file(name, content);
+            let e = Config::default().apply_file(&path).unwrap_err();
+            assert_eq!(e.kind(), io::ErrorKind::InvalidData, "{}", name);
+        }
+    }
+
+    #[test]
+    fn day_part_keys() {
+        assert_eq!(parse_day_part_key("day07"), Some((7, 0)));
+        assert_eq!(parse_day_part_key("day11.part2"), Some((11, 2)));
+        assert_eq!(parse_day_part_key("day11.2"), None);
+        assert_eq!(parse_day_part_key("d11"), None);
+    }
+}
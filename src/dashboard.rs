@@ -0,0 +1,504 @@
+use crossterm::cursor::{Hide, MoveTo, Show};
+use crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use crossterm::style::{Attribute, Print, SetAttribute};
+use crossterm::terminal::{self, Clear, ClearType, EnterAlternateScreen, LeaveAlternateScreen};
+use crossterm::{execute, queue};
+use std::io;
+use std::io::prelude::*;
+use std::io::IsTerminal;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, Receiver};
+use std::thread;
+use std::time::Duration;
+
+use crate::answers::{Answers, Verdict};
+use crate::history::{self, Baseline, Trend};
+use crate::input_sets;
+use crate::registry;
+use crate::runner::{self, DayRuns, Run, RunOptions};
+use crate::status::{self, PartStatus};
+
+/// Width of the list of days on the left of the screen.
+const LIST_WIDTH: usize = 52;
+
+/// How often the screen is redrawn while waiting for keys, e.g. to notice a finished run.
+const TICK: Duration = Duration::from_millis(100);
+
+const HELP: &str = "↑↓ select  enter run  v variant  i input  p parameter  e explain  \
+                    pgup/pgdn scroll  q quit";
+
+/// What can be chosen for a single day, and the outcome of its last run.
+struct DayState {
+    day: u8,
+    status: [PartStatus; 2],
+    /// The variants of the day, where `None` runs the default variant of each part.
+    variants: Vec<Option<String>>,
+    variant: usize,
+    /// The inputs of the day, where `None` runs all of them.
+    input_sets: Vec<Option<String>>,
+    input_set: usize,
+    results: Option<Vec<DayRuns>>,
+    /// Latest recorded time of each part, for days not run yet in this session.
+    recorded: [Option<u64>; 2],
+}
+
+/// The state of the dashboard, which is redrawn from scratch after every key press.
+pub struct Dashboard {
+    input_dir: PathBuf,
+    options: RunOptions,
+    answers: Answers,
+    days: Vec<DayState>,
+    selected: usize,
+    explain: bool,
+    scroll: usize,
+    /// The day being run and where its results will arrive.
+    running: Option<(usize, Receiver<Vec<DayRuns>>)>,
+    /// The parameter being typed, as `key=value`.
+    editing: Option<String>,
+    message: Option<String>,
+    /// Size of the terminal when last drawn, to clear the screen when it changes.
+    size: (u16, u16),
+}
+
+impl Dashboard {
+    /// A dashboard of every registered day. Runs use `options`, apart from the part (always both),
+    /// the variant, the input and explain mode, which are chosen per run.
+    pub fn new(input_dir: &Path, options: RunOptions, history_file: &Path) -> io::Result<Self> {
+        let answers = Answers::load(input_dir)?;
+        let calendar = status::calendar(input_dir, &answers);
+        let records = history::load(history_file).unwrap_or_default();
+        let trends = history::trends(&records, Baseline::Previous, 0.0);
+        let solvers = registry::solvers();
+
+        let days = registry::days()
+            .into_iter()
+            .map(|day| {
+                let mut variants: Vec<Option<String>> = vec![None];
+                for solver in solvers.iter().filter(|s| s.day == day) {
+                    let variant = Some(solver.variant.to_string());
+                    if solver.variant != registry::DEFAULT_VARIANT && !variants.contains(&variant) {
+                        variants.push(variant);
+                    }
+                }
+
+                let mut sets: Vec<Option<String>> = vec![None];
+                if let Ok(found) = input_sets(input_dir, day) {
+                    sets.extend(found.iter().map(|s| Some(s.display_name().to_string())));
+                }
+
+                let recorded = [1, 2].map(|part| {
+                    let default = solvers.iter().find(|s| s.day == day && s.part == part)?;
+                    latest_time(&trends, day, part, default.variant)
+                });
+
+                DayState {
+                    day,
+                    status: calendar
+                        .iter()
+                        .find(|d| d.day == day)
+                        .map(|d| d.parts)
+                        .unwrap_or([PartStatus::Missing; 2]),
+                    variants,
+                    variant: 0,
+                    input_sets: sets,
+                    input_set: 0,
+                    results: None,
+                    recorded,
+                }
+            })
+            .collect();
+
+        Ok(Dashboard {
+            input_dir: input_dir.to_path_buf(),
+            options,
+            answers,
+            days,
+            selected: 0,
+            explain: false,
+            scroll: 0,
+            running: None,
+            editing: None,
+            message: None,
+            size: (0, 0),
+        })
+    }
+
+    /// Take over the terminal until the user quits.
+    pub fn run(mut self) -> io::Result<()> {
+        let mut stdout = io::stdout();
+        if !stdout.is_terminal() {
+            return Err(io::Error::other("The dashboard needs a terminal"));
+        }
+
+        terminal::enable_raw_mode()?;
+        execute!(stdout, EnterAlternateScreen, Hide)?;
+
+        let result = self.event_loop(&mut stdout);
+
+        // restore the terminal even if drawing failed
+        let _ = execute!(stdout, Show, LeaveAlternateScreen);
+        let _ = terminal::disable_raw_mode();
+
+        result
+    }
+
+    fn event_loop(&mut self, stdout: &mut io::Stdout) -> io::Result<()> {
+        loop {
+            self.receive();
+            self.draw(stdout)?;
+
+            if !event::poll(TICK)? {
+                continue;
+            }
+            if let Event::Key(key) = event::read()? {
+                if key.kind != KeyEventKind::Release && !self.key(key) {
+                    return Ok(());
+                }
+            }
+        }
+    }
+
+    /// Pick up the results of a finished run.
+    fn receive(&mut self) {
+        if let Some((index, receiver)) = &self.running {
+            if let Ok(results) = receiver.try_recv() {
+                let state = &mut self.days[*index];
+                status::check_parts(state.day, &mut state.status, &results, &self.answers);
+                state.results = Some(results);
+                self.running = None;
+            }
+        }
+    }
+
+    /// Handle a key press, telling whether the dashboard should keep going.
+    fn key(&mut self, key: KeyEvent) -> bool {
+        if let Some(editing) = &mut self.editing {
+            match key.code {
+                KeyCode::Enter => {
+                    let pair = editing.clone();
+                    self.editing = None;
+                    self.message = Some(self.set_param(&pair));
+                }
+                KeyCode::Esc => self.editing = None,
+                KeyCode::Backspace => {
+                    editing.pop();
+                }
+                KeyCode::Char(c) => editing.push(c),
+                _ => {}
+            }
+            return true;
+        }
+
+        self.message = None;
+        let selected = self.selected;
+        match key.code {
+            KeyCode::Char('q') | KeyCode::Esc => return false,
+            KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => return false,
+            KeyCode::Up | KeyCode::Char('k') => {
+                self.selected = self.selected.saturating_sub(1);
+                self.scroll = 0;
+            }
+            KeyCode::Down | KeyCode::Char('j') => {
+                self.selected = (self.selected + 1).min(self.days.len().saturating_sub(1));
+                self.scroll = 0;
+            }
+            KeyCode::Enter | KeyCode::Char('r') => self.start_run(),
+            KeyCode::Char('v') => {
+                let day = &mut self.days[selected];
+                day.variant = (day.variant + 1) % day.variants.len();
+            }
+            KeyCode::Char('i') => {
+                let day = &mut self.days[selected];
+                day.input_set = (day.input_set + 1) % day.input_sets.len();
+            }
+            KeyCode::Char('e') => self.explain = !self.explain,
+            KeyCode::Char('p') => {
+                self.editing = Some(format!("day{:0>2}.", self.days[selected].day));
+            }
+            KeyCode::PageUp => self.scroll = self.scroll.saturating_sub(10),
+            KeyCode::PageDown => self.scroll += 10,
+            _ => {}
+        }
+
+        true
+    }
+
+    fn set_param(&mut self, pair: &str) -> String {
+        match pair.split_once('=') {
+            Some((key, value)) if !key.trim().is_empty() => {
+                self.options.params.insert(key.trim(), value.trim());
+                format!("Set {} = {}", key.trim(), value.trim())
+            }
+            _ => format!("Invalid parameter \"{}\" (expected key=value)", pair),
+        }
+    }
+
+    fn start_run(&mut self) {
+        if self.running.is_some() {
+            self.message = Some("Wait for the current run to finish".to_string());
+            return;
+        }
+
+        let state = &self.days[self.selected];
+        let options = RunOptions {
+            part: 0,
+            variant: state.variants[state.variant].clone(),
+            input_set: state.input_sets[state.input_set].clone(),
+            explain: self.explain,
+            ..self.options.clone()
+        };
+        let day = state.day;
+        let input_dir = self.input_dir.clone();
+
+        let (sender, receiver) = mpsc::channel();
+        thread::spawn(move || {
+            let _ = sender.send(runner::run_day(day, &input_dir, &options));
+        });
+
+        self.running = Some((self.selected, receiver));
+        self.scroll = 0;
+    }
+
+    fn draw(&mut self, stdout: &mut io::Stdout) -> io::Result<()> {
+        let size = terminal::size()?;
+        if size != self.size {
+            queue!(stdout, Clear(ClearType::All))?;
+            self.size = size;
+        }
+
+        // every line is padded to the full width, so drawing over the previous screen erases it
+        let (cols, rows) = (size.0 as usize, size.1 as usize);
+        let detail_width = cols.saturating_sub(LIST_WIDTH + 3);
+
+        let list = self.list_lines();
+        let details = self.detail_lines();
+        let details = details.iter().skip(self.scroll);
+
+        // the days on the left, the selected one in reverse video, and its details on the right
+        let body = rows.saturating_sub(1);
+        let mut details = details.take(body);
+        for y in 0..body {
+            queue!(stdout, MoveTo(0, y as u16))?;
+
+            let line = list.get(y).map(String::as_str).unwrap_or("");
+            if y >= 2 && y - 2 == self.selected {
+                queue!(
+                    stdout,
+                    SetAttribute(Attribute::Reverse),
+                    Print(fit(line, LIST_WIDTH)),
+                    SetAttribute(Attribute::Reset)
+                )?;
+            } else {
+                queue!(stdout, Print(fit(line, LIST_WIDTH)))?;
+            }
+
+            if let Some(detail) = details.next() {
+                queue!(stdout, Print(" │ "), Print(fit(detail, detail_width)))?;
+            } else {
+                queue!(stdout, Print(" │"))?;
+            }
+        }
+
+        let footer = match (&self.editing, &self.message) {
+            (Some(editing), _) => format!("parameter (key=value, enter to set): {}", editing),
+            (None, Some(message)) => message.clone(),
+            (None, None) => HELP.to_string(),
+        };
+        queue!(
+            stdout,
+            MoveTo(0, rows.saturating_sub(1) as u16),
+            SetAttribute(Attribute::Reverse),
+            Print(fit(&footer, cols)),
+            SetAttribute(Attribute::Reset)
+        )?;
+
+        stdout.flush()
+    }
+
+    fn list_lines(&self) -> Vec<String> {
+        let mut lines = vec![
+            format!(
+                "{:>3}  {:<2}  {:<14}  {:<14}  {:>10}",
+                "Day", "", "Part 1", "Part 2", "Time"
+            ),
+            String::new(),
+        ];
+
+        for (i, state) in self.days.iter().enumerate() {
+            let stars: String = state
+                .status
+                .iter()
+                .map(|&p| if p == PartStatus::Verified { '*' } else { ' ' })
+                .collect();
+
+            let runs: Vec<&Run> = match &state.results {
+                Some(results) => results
+                    .iter()
+                    .filter_map(|r| r.runs.as_ref().ok())
+                    .flatten()
+                    .collect(),
+                None => Vec::new(),
+            };
+            let part = |part: u8| match runs.iter().find(|r| r.part == part) {
+                Some(run) => short_answer(run),
+                None => match state.status[part as usize - 1] {
+                    PartStatus::Missing => "-".to_string(),
+                    status => format!("({})", status),
+                },
+            };
+
+            let time = match (runs.is_empty(), &self.running) {
+                (_, Some((index, _))) if *index == i => "running".to_string(),
+                (false, _) => {
+                    let total: u128 = runs.iter().map(|r| r.time.as_micros()).sum();
+                    format!("{} µs", total)
+                }
+                (true, _) => match state.recorded {
+                    [None, None] => String::new(),
+                    [a, b] => format!("{} µs", a.unwrap_or(0) + b.unwrap_or(0)),
+                },
+            };
+
+            lines.push(format!(
+                "{:>3}  {:<2}  {:<14}  {:<14}  {:>10}",
+                state.day,
+                stars,
+                fit(&part(1), 14),
+                fit(&part(2), 14),
+                time
+            ));
+        }
+
+        lines
+    }
+
+    fn detail_lines(&self) -> Vec<String> {
+        let state = match self.days.get(self.selected) {
+            Some(state) => state,
+            None => return vec!["No days are registered".to_string()],
+        };
+
+        let mut lines = vec![
+            format!("# ---- DAY {:0>2} ---- #", state.day),
+            format!(
+                "Variant: {}   Input: {}   Explain: {}",
+                state.variants[state.variant]
+                    .as_deref()
+                    .unwrap_or("default"),
+                state.input_sets[state.input_set]
+                    .as_deref()
+                    .unwrap_or("all"),
+                if self.explain { "on" } else { "off" }
+            ),
+        ];
+
+        let prefix = format!("day{:0>2}.", state.day);
+        let params: Vec<String> = self
+            .options
+            .params
+            .iter()
+            .filter(|(k, _)| k.starts_with(&prefix))
+            .map(|(k, v)| format!("{}={}", k, v))
+            .collect();
+        if !params.is_empty() {
+            lines.push(format!("Parameters: {}", params.join(" ")));
+        }
+        lines.push(String::new());
+
+        let results = match (&state.results, &self.running) {
+            (_, Some((index, _))) if *index == self.selected => {
+                lines.push("Running...".to_string());
+                return lines;
+            }
+            (Some(results), _) => results,
+            (None, _) => {
+                lines.push("Press enter to run the day".to_string());
+                return lines;
+            }
+        };
+
+        for day_runs in results.iter() {
+            if let Some(name) = &day_runs.input_set {
+                lines.push(format!("Input {}:", name));
+            }
+
+            let runs = match &day_runs.runs {
+                Ok(runs) => runs,
+                Err(e) => {
+                    lines.push(format!("error: {}", e));
+                    continue;
+                }
+            };
+
+            for run in runs.iter() {
+                let verdict = match &run.answer {
+                    Ok(answer) => match self.answers.verdict(
+                        run.day,
+                        day_runs.input_set.as_deref(),
+                        run.part,
+                        answer.as_deref(),
+                    ) {
+                        Verdict::Correct => "  (correct)".to_string(),
+                        Verdict::Wrong(expected) => format!("  (WRONG, expected {})", expected),
+                        Verdict::Unknown => String::new(),
+                    },
+                    Err(_) => String::new(),
+                };
+
+                let answer = match &run.answer {
+                    Ok(Some(answer)) => answer.clone(),
+                    Ok(None) => "-".to_string(),
+                    Err(_) if runner::timed_out(run) => "TIMEOUT".to_string(),
+                    Err(e) => format!("error: {}", e),
+                };
+
+                let mut answer_lines = answer.lines().filter(|l| !l.is_empty());
+                lines.push(format!(
+                    "Part {} ({}, {} µs): {}{}",
+                    run.part,
+                    run.variant,
+                    run.time.as_micros(),
+                    match answer.contains('\n') {
+                        true => "",
+                        false => answer_lines.next().unwrap_or(""),
+                    },
+                    verdict
+                ));
+                // multi-line answers, such as the screen of day 10, are drawn below
+                lines.extend(answer_lines.map(|l| format!("  {}", l)));
+                lines.extend(run.explanation.iter().map(|l| format!("  {}", l)));
+            }
+            lines.push(String::new());
+        }
+
+        lines
+    }
+}
+
+/// The latest recorded time of the default input of a part, in µs.
+fn latest_time(trends: &[Trend], day: u8, part: u8, variant: &str) -> Option<u64> {
+    trends
+        .iter()
+        .find(|t| t.day == day && t.part == part && t.input_set.is_none() && t.variant == variant)
+        .map(|t| t.latest_us)
+}
+
+/// The answer of a run as shown in the list, where multi-line answers do not fit.
+fn short_answer(run: &Run) -> String {
+    match &run.answer {
+        Ok(Some(answer)) if answer.trim().contains('\n') => "(see output)".to_string(),
+        Ok(Some(answer)) => answer.trim().to_string(),
+        Ok(None) => "-".to_string(),
+        Err(_) if runner::timed_out(run) => "TIMEOUT".to_string(),
+        Err(_) => "error".to_string(),
+    }
+}
+
+/// Pad or cut a line to exactly `width` characters.
+fn fit(line: &str, width: usize) -> String {
+    let mut s: String = line.chars().take(width).collect();
+    let len = s.chars().count();
+    s.extend(std::iter::repeat_n(' ', width - len));
+
+    s
+}
@@ -1,96 +1,716 @@
+use crate::parsing::{self, parse_all};
+use crate::registry::{Context, Solver};
+use std::cmp::Reverse;
+use std::collections::{BTreeMap, BinaryHeap};
+use std::fmt;
 use std::io;
 use std::io::prelude::*;
 
 #[derive(Debug, PartialOrd, PartialEq)]
 pub struct Elf {
+    /// The position of the elf in the list, starting at 1.
+    pub id: usize,
     pub inventory: Vec<i32>,
 }
 
-pub fn solve(input: impl BufRead, part: u8) -> io::Result<()> {
-    let elves = parse(input)?;
+impl Elf {
+    /// The calories of the whole inventory, summed in an `i64` so that no inventory overflows.
+    pub fn total(&self) -> i64 {
+        self.inventory.iter().map(|&c| c as i64).sum()
+    }
 
-    let now = std::time::Instant::now();
-    let solution = match part {
-        0 => (part_1(&elves), part_2(&elves)),
-        1 => (part_1(&elves), None),
-        2 => (None, part_2(&elves)),
-        _ => unimplemented!(),
-    };
-    let time = now.elapsed().as_micros();
+    pub fn largest_item(&self) -> Option<i32> {
+        self.inventory.iter().copied().max()
+    }
+}
+
+pub fn solvers() -> Vec<Solver> {
+    vec![
+        Solver::new(1, 1, |input, ctx| {
+            let elves = parse(input.as_bytes())?;
+            explain_top(&elves, 1, ctx);
+            if ctx.explaining() {
+                if let Some(statistics) = Statistics::new(&elves) {
+                    statistics
+                        .to_string()
+                        .lines()
+                        .for_each(|line| ctx.explain(|| line.to_string()));
+                }
+                if ctx.params.get("day01.rebalance", false)? {
+                    rebalance(&elves)
+                        .to_string()
+                        .lines()
+                        .for_each(|line| ctx.explain(|| line.to_string()));
+                }
+            }
+
+            Ok(part_1(&elves).map(|x| x.to_string()))
+        }),
+        Solver::new(1, 2, |input, ctx| {
+            let elves = parse(input.as_bytes())?;
+            let n = ctx.params.get("day01.top", 3)?;
+            explain_top(&elves, n, ctx);
+
+            Ok(part_2(&elves, n).map(|x| x.to_string()))
+        }),
+    ]
+}
+
+pub fn parse(input: impl BufRead) -> io::Result<Vec<Elf>> {
+    // the inventories of the elves are separated by blank lines
+    parse_all(input, |cursor| {
+        // the calories of all the elves together fit in an i32, so that no load of a rebalancing
+        // plan overflows
+        let mut calories: i32 = 0;
+
+        cursor
+            .blocks()
+            .into_iter()
+            .enumerate()
+            .map(|(i, mut block)| {
+                let inventory = block
+                    .lines()
+                    .into_iter()
+                    .map(|line| {
+                        line.all(|l| {
+                            let start = *l;
+                            let item = l.integer::<i32>()?;
+                            if item < 0 {
+                                return Err(start.error("an item cannot have negative calories"));
+                            }
+                            calories = calories.checked_add(item).ok_or_else(|| {
+                                start.error(format!(
+                                    "the calories of all the elves add up to more than {}",
+                                    i32::MAX
+                                ))
+                            })?;
+
+                            Ok(item)
+                        })
+                    })
+                    .collect::<parsing::Result<Vec<i32>>>()?;
+
+                Ok(Elf {
+                    id: i + 1,
+                    inventory,
+                })
+            })
+            .collect()
+    })
+}
+
+/// The `n` elves carrying the most calories, the most first and the first in the list first
+/// among equals. A single pass keeps the best `n` so far in a min-heap.
+pub fn top(elves: &[Elf], n: usize) -> Vec<&Elf> {
+    let mut heap: BinaryHeap<Reverse<(i64, Reverse<usize>)>> = BinaryHeap::with_capacity(n + 1);
+
+    for (i, elf) in elves.iter().enumerate() {
+        heap.push(Reverse((elf.total(), Reverse(i))));
+        if heap.len() > n {
+            heap.pop();
+        }
+    }
+
+    heap.into_sorted_vec()
+        .into_iter()
+        .map(|Reverse((_, Reverse(i)))| &elves[i])
+        .collect()
+}
+
+fn part_1(elves: &[Elf]) -> Option<i64> {
+    /* Find the most calories carried! */
+    Some(top(elves, 1).iter().map(|elf| elf.total()).sum())
+}
+
+fn part_2(elves: &[Elf], n: usize) -> Option<i64> {
+    /* Find the top three elves carrying the most calories! */
+    Some(top(elves, n).iter().map(|elf| elf.total()).sum())
+}
+
+fn explain_top(elves: &[Elf], n: usize, ctx: &Context) {
+    if !ctx.explaining() {
+        return;
+    }
+
+    for elf in top(elves, n) {
+        ctx.explain(|| format!("Elf {} carries {} calories", elf.id, elf.total()));
+    }
+}
+
+/// One bar of the histogram of the totals: the elves carrying `start..=end` calories.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Bin {
+    pub start: i64,
+    pub end: i64,
+    pub count: usize,
+}
 
-    match solution.0 {
-        Some(x) => println!("Part 1: {}", x),
-        None => println!(),
+/// A summary of the calories carried by all the elves.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Statistics {
+    pub count: usize,
+    pub mean: f64,
+    pub median: f64,
+    /// The 25th, 75th and 90th percentiles of the totals, by the nearest-rank method.
+    pub percentiles: Vec<(u8, i64)>,
+    pub histogram: Vec<Bin>,
+    /// The single most caloric item, and the elf carrying it.
+    pub largest_item: Option<(usize, i32)>,
+}
+
+const PERCENTILES: [u8; 3] = [25, 75, 90];
+const HISTOGRAM_BINS: usize = 8;
+
+impl Statistics {
+    /// The statistics of the elves, or `None` if there are none.
+    pub fn new(elves: &[Elf]) -> Option<Self> {
+        let mut totals: Vec<i64> = elves.iter().map(|elf| elf.total()).collect();
+        totals.sort_unstable();
+
+        let count = totals.len();
+        let (&min, &max) = (totals.first()?, totals.last()?);
+
+        let mean = totals.iter().map(|&t| t as f64).sum::<f64>() / count as f64;
+        let median = match count % 2 {
+            0 => (totals[count / 2 - 1] as f64 + totals[count / 2] as f64) / 2.0,
+            _ => totals[count / 2] as f64,
+        };
+        let percentiles = PERCENTILES
+            .iter()
+            .map(|&p| {
+                let rank = (p as usize * count).div_ceil(100).max(1);
+                (p, totals[rank - 1])
+            })
+            .collect();
+
+        // bins of equal width covering every total, the last one possibly narrower; totals of
+        // `i32` items stay far enough from the bounds of an `i64` for none of this to overflow
+        let width = (max - min) / HISTOGRAM_BINS as i64 + 1;
+        let mut histogram: Vec<Bin> = (0..HISTOGRAM_BINS as i64)
+            .map(|i| min + i * width)
+            .take_while(|&start| start <= max)
+            .map(|start| Bin {
+                start,
+                end: (start + width - 1).min(max),
+                count: 0,
+            })
+            .collect();
+        for &total in totals.iter() {
+            histogram[((total - min) / width) as usize].count += 1;
+        }
+
+        let largest_item = elves
+            .iter()
+            .filter_map(|elf| Some((elf.id, elf.largest_item()?)))
+            .max_by_key(|&(id, item)| (item, Reverse(id)));
+
+        Some(Statistics {
+            count,
+            mean,
+            median,
+            percentiles,
+            histogram,
+            largest_item,
+        })
+    }
+}
+
+impl fmt::Display for Statistics {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        const BAR_WIDTH: usize = 40;
+
+        writeln!(f, "{} elves", self.count)?;
+        writeln!(f, "Mean: {:.1} calories", self.mean)?;
+        writeln!(f, "Median: {:.1} calories", self.median)?;
+        for (p, total) in self.percentiles.iter() {
+            writeln!(f, "{}th percentile: {} calories", p, total)?;
+        }
+        if let Some((id, item)) = self.largest_item {
+            writeln!(f, "Largest item: {} calories, carried by elf {}", item, id)?;
+        }
+
+        let most = self.histogram.iter().map(|b| b.count).max().unwrap_or(0);
+        let label = |b: &Bin| format!("{}-{}", b.start, b.end);
+        let label_width = self.histogram.iter().map(|b| label(b).len()).max();
+        for bin in self.histogram.iter() {
+            writeln!(
+                f,
+                "{:>width$} | {} {}",
+                label(bin),
+                "#".repeat((bin.count * BAR_WIDTH).div_ceil(most.max(1))),
+                bin.count,
+                width = label_width.unwrap_or(0)
+            )?;
+        }
+
+        Ok(())
     }
-    match solution.1 {
-        Some(x) => println!("Part 2: {}", x),
-        None => println!(),
+}
+
+/// Instances with at most this many items are rebalanced exactly, larger ones heuristically.
+pub const EXACT_ITEMS: usize = 16;
+
+/// A food item handed over from one elf to another.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Transfer {
+    /// The elf handing the item over, and the position of the item in its inventory as parsed,
+    /// both starting at 1.
+    pub from: usize,
+    pub item: usize,
+    pub calories: i32,
+    pub to: usize,
+}
+
+/// How the redistribution of a plan is searched for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Method {
+    /// Branch and bound over every partition of the items, which only suits a few items.
+    Exact,
+    /// The longest processing time heuristic: the largest item first, to the least loaded elf.
+    Heuristic,
+}
+
+/// A redistribution of the items among the elves that makes the most calories any elf carries
+/// as small as possible (multiway number partitioning).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Plan {
+    /// The most calories carried by an elf before and after.
+    pub before: i32,
+    pub after: i32,
+    /// No elf can carry fewer than this, whatever the plan: the larger of the largest item and
+    /// the average total.
+    pub lower_bound: i32,
+    /// Whether the plan is known to be optimal: found by the exact solver, or reaching the lower
+    /// bound.
+    pub optimal: bool,
+    pub transfers: Vec<Transfer>,
+}
+
+/// Plan the redistribution of the items, exactly when there are few of them and heuristically
+/// otherwise.
+pub fn rebalance(elves: &[Elf]) -> Plan {
+    let items: usize = elves.iter().map(|elf| elf.inventory.len()).sum();
+    let method = match items <= EXACT_ITEMS {
+        true => Method::Exact,
+        false => Method::Heuristic,
+    };
+
+    rebalance_with(elves, method)
+}
+
+/// Plan the redistribution of the items with the given method. The exact search relies on loads
+/// only growing, so with a negative item it falls back to the heuristic. Of the plans reaching the
+/// same maximum, the one moving the fewest items is kept.
+pub fn rebalance_with(elves: &[Elf], method: Method) -> Plan {
+    // every item with the indices of the elf carrying it and of the item in its inventory, the
+    // largest first
+    let mut items: Vec<(usize, usize, i32)> = elves
+        .iter()
+        .enumerate()
+        .flat_map(|(e, elf)| {
+            elf.inventory
+                .iter()
+                .enumerate()
+                .map(move |(i, &c)| (e, i, c))
+        })
+        .collect();
+    items.sort_by_key(|&(e, i, c)| (Reverse(c), e, i));
+    let calories: Vec<i32> = items.iter().map(|&(_, _, c)| c).collect();
+
+    // loads are kept in an i32, which every total of a parsed input fits in
+    let before = elves
+        .iter()
+        .map(|elf| elf.total() as i32)
+        .max()
+        .unwrap_or(0);
+    let lower_bound = lower_bound(&calories, elves.len());
+
+    let greedy = longest_processing_time(&calories, elves.len());
+    let exact = match method == Method::Exact && calories.iter().all(|&c| c >= 0) {
+        true => Some(branch_and_bound(
+            &calories,
+            elves.len(),
+            greedy.clone(),
+            lower_bound,
+        )),
+        false => None,
+    };
+    let bins = exact.clone().unwrap_or(greedy);
+    let after = max_load(&calories, &bins, elves.len());
+
+    let optimal = exact.is_some() || after == lower_bound;
+
+    // nothing to gain from moving anything around
+    if before <= after {
+        return Plan {
+            before,
+            after: before,
+            lower_bound,
+            optimal: optimal || before == lower_bound,
+            transfers: Vec::new(),
+        };
     }
 
-    println!("Time elapsed: {} µs", time);
+    let owners = assign_owners(&items, &bins, elves.len());
+    let mut transfers: Vec<Transfer> = items
+        .iter()
+        .zip(bins.iter())
+        .filter(|&(&(from, _, _), &bin)| owners[bin] != from)
+        .map(|(&(from, item, calories), &bin)| Transfer {
+            from: elves[from].id,
+            item: item + 1,
+            calories,
+            to: elves[owners[bin]].id,
+        })
+        .collect();
 
-    Ok(())
+    // the partition is built from scratch, while a few moves off the heaviest elves may do
+    if let Some(repair) = repair(elves, after).filter(|r| r.len() < transfers.len()) {
+        transfers = repair;
+    }
+
+    Plan {
+        before,
+        after,
+        lower_bound,
+        optimal,
+        transfers,
+    }
 }
 
-fn parse(input: impl BufRead) -> io::Result<Vec<Elf>> {
-    let mut elves: Vec<Elf> = Vec::new();
-    elves.push(Elf {
-        inventory: Vec::new(),
-    });
+/// Bring every elf down to at most `target` calories by handing its largest items that fit to
+/// the least loaded elves, or `None` if some elf cannot be brought down that way.
+fn repair(elves: &[Elf], target: i32) -> Option<Vec<Transfer>> {
+    let mut loads: Vec<i32> = elves.iter().map(|elf| elf.total() as i32).collect();
+    let mut transfers: Vec<Transfer> = Vec::new();
+
+    for (e, elf) in elves.iter().enumerate() {
+        let mut items: Vec<(usize, i32)> = elf.inventory.iter().copied().enumerate().collect();
+        items.sort_by_key(|&(i, c)| (Reverse(c), i));
 
-    let mut i: usize = 0;
+        for (item, calories) in items.into_iter() {
+            if loads[e] <= target {
+                break;
+            }
 
-    for line in input.lines() {
-        let s: String = line?;
+            let (to, _) = loads
+                .iter()
+                .enumerate()
+                .filter(|&(other, _)| other != e)
+                .min_by_key(|&(other, &load)| (load, other))?;
+            if calories <= 0 || loads[to] + calories > target {
+                continue;
+            }
 
-        if s.is_empty() {
-            elves.push(Elf {
-                inventory: Vec::new(),
+            loads[e] -= calories;
+            loads[to] += calories;
+            transfers.push(Transfer {
+                from: elf.id,
+                item: item + 1,
+                calories,
+                to: elves[to].id,
             });
+        }
 
-            i = i + 1;
-        } else {
-            let calories: i32 = match s.parse::<i32>() {
-                Ok(cal) => cal,
-                Err(_err) => panic!("Error parsing calories!"),
-            };
-            elves[i].inventory.push(calories);
+        if loads[e] > target {
+            return None;
         }
     }
 
-    Ok(elves)
+    Some(transfers)
 }
 
-fn part_1(elves: &Vec<Elf>) -> Option<i32> {
-    /* Find the most calories carried! */
-    let mut most_calories: i32 = 0;
+fn lower_bound(calories: &[i32], bins: usize) -> i32 {
+    let total: i64 = calories.iter().map(|&c| c as i64).sum();
+    let average = match bins {
+        0 => 0,
+        _ => (total.max(0) as u64).div_ceil(bins as u64) as i32,
+    };
+
+    average.max(calories.iter().copied().max().unwrap_or(0))
+}
+
+fn max_load(calories: &[i32], assignment: &[usize], bins: usize) -> i32 {
+    let mut loads = vec![0; bins];
+    for (&c, &bin) in calories.iter().zip(assignment.iter()) {
+        loads[bin] += c;
+    }
+
+    loads.into_iter().max().unwrap_or(0)
+}
+
+/// The bin of every item (given largest first), each going to the least loaded bin so far.
+fn longest_processing_time(calories: &[i32], bins: usize) -> Vec<usize> {
+    let mut loads: BinaryHeap<Reverse<(i32, usize)>> = (0..bins).map(|b| Reverse((0, b))).collect();
+
+    calories
+        .iter()
+        .map(|&c| {
+            let Reverse((load, bin)) = loads.pop().unwrap();
+            loads.push(Reverse((load + c, bin)));
+            bin
+        })
+        .collect()
+}
 
-    for elf in elves.iter() {
-        let sum_of_calories: i32 = elf.inventory.iter().sum();
+/// The bin of every item (given largest first) in an optimal partition, searching depth-first
+/// from the `best` one known and stopping early once it reaches the lower bound.
+fn branch_and_bound(
+    calories: &[i32],
+    bins: usize,
+    best: Vec<usize>,
+    lower_bound: i32,
+) -> Vec<usize> {
+    struct Search<'a> {
+        calories: &'a [i32],
+        lower_bound: i32,
+        loads: Vec<i32>,
+        assignment: Vec<usize>,
+        best: i32,
+        best_assignment: Vec<usize>,
+    }
+
+    impl Search<'_> {
+        fn place(&mut self, i: usize) {
+            if self.best <= self.lower_bound {
+                return;
+            }
+            if i == self.calories.len() {
+                self.best = self.loads.iter().copied().max().unwrap_or(0);
+                self.best_assignment = self.assignment.clone();
+                return;
+            }
 
-        if sum_of_calories >= most_calories {
-            most_calories = sum_of_calories;
+            for bin in 0..self.loads.len() {
+                let load = self.loads[bin];
+                // bins as loaded as an earlier one lead to the same partitions
+                if load + self.calories[i] >= self.best || self.loads[..bin].contains(&load) {
+                    continue;
+                }
+
+                self.loads[bin] += self.calories[i];
+                self.assignment.push(bin);
+                self.place(i + 1);
+                self.assignment.pop();
+                self.loads[bin] -= self.calories[i];
+            }
         }
     }
 
-    Some(most_calories)
+    let mut search = Search {
+        calories,
+        lower_bound,
+        loads: vec![0; bins],
+        assignment: Vec::with_capacity(calories.len()),
+        best: max_load(calories, &best, bins),
+        best_assignment: best,
+    };
+    search.place(0);
+
+    search.best_assignment
 }
 
-fn part_2(elves: &Vec<Elf>) -> Option<i32> {
-    /* Find the top three elves carrying the most calories! */
-    let mut most_calories: Vec<i32> = vec![0, 0, 0];
+/// The elf ending up with each bin, chosen to move as little food as possible: the bins and elves
+/// sharing the most calories are paired first.
+fn assign_owners(items: &[(usize, usize, i32)], bins: &[usize], n: usize) -> Vec<usize> {
+    let mut shared: BTreeMap<(usize, usize), i64> = BTreeMap::new();
+    for (&(elf, _, calories), &bin) in items.iter().zip(bins.iter()) {
+        *shared.entry((bin, elf)).or_default() += calories as i64;
+    }
+    let mut pairs: Vec<((usize, usize), i64)> = shared.into_iter().collect();
+    pairs.sort_by_key(|&(pair, calories)| (Reverse(calories), pair));
+
+    let mut owners: Vec<Option<usize>> = vec![None; n];
+    let mut owned = vec![false; n];
+    for ((bin, elf), _) in pairs.into_iter() {
+        if owners[bin].is_none() && !owned[elf] {
+            owners[bin] = Some(elf);
+            owned[elf] = true;
+        }
+    }
+
+    // the remaining bins go to the remaining elves in order
+    let mut free = (0..n).filter(|&e| !owned[e]);
+    owners
+        .into_iter()
+        .map(|owner| owner.or_else(|| free.next()).unwrap())
+        .collect()
+}
 
-    for elf in elves.iter() {
-        let sum_of_calories: i32 = elf.inventory.iter().sum();
+impl Plan {
+    /// The inventories of the elves once every transfer is carried out, or `None` if a transfer
+    /// names an elf or an item that does not exist, or an item moved twice.
+    pub fn apply(&self, elves: &[Elf]) -> Option<Vec<Elf>> {
+        let index = |id: usize| elves.iter().position(|elf| elf.id == id);
 
-        if most_calories.iter().any(|&x| x <= sum_of_calories) {
-            let min_val = most_calories.iter().min()?;
-            let min_idx = most_calories.iter().position(|x| x == min_val)?;
+        let mut moved: Vec<Vec<bool>> = elves
+            .iter()
+            .map(|elf| vec![false; elf.inventory.len()])
+            .collect();
+        let mut received: Vec<Vec<i32>> = vec![Vec::new(); elves.len()];
+        for transfer in self.transfers.iter() {
+            let (from, to) = (index(transfer.from)?, index(transfer.to)?);
+            let item = transfer.item.checked_sub(1)?;
+            if elves[from].inventory.get(item) != Some(&transfer.calories) || moved[from][item] {
+                return None;
+            }
 
-            most_calories.swap_remove(min_idx);
-            most_calories.push(sum_of_calories);
+            moved[from][item] = true;
+            received[to].push(transfer.calories);
         }
+
+        Some(
+            elves
+                .iter()
+                .zip(moved.iter().zip(received))
+                .map(|(elf, (moved, received))| Elf {
+                    id: elf.id,
+                    inventory: elf
+                        .inventory
+                        .iter()
+                        .zip(moved.iter())
+                        .filter(|&(_, &moved)| !moved)
+                        .map(|(&c, _)| c)
+                        .chain(received)
+                        .collect(),
+                })
+                .collect(),
+        )
+    }
+}
+
+impl fmt::Display for Plan {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let method = match self.optimal {
+            true => "optimal",
+            false => "heuristic",
+        };
+        writeln!(
+            f,
+            "Rebalancing ({}): the most calories carried go from {} to {} (at best {})",
+            method, self.before, self.after, self.lower_bound
+        )?;
+        for transfer in self.transfers.iter() {
+            writeln!(
+                f,
+                "Move item {} of elf {} ({} calories) to elf {}",
+                transfer.item, transfer.from, transfer.calories, transfer.to
+            )?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn elves(totals: &[i32]) -> Vec<Elf> {
+        totals
+            .iter()
+            .enumerate()
+            .map(|(i, &t)| Elf {
+                id: i + 1,
+                inventory: vec![t],
+            })
+            .collect()
+    }
+
+    fn ids(elves: Vec<&Elf>) -> Vec<usize> {
+        elves.iter().map(|elf| elf.id).collect()
+    }
+
+    #[test]
+    fn top_breaks_ties_by_position() {
+        let elves = elves(&[5, 9, 7, 9, 5, 9]);
+
+        assert_eq!(ids(top(&elves, 1)), vec![2]);
+        assert_eq!(ids(top(&elves, 3)), vec![2, 4, 6]);
+        assert_eq!(ids(top(&elves, 5)), vec![2, 4, 6, 3, 1]);
+        assert_eq!(ids(top(&elves, 10)).len(), 6);
+        assert!(top(&elves, 0).is_empty());
+    }
+
+    #[test]
+    fn percentiles_by_nearest_rank() {
+        let statistics = Statistics::new(&elves(&[15, 20, 35, 40, 50])).unwrap();
+
+        assert_eq!(statistics.percentiles, vec![(25, 20), (75, 40), (90, 50)]);
+        assert_eq!(statistics.median, 35.0);
+        assert_eq!(statistics.mean, 32.0);
+
+        let statistics = Statistics::new(&elves(&[3, 1, 4, 2])).unwrap();
+        assert_eq!(statistics.percentiles, vec![(25, 1), (75, 3), (90, 4)]);
+        assert_eq!(statistics.median, 2.5);
+
+        let statistics = Statistics::new(&elves(&[7])).unwrap();
+        assert_eq!(statistics.percentiles, vec![(25, 7), (75, 7), (90, 7)]);
+        assert_eq!(Statistics::new(&[]), None);
+    }
+
+    #[test]
+    fn histogram_covers_every_total() {
+        let bins = |totals: &[i32]| Statistics::new(&elves(totals)).unwrap().histogram;
+
+        // a single value makes a single bin
+        assert_eq!(
+            bins(&[4, 4]),
+            vec![Bin {
+                start: 4,
+                end: 4,
+                count: 2
+            }]
+        );
+
+        // the minimum falls in the first bin and the maximum in the last one, which is narrower
+        let histogram = bins(&[0, 8, 9, 17, 80]);
+        assert_eq!(histogram.len(), 8);
+        assert_eq!((histogram[0].start, histogram[0].end), (0, 10));
+        assert_eq!((histogram[7].start, histogram[7].end), (77, 80));
+        let counts: Vec<usize> = histogram.iter().map(|b| b.count).collect();
+        assert_eq!(counts, vec![3, 1, 0, 0, 0, 0, 0, 1]);
+
+        // fewer distinct values than bins
+        let histogram = bins(&[1, 2, 3]);
+        assert_eq!(histogram.iter().map(|b| b.count).sum::<usize>(), 3);
+        assert_eq!(histogram.last().unwrap().end, 3);
+    }
+
+    #[test]
+    fn histogram_of_extreme_totals() {
+        let elves = vec![
+            Elf {
+                id: 1,
+                inventory: vec![i32::MIN, i32::MIN],
+            },
+            Elf {
+                id: 2,
+                inventory: vec![i32::MAX, i32::MAX],
+            },
+        ];
+        assert_eq!(elves[1].total(), 2 * i32::MAX as i64);
+
+        let statistics = Statistics::new(&elves).unwrap();
+        let histogram = statistics.histogram;
+        assert_eq!(histogram.first().unwrap().start, 2 * i32::MIN as i64);
+        assert_eq!(histogram.last().unwrap().end, 2 * i32::MAX as i64);
+        assert_eq!(histogram.iter().map(|b| b.count).sum::<usize>(), 2);
     }
 
-    Some(most_calories.into_iter().sum())
+    #[test]
+    fn rejects_negative_calories() {
+        let e = parse("100\n\n200\n-5\n".as_bytes()).unwrap_err();
+        assert!(e.to_string().starts_with("line 4, column 1:"), "{}", e);
+    }
+
+    #[test]
+    fn rejects_calories_that_add_up_past_an_i32() {
+        let input = format!("{}\n\n{}\n", i32::MAX, 1);
+        let e = parse(input.as_bytes()).unwrap_err();
+        assert!(e.to_string().starts_with("line 3, column 1:"), "{}", e);
+
+        let input = format!("{}\n\n{}\n", i32::MAX - 1, 1);
+        assert_eq!(parse(input.as_bytes()).unwrap()[1].total(), 1);
+    }
 }
@@ -1,116 +1,386 @@
-use itertools::Itertools;
+use crate::parsing::{self, parse_all, Cursor};
+use crate::registry::{Params, Solver};
 use std::io;
 use std::io::prelude::*;
 
-pub fn solve(input: impl BufRead, part: u8) -> io::Result<()> {
-    let parsed_input = parse(input)?;
+pub fn solvers() -> Vec<Solver> {
+    vec![
+        Solver::new(2, 1, |input, ctx| {
+            let rules = Rules::from_params(&ctx.params)?;
+            Ok(part_1(&rules, &parse(input.as_bytes(), &rules)?)?.map(|x| x.to_string()))
+        }),
+        Solver::new(2, 2, |input, ctx| {
+            let rules = Rules::from_params(&ctx.params)?;
+            Ok(part_2(&rules, &parse(input.as_bytes(), &rules)?)?.map(|x| x.to_string()))
+        }),
+    ]
+}
+
+/// One of the shapes of the game, by its position in the cycle of the rules. This is a position
+/// rather than an enum of named shapes because the shapes come from the rules, which can have any
+/// odd number of them, and the outcome of a round follows from how far apart two shapes are in
+/// the cycle. The names are kept in `Rules::names`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Shape(pub usize);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Outcome {
+    Lose,
+    Draw,
+    Win,
+}
+
+impl Outcome {
+    pub fn score(self) -> u32 {
+        match self {
+            Outcome::Lose => 0,
+            Outcome::Draw => 3,
+            Outcome::Win => 6,
+        }
+    }
+}
+
+/// A cyclic "beats" game with an odd number of shapes, such as rock-paper-scissors or
+/// rock-paper-scissors-Spock-lizard. Every shape beats the shapes an odd number of places before
+/// it in the cycle (wrapping around) and loses to the others, so that each shape beats exactly
+/// half of the other shapes.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Rules {
+    pub names: Vec<String>,
+    /// The letters standing for each shape in the first column of the strategy guide, and in the
+    /// second column when it is read as the shape to play.
+    pub opponent: Vec<char>,
+    pub own: Vec<char>,
+    /// The letters standing for losing, drawing and winning, when the second column is read as
+    /// the outcome to reach.
+    pub outcomes: [char; 3],
+}
+
+impl Rules {
+    /// Rock, paper and scissors, with the letters of the puzzle.
+    pub fn classic() -> Self {
+        Rules::new(
+            vec!["rock".into(), "paper".into(), "scissors".into()],
+            "ABC".chars().collect(),
+            "XYZ".chars().collect(),
+            ['X', 'Y', 'Z'],
+        )
+        .unwrap()
+    }
+
+    /// The rules described by the solver parameters, falling back to the classic game:
+    ///
+    /// [params.day02]
+    /// shapes = "rock paper scissors spock lizard"   # in the order of the cycle
+    /// opponent = "ABCDE"
+    /// own = "VWXYZ"
+    /// outcomes = "XYZ"                              # lose, draw and win
+    pub fn from_params(params: &Params) -> io::Result<Self> {
+        let classic = Rules::classic();
+        let shapes = params.get("day02.shapes", classic.names.join(" "))?;
+        let opponent = params.get("day02.opponent", String::from("ABC"))?;
+        let own = params.get("day02.own", String::from("XYZ"))?;
+        let outcomes: Vec<char> = params
+            .get("day02.outcomes", String::from("XYZ"))?
+            .chars()
+            .collect();
+
+        let outcomes: [char; 3] = outcomes.try_into().map_err(|_| {
+            invalid_rules("day02.outcomes needs exactly 3 letters (lose, draw, win)".into())
+        })?;
+
+        Rules::new(
+            shapes.split_whitespace().map(String::from).collect(),
+            opponent.chars().collect(),
+            own.chars().collect(),
+            outcomes,
+        )
+    }
+
+    pub fn new(
+        names: Vec<String>,
+        opponent: Vec<char>,
+        own: Vec<char>,
+        outcomes: [char; 3],
+    ) -> io::Result<Self> {
+        let n = names.len();
+        if n < 3 || n.is_multiple_of(2) {
+            return Err(invalid_rules(format!(
+                "a cyclic game needs an odd number of shapes, at least 3, not {}",
+                n
+            )));
+        }
+        for (key, letters) in [("opponent", &opponent), ("own", &own)] {
+            if letters.len() != n {
+                return Err(invalid_rules(format!(
+                    "{} shapes need {} {} letters, not {}",
+                    n,
+                    n,
+                    key,
+                    letters.len()
+                )));
+            }
+            if (1..n).any(|i| letters[..i].contains(&letters[i])) {
+                return Err(invalid_rules(format!("the {} letters repeat", key)));
+            }
+        }
+        if outcomes[1..].contains(&outcomes[0]) || outcomes[2] == outcomes[1] {
+            return Err(invalid_rules("the outcome letters repeat".into()));
+        }
+
+        Ok(Rules {
+            names,
+            opponent,
+            own,
+            outcomes,
+        })
+    }
+
+    /// The outcome of playing `own` against `opponent`.
+    pub fn outcome(&self, own: Shape, opponent: Shape) -> Outcome {
+        let n = self.names.len();
+        match (own.0 + n - opponent.0) % n {
+            0 => Outcome::Draw,
+            d if d % 2 == 1 => Outcome::Win,
+            _ => Outcome::Lose,
+        }
+    }
+
+    /// A shape reaching `outcome` against `opponent`: the next one in the cycle to win and the
+    /// previous one to lose.
+    pub fn response(&self, opponent: Shape, outcome: Outcome) -> Shape {
+        let n = self.names.len();
+        match outcome {
+            Outcome::Lose => Shape((opponent.0 + n - 1) % n),
+            Outcome::Draw => opponent,
+            Outcome::Win => Shape((opponent.0 + 1) % n),
+        }
+    }
 
-    let now = std::time::Instant::now();
-    let solution = match part {
-        0 => (part_1(&parsed_input), part_2(&parsed_input)),
-        1 => (part_1(&parsed_input), None),
-        2 => (None, part_2(&parsed_input)),
-        _ => unimplemented!(),
-    };
-    let time = now.elapsed().as_micros();
+    /// The score of a round: the value of the shape played (its place in the cycle, from 1) plus
+    /// that of the outcome.
+    pub fn score(&self, own: Shape, opponent: Shape) -> u32 {
+        own.0 as u32 + 1 + self.outcome(own, opponent).score()
+    }
 
-    match solution.0 {
-        Some(x) => println!("Part 1: {}", x),
-        None => println!(),
+    fn own_shape(&self, letter: char) -> Option<Shape> {
+        self.own.iter().position(|&c| c == letter).map(Shape)
     }
-    match solution.1 {
-        Some(x) => println!("Part 2: {}", x),
-        None => println!(),
+
+    fn outcome_of(&self, letter: char) -> Option<Outcome> {
+        let i = self.outcomes.iter().position(|&c| c == letter)?;
+
+        Some([Outcome::Lose, Outcome::Draw, Outcome::Win][i])
     }
+}
 
-    println!("Time elapsed: {} µs", time);
+fn invalid_rules(message: String) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::InvalidInput,
+        format!("Invalid day 2 rules: {}", message),
+    )
+}
 
-    Ok(())
+/// A line of the strategy guide: the shape the opponent plays, and the letter of the second
+/// column, whose meaning depends on the part.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Round {
+    pub opponent: Shape,
+    pub response: char,
 }
 
-fn parse(input: impl BufRead) -> io::Result<Vec<String>> {
-    /* each line consists of a string of two chars separated by a whitespace
-     * and each line will simply be read into a vector */
+pub fn parse(input: impl BufRead, rules: &Rules) -> io::Result<Vec<Round>> {
+    // one round per line, e.g. "A Y"
+    parse_all(input, |cursor| {
+        cursor
+            .lines()
+            .into_iter()
+            .map(|line| {
+                line.all(|l| {
+                    let start = *l;
+                    let letter = single_letter(l)?;
+                    let opponent = match rules.opponent.iter().position(|&c| c == letter) {
+                        Some(i) => Shape(i),
+                        None => return Err(start.error(format!("unknown shape {:?}", letter))),
+                    };
+
+                    l.expect(" ")?;
+                    let start = *l;
+                    let response = single_letter(l)?;
+                    if !rules.own.contains(&response) && !rules.outcomes.contains(&response) {
+                        return Err(start.error(format!("unknown response {:?}", response)));
+                    }
 
-    Ok(input.lines().map(|x| x.unwrap()).collect_vec())
+                    Ok(Round { opponent, response })
+                })
+            })
+            .collect()
+    })
 }
 
-fn part_1(strategy: &Vec<String>) -> Option<i32> {
-    /* Find the score of the employed strategy
-     * (A, B, C) = (X, Y, Z) -> Rock, Paper, Scissors
-     * such that
-     * X beats C, but loses to B
-     * Y beats A, but loses to C
-     * Z beats B, but loses to A
-     * The points are as follows:
-     * (1, 2, 3) for choosing (X, Y, Z)
-     * 6 for winning
-     * 3 for a tie
-     * 0 for losing */
-    let mut score: i32 = 0;
-
-    for game in strategy.into_iter() {
-        let (opposing_play, own_play) = game.split_whitespace().next_tuple().unwrap();
-
-        score += match own_play {
-            "X" => match opposing_play {
-                "A" => 4,
-                "C" => 7,
-                _ => 1,
-            },
-            "Y" => match opposing_play {
-                "A" => 8,
-                "B" => 5,
-                _ => 2,
-            },
-            "Z" => match opposing_play {
-                "B" => 9,
-                "C" => 6,
-                _ => 3,
-            },
-            &_ => 0,
-        }
+fn single_letter(l: &mut Cursor) -> parsing::Result<char> {
+    let start = *l;
+    let word = l.word()?;
+    let mut chars = word.chars();
+    match (chars.next(), chars.next()) {
+        (Some(c), None) => Ok(c),
+        _ => Err(start.error(format!("expected a single letter, found {:?}", word))),
+    }
+}
+
+fn part_1(rules: &Rules, strategy: &[Round]) -> io::Result<Option<u32>> {
+    /* Find the score of the employed strategy, reading the second column as the shape to play */
+    let mut score: u32 = 0;
+
+    for (i, round) in strategy.iter().enumerate() {
+        let own = rules
+            .own_shape(round.response)
+            .ok_or_else(|| unknown_response(i, round.response, "shape"))?;
+        score += rules.score(own, round.opponent);
     }
 
-    Some(score)
+    Ok(Some(score))
+}
+
+fn part_2(rules: &Rules, strategy: &[Round]) -> io::Result<Option<u32>> {
+    /* Similar to part 1, but now the second column is the outcome to reach, and the shape and
+     * points need to be tallied based on that. */
+    let mut score: u32 = 0;
+
+    for (i, round) in strategy.iter().enumerate() {
+        let outcome = rules
+            .outcome_of(round.response)
+            .ok_or_else(|| unknown_response(i, round.response, "outcome"))?;
+        let own = rules.response(round.opponent, outcome);
+        score += rules.score(own, round.opponent);
+    }
+
+    Ok(Some(score))
+}
+
+fn unknown_response(round: usize, letter: char, meaning: &str) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::InvalidData,
+        format!(
+            "Round {}: {:?} is not one of the {} letters",
+            round + 1,
+            letter,
+            meaning
+        ),
+    )
 }
 
-fn part_2(strategy: &Vec<String>) -> Option<i32> {
-    /* Similar to part 1, but now
-     * (X, Y, Z) -> Lose, Draw, Win
-     * and the shape and points need to be tallied based on that. */
-
-    let mut score: i32 = 0;
-
-    for game in strategy.into_iter() {
-        // naive implementation
-        let (opposing_play, own_play) = game.split_whitespace().next_tuple().unwrap();
-
-        score += match own_play {
-            // lose
-            "X" => match opposing_play {
-                "A" => 3,
-                "B" => 1,
-                "C" => 2,
-                _ => 0,
-            },
-            // draw
-            "Y" => match opposing_play {
-                "A" => 4,
-                "B" => 5,
-                "C" => 6,
-                _ => 0,
-            },
-            // win
-            "Z" => match opposing_play {
-                "A" => 8,
-                "B" => 9,
-                "C" => 7,
-                _ => 0,
-            },
-            &_ => 0,
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &str = "A Y\nB X\nC Z\n";
+
+    fn rpsls() -> Rules {
+        let mut params = Params::new();
+        params.insert("day02.shapes", "rock paper scissors spock lizard");
+        params.insert("day02.opponent", "ABCDE");
+        params.insert("day02.own", "VWXYZ");
+        params.insert("day02.outcomes", "XYZ");
+
+        Rules::from_params(&params).unwrap()
+    }
+
+    fn shape(rules: &Rules, name: &str) -> Shape {
+        Shape(rules.names.iter().position(|n| n == name).unwrap())
+    }
+
+    #[test]
+    fn solves_the_example() {
+        let rules = Rules::classic();
+        let strategy = parse(EXAMPLE.as_bytes(), &rules).unwrap();
+
+        assert_eq!(part_1(&rules, &strategy).unwrap(), Some(15));
+        assert_eq!(part_2(&rules, &strategy).unwrap(), Some(12));
+    }
+
+    #[test]
+    fn rock_paper_scissors_spock_lizard() {
+        let rules = rpsls();
+        let wins = [
+            ("scissors", "paper"),
+            ("paper", "rock"),
+            ("rock", "lizard"),
+            ("lizard", "spock"),
+            ("spock", "scissors"),
+            ("scissors", "lizard"),
+            ("lizard", "paper"),
+            ("paper", "spock"),
+            ("spock", "rock"),
+            ("rock", "scissors"),
+        ];
+
+        for (winner, loser) in wins {
+            let (winner, loser) = (shape(&rules, winner), shape(&rules, loser));
+            assert_eq!(rules.outcome(winner, loser), Outcome::Win);
+            assert_eq!(rules.outcome(loser, winner), Outcome::Lose);
         }
+        for own in 0..5 {
+            assert_eq!(rules.outcome(Shape(own), Shape(own)), Outcome::Draw);
+        }
+    }
+
+    #[test]
+    fn responses_reach_their_outcome() {
+        for rules in [Rules::classic(), rpsls()] {
+            for opponent in (0..rules.names.len()).map(Shape) {
+                for outcome in [Outcome::Lose, Outcome::Draw, Outcome::Win] {
+                    let own = rules.response(opponent, outcome);
+                    assert_eq!(rules.outcome(own, opponent), outcome);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn scores_with_five_shapes() {
+        let rules = rpsls();
+        // spock (4) against rock wins (6), lizard (5) against rock loses (0)
+        let strategy = parse("A Y\nA Z\n".as_bytes(), &rules).unwrap();
+
+        assert_eq!(part_1(&rules, &strategy).unwrap(), Some(4 + 6 + 5));
     }
 
-    Some(score)
+    #[test]
+    fn unknown_letters_are_errors() {
+        let rules = Rules::classic();
+
+        let e = parse("A Y\nF Y\n".as_bytes(), &rules).unwrap_err();
+        assert_eq!(e.to_string(), "line 2, column 1: unknown shape 'F'");
+
+        let e = parse("A Q\n".as_bytes(), &rules).unwrap_err();
+        assert_eq!(e.to_string(), "line 1, column 3: unknown response 'Q'");
+
+        let e = parse("AB Y\n".as_bytes(), &rules).unwrap_err();
+        assert!(e.to_string().contains("expected a single letter"), "{}", e);
+
+        // a letter that only stands for an outcome cannot be played as a shape
+        let mut params = Params::new();
+        params.insert("day02.outcomes", "LDW");
+        let rules = Rules::from_params(&params).unwrap();
+        let strategy = parse("A W\n".as_bytes(), &rules).unwrap();
+        assert!(part_1(&rules, &strategy).is_err());
+        assert_eq!(part_2(&rules, &strategy).unwrap(), Some(2 + 6));
+    }
+
+    #[test]
+    fn invalid_rules() {
+        let rules = |shapes: &str, opponent: &str, own: &str| {
+            let mut params = Params::new();
+            params.insert("day02.shapes", shapes);
+            params.insert("day02.opponent", opponent);
+            params.insert("day02.own", own);
+            Rules::from_params(&params)
+        };
+
+        assert!(rules("a b c d", "ABCD", "WXYZ").is_err());
+        assert!(rules("a b c", "AB", "XYZ").is_err());
+        assert!(rules("a b c", "ABA", "XYZ").is_err());
+        assert!(rules("a b c", "ABC", "XYZ").is_ok());
+    }
 }
@@ -1,46 +1,34 @@
+use crate::parsing::parse_all;
+use crate::registry::Solver;
 use itertools::Itertools;
 use std::collections::HashSet;
 use std::io;
 use std::io::prelude::*;
 
-pub fn solve(input: impl BufRead, part: u8) -> io::Result<()> {
-    let parsed_input = parse(input)?;
-
-    let now = std::time::Instant::now();
-    let solution = match part {
-        0 => (part_1(&parsed_input), part_2(&parsed_input)),
-        1 => (part_1(&parsed_input), None),
-        2 => (None, part_2(&parsed_input)),
-        _ => unimplemented!(),
-    };
-    let time = now.elapsed().as_micros();
-
-    match solution.0 {
-        Some(x) => println!("Part 1: {}", x),
-        None => println!(),
-    }
-    match solution.1 {
-        Some(x) => println!("Part 2: {}", x),
-        None => println!(),
-    }
-
-    println!("Time elapsed: {} µs", time);
-
-    Ok(())
+pub fn solvers() -> Vec<Solver> {
+    vec![
+        Solver::new(3, 1, |input, _| {
+            Ok(part_1(&parse(input.as_bytes())?).map(|x| x.to_string()))
+        }),
+        Solver::new(3, 2, |input, _| {
+            Ok(part_2(&parse(input.as_bytes())?).map(|x| x.to_string()))
+        }),
+    ]
 }
 
-fn parse(input: impl BufRead) -> io::Result<Vec<String>> {
+pub fn parse(input: impl BufRead) -> io::Result<Vec<String>> {
     /* each line corresponds to a rucksack, where each character
      * is a case-sensitive item. */
-    let rucksacks: Vec<String> = input
-        .lines()
-        .map(|l| l.unwrap().chars().collect())
-        .collect();
-
-    Ok(rucksacks)
+    parse_all(input, |cursor| {
+        cursor
+            .lines()
+            .into_iter()
+            .map(|line| line.all(|l| Ok(l.take_while(|c| c.is_ascii_alphabetic()).to_string())))
+            .collect()
+    })
 }
 
-fn part_1(rucksacks: &Vec<String>) -> Option<i32> {
+fn part_1(rucksacks: &[String]) -> Option<i32> {
     /* Each rucksack has two compartments of equal size,
      * i.e. the first half of the line is the contents of the
      * first compartment, and the second half represents the
@@ -49,7 +37,7 @@ fn part_1(rucksacks: &Vec<String>) -> Option<i32> {
      * and compute the priority value */
     let mut priority_sum: i32 = 0;
 
-    for rucksack in rucksacks.into_iter() {
+    for rucksack in rucksacks.iter() {
         let (left, right) = rucksack.split_at(rucksack.len() / 2);
         // build HashSets for each compartment
         let set_l: HashSet<char> = HashSet::from_iter(left.chars());
@@ -68,21 +56,21 @@ fn part_1(rucksacks: &Vec<String>) -> Option<i32> {
     Some(priority_sum)
 }
 
-fn part_2(rucksacks: &Vec<String>) -> Option<i32> {
+fn part_2(rucksacks: &[String]) -> Option<i32> {
     /* group the rucksack in sets of three, and find
      * the only item that occurs in all three sets:
      * this is the badge, and defines the priority value */
     let mut priority_sum: i32 = 0;
 
-    let mut sacks = rucksacks.iter().tuples();
-    while let Some((one, two, three)) = sacks.next() {
+    let sacks = rucksacks.iter().tuples();
+    for (one, two, three) in sacks {
         // define three HashSets for the three sacks
         let sack_1: HashSet<char> = HashSet::from_iter(one.chars());
         let sack_2: HashSet<char> = HashSet::from_iter(two.chars());
         let sack_3: HashSet<char> = HashSet::from_iter(three.chars());
 
         // compare sack 1 and 2 first
-        let intersection_1: HashSet<char> = sack_1.intersection(&sack_2).map(|&x| x).collect();
+        let intersection_1: HashSet<char> = sack_1.intersection(&sack_2).copied().collect();
         let intersection_2: char = *intersection_1.intersection(&sack_3).next().unwrap();
 
         // match to whether it is uppercase, and compute priority value from there
@@ -1,79 +1,91 @@
+use crate::parsing::parse_all;
+use crate::registry::{Context, Solver};
 use std::io;
 use std::io::prelude::*;
 
-pub fn solve(input: impl BufRead, part: u8) -> io::Result<()> {
-    let parsed_input = parse(input)?;
-
-    let now = std::time::Instant::now();
-    let solution = match part {
-        0 => (part_1(&parsed_input), part_2(&parsed_input)),
-        1 => (part_1(&parsed_input), None),
-        2 => (None, part_2(&parsed_input)),
-        _ => unimplemented!(),
-    };
-    let time = now.elapsed().as_micros();
-
-    match solution.0 {
-        Some(x) => println!("Part 1: {}", x),
-        None => println!(),
-    }
-    match solution.1 {
-        Some(x) => println!("Part 2: {}", x),
-        None => println!(),
-    }
-
-    println!("Time elapsed: {} µs", time);
-
-    Ok(())
+pub fn solvers() -> Vec<Solver> {
+    vec![
+        Solver::new(4, 1, |input, ctx| {
+            Ok(part_1(&parse(input.as_bytes())?, ctx).map(|x| x.to_string()))
+        }),
+        Solver::new(4, 2, |input, ctx| {
+            Ok(part_2(&parse(input.as_bytes())?, ctx).map(|x| x.to_string()))
+        }),
+    ]
 }
 
-fn parse(input: impl BufRead) -> io::Result<Vec<Vec<i32>>> {
-    let mut sections: Vec<Vec<i32>> = Vec::new();
-
-    for line in input.lines() {
-        let s = line?;
-
-        // split at the comma and dash, and push to sections vector
-        let str_vec: Vec<&str> = s.split(&['-', ','][..]).collect();
-        let mut i32_vec: Vec<i32> = Vec::new();
-        for c in str_vec.into_iter() {
-            i32_vec.push(c.parse::<i32>().unwrap());
-        }
-
-        sections.push(i32_vec);
-    }
-    Ok(sections)
+pub fn parse(input: impl BufRead) -> io::Result<Vec<Vec<i32>>> {
+    // each line holds two section ranges, e.g. 2-4,6-8
+    parse_all(input, |cursor| {
+        cursor
+            .lines()
+            .into_iter()
+            .map(|line| {
+                line.all(|l| {
+                    let start = *l;
+                    let ranges = l.separated(",", |r| r.separated("-", |n| n.integer::<i32>()))?;
+                    match ranges.as_slice() {
+                        [a, b] if a.len() == 2 && b.len() == 2 => Ok(ranges.concat()),
+                        _ => Err(start.error("expected two ranges of the form a-b,c-d")),
+                    }
+                })
+            })
+            .collect()
+    })
 }
 
-fn part_1(ranges: &Vec<Vec<i32>>) -> Option<i32> {
+fn part_1(ranges: &[Vec<i32>], ctx: &Context) -> Option<i32> {
     let mut n_contained_ranges: i32 = 0;
 
     // for each set of section ranges, check if either range
     // is a subrange of the other (i.e. is fully contained)
-    for range in ranges.into_iter() {
+    for (i, range) in ranges.iter().enumerate() {
         let start_cmp: i32 = range[0] - range[2];
         let end_cmp: i32 = range[3] - range[1];
 
         if start_cmp.signum() == end_cmp.signum() || start_cmp == 0 || end_cmp == 0 {
             n_contained_ranges += 1;
+
+            // pairs are named by their line rather than quoted, so that explanations can be
+            // published without the input
+            ctx.explain(|| {
+                let first_outer = range[0] <= range[2] && range[1] >= range[3];
+                let second_outer = range[2] <= range[0] && range[3] >= range[1];
+                match (first_outer, second_outer) {
+                    (true, true) => format!("Pair {}: both elves have the same sections", i + 1),
+                    (true, false) => format!("Pair {}: the first range contains the second", i + 1),
+                    _ => format!("Pair {}: the second range contains the first", i + 1),
+                }
+            });
         }
     }
 
     Some(n_contained_ranges)
 }
 
-fn part_2(ranges: &Vec<Vec<i32>>) -> Option<i32> {
+fn part_2(ranges: &[Vec<i32>], ctx: &Context) -> Option<i32> {
     let mut n_overlapping_ranges: i32 = 0;
 
     // for each set of section ranges, check if either range
     // overlaps at all.
-    for range in ranges.into_iter() {
+    for (i, range) in ranges.iter().enumerate() {
         if (range[0] >= range[2] && range[0] <= range[3])
             || (range[1] >= range[2] && range[1] <= range[3])
             || (range[2] >= range[0] && range[2] <= range[1])
             || (range[3] >= range[0] && range[3] <= range[1])
         {
             n_overlapping_ranges += 1;
+
+            ctx.explain(|| {
+                let shared = range[1].min(range[3]) - range[0].max(range[2]) + 1;
+                let plural = if shared == 1 { "" } else { "s" };
+                format!(
+                    "Pair {}: the ranges share {} section{}",
+                    i + 1,
+                    shared,
+                    plural
+                )
+            });
         }
     }
 
@@ -1,71 +1,105 @@
+use crate::parsing::{self, parse_all, Cursor};
+use crate::registry::Solver;
 use std::io;
 use std::io::prelude::*;
 
-pub fn solve(input: impl BufRead, part: u8) -> io::Result<()> {
-    let parsed_input = parse(input)?;
-
-    let now = std::time::Instant::now();
-    let solution = match part {
-        0 => (part_1(&parsed_input), part_2(&parsed_input)),
-        1 => (part_1(&parsed_input), None),
-        2 => (None, part_2(&parsed_input)),
-        _ => unimplemented!(),
-    };
-    let time = now.elapsed().as_micros();
-
-    match solution.0 {
-        Some(x) => println!("Part 1: {}", x),
-        None => println!(),
-    }
-    match solution.1 {
-        Some(x) => println!("Part 2: {}", x),
-        None => println!(),
-    }
-
-    println!("Time elapsed: {} µs", time);
-
-    Ok(())
+pub fn solvers() -> Vec<Solver> {
+    vec![
+        Solver::new(5, 1, |input, _| {
+            Ok(part_1(&parse(input.as_bytes())?).map(|x| x.to_string()))
+        }),
+        Solver::new(5, 2, |input, _| {
+            Ok(part_2(&parse(input.as_bytes())?).map(|x| x.to_string()))
+        }),
+    ]
 }
 
-fn parse(input: impl BufRead) -> io::Result<(Vec<Vec<String>>, Vec<Vec<usize>>)> {
-    let mut storage: Vec<Vec<String>> = Vec::new();
-    let mut commands: Vec<Vec<usize>> = Vec::new();
-
-    let mut switch: bool = false;
-    for line in input.lines() {
-        let s = line?;
-        if s.is_empty() {
-            switch = true;
-        } else {
-            if switch == false {
-                for (i, c) in s.chars().enumerate() {
-                    if c.is_alphabetic() {
-                        // [1] [5] [9] [13] ...
-                        // i.e. the actual index is i / 4
-                        let index = i / 4;
-                        while storage.len() < index + 1 {
-                            storage.push(Vec::new());
-                        }
-                        storage[index].insert(0, c.to_string());
+pub type Storage = Vec<Vec<String>>;
+pub type Commands = Vec<Vec<usize>>;
+
+pub fn parse(input: impl BufRead) -> io::Result<(Storage, Commands)> {
+    parse_all(input, |cursor| {
+        let start = *cursor;
+        let blocks = cursor.blocks();
+        let (mut drawing, mut procedure) = match blocks.as_slice() {
+            [drawing, procedure] => (*drawing, *procedure),
+            _ => return Err(start.error("expected a drawing of the stacks and a procedure")),
+        };
+
+        // the last line of the drawing numbers the stacks, e.g. " 1   2   3 "
+        let mut rows = drawing.lines();
+        let mut labels: Vec<usize> = Vec::new();
+        match rows.pop() {
+            Some(mut row) => loop {
+                row.skip_spaces();
+                if row.is_empty() {
+                    break;
+                }
+                labels.push(row.integer()?);
+            },
+            None => return Err(drawing.error("expected the numbers of the stacks")),
+        }
+        let mut storage: Storage = vec![Vec::new(); labels.len()];
+
+        // every other line holds a crate ("[A]") or a gap ("   ") per stack, left to right,
+        // from the top of the stacks down
+        for mut row in rows.into_iter().rev() {
+            let mut index: usize = 0;
+            while !row.is_empty() {
+                if row.eat("[") {
+                    if index >= storage.len() {
+                        return Err(row.error("crate outside of the numbered stacks"));
                     }
+                    let label = row.take_while(|c| c.is_alphabetic());
+                    if label.chars().count() != 1 {
+                        return Err(row.error("expected a single letter"));
+                    }
+                    row.expect("]")?;
+                    storage[index].push(label.to_string());
+                } else {
+                    row.expect("   ")?;
                 }
-            } else {
-                let mut numerics: Vec<usize> = Vec::new();
-                for c in s
-                    .split_whitespace()
-                    .filter(|c| c.chars().all(|x| x.is_numeric()))
-                {
-                    numerics.push(c.to_string().parse::<usize>().unwrap());
+
+                index += 1;
+                if !row.eat(" ") {
+                    break;
                 }
-                commands.push(numerics);
             }
+            row.end()?;
         }
-    }
 
-    Ok((storage, commands))
+        // the procedure has one step per line, e.g. "move 1 from 2 to 1"
+        let commands = procedure
+            .lines()
+            .into_iter()
+            .map(|line| {
+                line.all(|l| {
+                    l.expect("move ")?;
+                    let moves = l.integer::<usize>()?;
+                    l.expect(" from ")?;
+                    let from = stack_number(l, storage.len())?;
+                    l.expect(" to ")?;
+                    let to = stack_number(l, storage.len())?;
+
+                    Ok(vec![moves, from, to])
+                })
+            })
+            .collect::<parsing::Result<Commands>>()?;
+
+        Ok((storage, commands))
+    })
+}
+
+/// The 1-based number of one of the `n` stacks.
+fn stack_number(cursor: &mut Cursor, n: usize) -> parsing::Result<usize> {
+    let start = *cursor;
+    match cursor.integer::<usize>()? {
+        number if (1..=n).contains(&number) => Ok(number),
+        number => Err(start.error(format!("there is no stack {}", number))),
+    }
 }
 
-fn part_1(storage_and_commands: &(Vec<Vec<String>>, Vec<Vec<usize>>)) -> Option<String> {
+fn part_1(storage_and_commands: &(Storage, Commands)) -> Option<String> {
     let (_storage, commands) = storage_and_commands;
     let mut storage: Vec<Vec<String>> = _storage.clone();
 
@@ -78,9 +112,8 @@ fn part_1(storage_and_commands: &(Vec<Vec<String>>, Vec<Vec<usize>>)) -> Option<
         let idx_to = command[2] - 1;
 
         while moves > 0 {
-            match storage[idx_from].pop() {
-                Some(c) => storage[idx_to].push(c),
-                None => (),
+            if let Some(c) = storage[idx_from].pop() {
+                storage[idx_to].push(c)
             }
 
             moves -= 1;
@@ -88,15 +121,14 @@ fn part_1(storage_and_commands: &(Vec<Vec<String>>, Vec<Vec<usize>>)) -> Option<
     }
 
     for mut vec in storage.into_iter() {
-        match &vec.pop() {
-            Some(c) => top_crates.push_str(c),
-            None => (),
+        if let Some(c) = &vec.pop() {
+            top_crates.push_str(c)
         }
     }
     Some(top_crates)
 }
 
-fn part_2(storage_and_commands: &(Vec<Vec<String>>, Vec<Vec<usize>>)) -> Option<String> {
+fn part_2(storage_and_commands: &(Storage, Commands)) -> Option<String> {
     let (_storage, commands) = storage_and_commands;
     let mut storage: Vec<Vec<String>> = _storage.clone();
 
@@ -110,9 +142,8 @@ fn part_2(storage_and_commands: &(Vec<Vec<String>>, Vec<Vec<usize>>)) -> Option<
 
         let mut crane_crates: Vec<String> = Vec::new();
         while moves > 0 {
-            match storage[idx_from].pop() {
-                Some(c) => crane_crates.push(c),
-                None => (),
+            if let Some(c) = storage[idx_from].pop() {
+                crane_crates.push(c)
             }
 
             moves -= 1;
@@ -125,9 +156,8 @@ fn part_2(storage_and_commands: &(Vec<Vec<String>>, Vec<Vec<usize>>)) -> Option<
     }
 
     for mut vec in storage.into_iter() {
-        match &vec.pop() {
-            Some(c) => top_crates.push_str(c),
-            None => (),
+        if let Some(c) = &vec.pop() {
+            top_crates.push_str(c)
         }
     }
     Some(top_crates)
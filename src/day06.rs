@@ -1,41 +1,36 @@
+use crate::parsing::parse_all;
+use crate::registry::Solver;
 use itertools::Itertools;
 use std::collections::HashSet;
 use std::io;
 use std::io::prelude::*;
 
-pub fn solve(input: impl BufRead, part: u8) -> io::Result<()> {
-    let parsed_input = parse(input)?;
-
-    let now = std::time::Instant::now();
-    let solution = match part {
-        0 => (part_1(&parsed_input), part_2(&parsed_input)),
-        1 => (part_1(&parsed_input), None),
-        2 => (None, part_2(&parsed_input)),
-        _ => unimplemented!(),
-    };
-    let time = now.elapsed().as_micros();
-
-    match solution.0 {
-        Some(x) => println!("Part 1: {}", x),
-        None => println!(),
-    }
-    match solution.1 {
-        Some(x) => println!("Part 2: {}", x),
-        None => println!(),
-    }
-
-    println!("Time elapsed: {} µs", time);
-
-    Ok(())
+pub fn solvers() -> Vec<Solver> {
+    vec![
+        Solver::variant(6, 1, "hashset", |input, _| {
+            Ok(part_1(&parse(input.as_bytes())?).map(|x| x.to_string()))
+        }),
+        Solver::variant(6, 2, "hashset", |input, _| {
+            Ok(part_2(&parse(input.as_bytes())?).map(|x| x.to_string()))
+        }),
+        Solver::variant(6, 1, "counting", |input, _| {
+            Ok(find_marker(&parse(input.as_bytes())?, 4).map(|x| x.to_string()))
+        }),
+        Solver::variant(6, 2, "counting", |input, _| {
+            Ok(find_marker(&parse(input.as_bytes())?, 14).map(|x| x.to_string()))
+        }),
+    ]
 }
 
-fn parse(input: impl BufRead) -> io::Result<String> {
+pub fn parse(input: impl BufRead) -> io::Result<String> {
     // one single line of chars
-    Ok(input.lines().next().unwrap()?)
+    parse_all(input, |cursor| {
+        Ok(cursor.take_while(|c| c.is_ascii_lowercase()).to_string())
+    })
 }
 
-fn part_1(buffer: &String) -> Option<usize> {
-    let slice: Vec<char> = buffer.clone().chars().collect_vec();
+fn part_1(buffer: &str) -> Option<usize> {
+    let slice: Vec<char> = buffer.chars().collect_vec();
 
     let n: usize = 4;
     Some(
@@ -48,8 +43,8 @@ fn part_1(buffer: &String) -> Option<usize> {
     )
 }
 
-fn part_2(buffer: &String) -> Option<usize> {
-    let slice: Vec<char> = buffer.clone().chars().collect_vec();
+fn part_2(buffer: &str) -> Option<usize> {
+    let slice: Vec<char> = buffer.chars().collect_vec();
 
     let n: usize = 14;
     Some(
@@ -61,3 +56,34 @@ fn part_2(buffer: &String) -> Option<usize> {
             + n,
     )
 }
+
+fn find_marker(buffer: &str, n: usize) -> Option<usize> {
+    /* Slide a window of n chars over the buffer while counting the occurences of each char
+     * in the window, along with how many chars occur more than once. The marker ends where
+     * the window no longer holds any duplicates. */
+    let bytes = buffer.as_bytes();
+    let mut counts: [usize; 256] = [0; 256];
+    let mut duplicates: usize = 0;
+
+    for (i, &c) in bytes.iter().enumerate() {
+        counts[c as usize] += 1;
+        if counts[c as usize] == 2 {
+            duplicates += 1;
+        }
+
+        // drop the char leaving the window
+        if i >= n {
+            let old = bytes[i - n] as usize;
+            counts[old] -= 1;
+            if counts[old] == 1 {
+                duplicates -= 1;
+            }
+        }
+
+        if i + 1 >= n && duplicates == 0 {
+            return Some(i + 1);
+        }
+    }
+
+    None
+}
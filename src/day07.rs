@@ -1,106 +1,264 @@
+use crate::parsing::parse_all;
+use crate::registry::{Context, Solver};
 use std::io;
 use std::io::prelude::*;
 
-pub fn solve(input: impl BufRead, part: u8) -> io::Result<()> {
-    let parsed_input = parse(input)?;
-
-    let now = std::time::Instant::now();
-    let solution = match part {
-        0 => (part_1(&parsed_input), part_2(&parsed_input)),
-        1 => (part_1(&parsed_input), None),
-        2 => (None, part_2(&parsed_input)),
-        _ => unimplemented!(),
-    };
-    let time = now.elapsed().as_micros();
-
-    match solution.0 {
-        Some(x) => println!("Part 1: {}", x),
-        None => println!(),
-    }
-    match solution.1 {
-        Some(x) => println!("Part 2: {}", x),
-        None => println!(),
-    }
+pub fn solvers() -> Vec<Solver> {
+    vec![
+        Solver::new(7, 1, |input, ctx| {
+            let threshold: u64 = ctx.params.get("day07.threshold", 100000)?;
 
-    println!("Time elapsed: {} µs", time);
+            Ok(part_1(&parse(input.as_bytes())?, threshold, ctx).map(|x| x.to_string()))
+        }),
+        Solver::new(7, 2, |input, ctx| {
+            let available_space: u64 = ctx.params.get("day07.disk_size", 70000000)?;
+            let required_space: u64 = ctx.params.get("day07.update_size", 30000000)?;
 
-    Ok(())
+            Ok(part_2(
+                &parse(input.as_bytes())?,
+                available_space,
+                required_space,
+                ctx,
+            )
+            .map(|x| x.to_string()))
+        }),
+    ]
 }
 
-fn parse(input: impl BufRead) -> io::Result<Vec<String>> {
-    // collect all terminal commands and output into a single vector
-    Ok(input
-        .lines()
-        .map(|x| x.unwrap())
-        .filter(|x| !x.contains("dir"))
-        .collect())
+/// A line of the terminal session.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Line {
+    /// `$ cd <name>`, where `..` goes back to the parent.
+    Cd(String),
+    /// `$ ls`
+    Ls,
+    /// `dir <name>` listed by `ls`.
+    Dir(String),
+    /// `<size> <name>` listed by `ls`.
+    File(u64, String),
 }
 
-fn get_directory_sizes(terminal_output: &Vec<String>) -> Option<Vec<u64>> {
-    let mut directories: Vec<u64> = Vec::new();
-    let mut active_directories: Vec<usize> = Vec::new();
+pub fn parse(input: impl BufRead) -> io::Result<Vec<Line>> {
+    // one command or listed entry per line, e.g. "$ cd a", "$ ls", "dir e" or "29116 f"
+    parse_all(input, |cursor| {
+        // how many directories deep the session is, so that it never leaves the first one and
+        // lists no files before entering it
+        let mut depth: usize = 0;
 
-    for instr in terminal_output.into_iter() {
-        let split: Vec<&str> = instr.split_whitespace().map(|x| x).collect();
-        match split[0] {
-            // command
-            "$" => {
-                match split[1] {
-                    "cd" => {
-                        if split[2] == ".." {
-                            let cascade = directories[active_directories.pop().unwrap()].clone();
-                            directories[*active_directories.last().unwrap()] += cascade;
-                        } else {
-                            directories.push(0);
-                            active_directories.push(directories.len() - 1);
+        cursor
+            .lines()
+            .into_iter()
+            .map(|line| {
+                let start = line;
+                let line = line.all(|l| {
+                    if l.eat("$ ") {
+                        if l.eat("ls") {
+                            return Ok(Line::Ls);
                         }
+                        l.expect("cd ")?;
+                        return Ok(Line::Cd(l.take_while(|_| true).to_string()));
+                    }
+                    if l.eat("dir ") {
+                        return Ok(Line::Dir(l.take_while(|_| true).to_string()));
                     }
-                    _ => (),
-                };
+                    let size = l.integer()?;
+                    l.expect(" ")?;
+                    Ok(Line::File(size, l.take_while(|_| true).to_string()))
+                })?;
+
+                match &line {
+                    Line::Cd(name) if name == ".." && depth < 2 => {
+                        return Err(start.error("cd .. leaves the outermost directory"))
+                    }
+                    Line::Cd(name) if name == ".." => depth -= 1,
+                    Line::Cd(name) if name.is_empty() => {
+                        return Err(start.error("cd without a directory"))
+                    }
+                    Line::Cd(_) => depth += 1,
+                    Line::File(..) if depth == 0 => {
+                        return Err(start.error("file listed before entering a directory"))
+                    }
+                    _ => {}
+                }
+
+                Ok(line)
+            })
+            .collect()
+    })
+}
+
+fn get_directory_sizes(terminal_output: &[Line]) -> Vec<u64> {
+    let mut directories: Vec<u64> = Vec::new();
+    let mut active_directories: Vec<usize> = Vec::new();
+
+    // the parser makes sure that a directory is entered before anything else, and never left
+    for line in terminal_output.iter() {
+        match line {
+            Line::Cd(name) if name == ".." => {
+                let cascade = directories[active_directories.pop().unwrap()];
+                directories[*active_directories.last().unwrap()] += cascade;
+            }
+            Line::Cd(_) => {
+                directories.push(0);
+                active_directories.push(directories.len() - 1);
             }
-            // list output
-            _ => {
-                directories[*active_directories.last().unwrap()] +=
-                    split[0].parse::<u64>().unwrap();
+            Line::File(size, _) => {
+                directories[*active_directories.last().unwrap()] += size;
             }
+            Line::Ls | Line::Dir(_) => {}
         }
     }
 
     // purge active_directories to include the remaining sizes
     while !active_directories.is_empty() {
-        let cascade = directories[active_directories.pop().unwrap()].clone();
-        match active_directories.last() {
-            Some(&i) => directories[i] += cascade,
-            None => (),
+        let cascade = directories[active_directories.pop().unwrap()];
+        if let Some(&i) = active_directories.last() {
+            directories[i] += cascade
         }
     }
 
-    Some(directories)
+    directories
 }
 
-fn part_1(terminal_output: &Vec<String>) -> Option<u64> {
-    let directories: Vec<u64> = get_directory_sizes(terminal_output).unwrap();
+fn get_directory_lines(terminal_output: &[Line]) -> Vec<usize> {
+    // the line entering every directory, in the same order as get_directory_sizes, so that
+    // explanations point into the input rather than quote the names of its directories
+    terminal_output
+        .iter()
+        .enumerate()
+        .filter(|(_, line)| matches!(line, Line::Cd(name) if name != ".."))
+        .map(|(i, _)| i + 1)
+        .collect()
+}
+
+fn part_1(terminal_output: &[Line], threshold: u64, ctx: &Context) -> Option<u64> {
+    let directories: Vec<u64> = get_directory_sizes(terminal_output);
+
+    if ctx.explaining() {
+        let lines = get_directory_lines(terminal_output);
+        for (line, size) in lines.iter().zip(directories.iter()) {
+            if *size <= threshold {
+                ctx.explain(|| format!("The directory entered on line {} has size {}", line, size));
+            }
+        }
+    }
 
     Some(
         directories
             .into_iter()
-            .filter(|&x| x <= 100000)
+            .filter(|&x| x <= threshold)
             .sum::<u64>(),
     )
 }
 
-fn part_2(terminal_output: &Vec<String>) -> Option<u64> {
-    let directories: Vec<u64> = get_directory_sizes(terminal_output).unwrap();
+fn part_2(
+    terminal_output: &[Line],
+    available_space: u64,
+    required_space: u64,
+    ctx: &Context,
+) -> Option<u64> {
+    let directories: Vec<u64> = get_directory_sizes(terminal_output);
+
+    // no answer for an empty session, or one that does not fit on the disk
+    let current_space: u64 = *directories.first()?;
+    let unused_space: u64 = available_space.checked_sub(current_space)?;
+    let space_to_be_found: u64 = required_space.saturating_sub(unused_space);
+
+    if ctx.explaining() {
+        ctx.explain(|| format!("{} unused, {} more needed", unused_space, space_to_be_found));
 
-    let available_space: u64 = 70000000;
-    let required_space: u64 = 30000000;
+        let lines = get_directory_lines(terminal_output);
+        let candidates: Vec<(&usize, &u64)> = lines
+            .iter()
+            .zip(directories.iter())
+            .filter(|(_, &size)| size >= space_to_be_found)
+            .collect();
 
-    let current_space: u64 = *directories.first().unwrap();
-    let unused_space: u64 = available_space - current_space;
-    let space_to_be_found: u64 = required_space - unused_space;
+        for (line, size) in candidates.iter() {
+            ctx.explain(|| format!("The directory entered on line {} has size {}", line, size));
+        }
+        if let Some((line, size)) = candidates.iter().min_by_key(|(_, &size)| size) {
+            ctx.explain(|| {
+                format!(
+                    "Deleting the directory entered on line {}, freeing {}",
+                    line, size
+                )
+            });
+        }
+    }
 
     directories
         .into_iter()
         .filter(|&x| x >= space_to_be_found)
         .min()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::registry::Params;
+
+    const EXAMPLE: &str = "\
+$ cd /
+$ ls
+dir a
+14848514 b.txt
+8504156 c.dat
+dir d
+$ cd a
+$ ls
+dir e
+29116 f
+2557 g
+62596 h.lst
+$ cd e
+$ ls
+584 i
+$ cd ..
+$ cd ..
+$ cd d
+$ ls
+4060174 j
+8033020 d.log
+5626152 d.ext
+7214296 k
+";
+
+    fn ctx() -> Context {
+        Context::new(Params::new(), false)
+    }
+
+    #[test]
+    fn solves_the_example() {
+        let session = parse(EXAMPLE.as_bytes()).unwrap();
+
+        assert_eq!(part_1(&session, 100000, &ctx()), Some(95437));
+        assert_eq!(part_2(&session, 70000000, 30000000, &ctx()), Some(24933642));
+    }
+
+    #[test]
+    fn files_named_like_directories_are_counted() {
+        let session = parse("$ cd /\n$ ls\n10 dir\n20 subdirs.txt\ndir x\n".as_bytes()).unwrap();
+
+        assert_eq!(session[2], Line::File(10, "dir".to_string()));
+        assert_eq!(session[4], Line::Dir("x".to_string()));
+        assert_eq!(get_directory_sizes(&session), vec![30]);
+    }
+
+    #[test]
+    fn rejects_sessions_that_leave_the_outermost_directory() {
+        let e = parse("$ cd /\n$ cd ..\n".as_bytes()).unwrap_err();
+        assert!(e.to_string().contains("line 2, column 1"), "{}", e);
+
+        let e = parse("$ ls\n10 a\n".as_bytes()).unwrap_err();
+        assert!(e.to_string().contains("line 2, column 1"), "{}", e);
+    }
+
+    #[test]
+    fn rejects_invalid_lines() {
+        for input in ["$ rm -rf /\n", "$ cd /\nten a\n", "$ cd /\n10\n", "$ cd\n"] {
+            assert!(parse(input.as_bytes()).is_err(), "{:?}", input);
+        }
+        assert!(parse(&[0xff, b'\n'][..]).is_err());
+    }
+}
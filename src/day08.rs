@@ -1,172 +1,161 @@
+use crate::geometry::{Point, Vector};
+use crate::grid::Grid;
+use crate::parsing::parse_all;
+use crate::registry::Solver;
 use std::io;
 use std::io::prelude::*;
 
-pub fn solve(input: impl BufRead, part: u8) -> io::Result<()> {
-    let parsed_input = parse(input)?;
-
-    let now = std::time::Instant::now();
-    let solution = match part {
-        0 => (part_1(&parsed_input), part_2(&parsed_input)),
-        1 => (part_1(&parsed_input), None),
-        2 => (None, part_2(&parsed_input)),
-        _ => unimplemented!(),
-    };
-    let time = now.elapsed().as_micros();
-
-    match solution.0 {
-        Some(x) => println!("Part 1: {}", x),
-        None => println!(),
-    }
-    match solution.1 {
-        Some(x) => println!("Part 2: {}", x),
-        None => println!(),
-    }
-
-    println!("Time elapsed: {} µs", time);
+pub fn solvers() -> Vec<Solver> {
+    vec![
+        Solver::variant(8, 1, "scan", |input, _| {
+            Ok(part_1(&parse(input.as_bytes())?).map(|x| x.to_string()))
+        }),
+        Solver::variant(8, 2, "scan", |input, _| {
+            Ok(part_2(&parse(input.as_bytes())?).map(|x| x.to_string()))
+        }),
+        Solver::variant(8, 1, "stacks", |input, _| {
+            Ok(part_1_stacks(&parse(input.as_bytes())?).map(|x| x.to_string()))
+        }),
+        Solver::variant(8, 2, "stacks", |input, _| {
+            Ok(part_2_stacks(&parse(input.as_bytes())?).map(|x| x.to_string()))
+        }),
+    ]
+}
 
-    Ok(())
+pub fn parse(input: impl BufRead) -> io::Result<Grid<u8>> {
+    // collect grid of tree heights
+    parse_all(input, |cursor| {
+        Grid::parse(cursor, |c| c.to_digit(10).map(|d| d as u8))
+    })
 }
 
-struct TreeGrid {
-    rows: Vec<Vec<u8>>,
-    cols: Vec<Vec<u8>>,
+/// The heights of the trees seen from `from` looking in `direction`, up to the edge.
+fn view(grid: &Grid<u8>, from: Point, direction: Vector) -> impl Iterator<Item = u8> + '_ {
+    grid.ray(from, direction).map(|(_, &height)| height)
 }
 
-impl TreeGrid {
-    fn new(grid: &Vec<Vec<u8>>) -> Self {
-        let mut cols: Vec<Vec<u8>> = vec![Vec::with_capacity(grid[0].len()); grid.len()];
-        for r in grid {
-            for (j, c) in r.iter().enumerate() {
-                cols[j].push(*c);
-            }
-        }
+fn part_1(grid: &Grid<u8>) -> Option<u64> {
+    let mut visible_trees: u64 = 0;
 
-        TreeGrid {
-            rows: grid.clone(),
-            cols,
-        }
-    }
+    // iterate through all trees, checking whether they can be seen from any edge (which the trees
+    // on the edge trivially are, with nothing in their view towards it)
+    for tree in grid.points() {
+        let tree_height = grid[tree];
 
-    fn get_width(&self) -> usize {
-        self.rows[0].len()
+        if Vector::ORTHOGONAL
+            .into_iter()
+            .any(|direction| view(grid, tree, direction).all(|h| h < tree_height))
+        {
+            visible_trees += 1;
+        }
     }
 
-    fn get_height(&self) -> usize {
-        self.cols[0].len()
-    }
+    Some(visible_trees)
 }
 
-fn parse(input: impl BufRead) -> io::Result<Vec<Vec<u8>>> {
-    // collect grid
-    Ok(input
-        .lines()
-        .map(|x| {
-            x.unwrap()
-                .chars()
-                .map(|x| x.to_string().parse::<u8>().unwrap())
-                .collect::<Vec<u8>>()
-        })
-        .collect())
-}
+fn part_2(grid: &Grid<u8>) -> Option<u32> {
+    let mut scenic_scores: Vec<u32> = Vec::new();
 
-fn part_1(grid: &Vec<Vec<u8>>) -> Option<u64> {
-    let tree_grid = TreeGrid::new(grid);
-
-    let grid_height: usize = tree_grid.get_height();
-    let grid_width: usize = tree_grid.get_width();
-
-    let n_edge_trees: usize = 2 * (grid_width + grid_height) - 4;
-    let mut visible_trees: u64 = n_edge_trees as u64;
-
-    // iterate through all trees
-    for i in 1..(grid_width - 1) {
-        for j in 1..(grid_height - 1) {
-            let tree_height = &grid[i][j];
-
-            // check horizontal
-            let check_left: bool = tree_grid.rows[i]
-                .iter()
-                .enumerate()
-                .filter(|(c, _)| c < &j)
-                .all(|(_, v)| v < tree_height);
-            let check_right: bool = tree_grid.rows[i]
-                .iter()
-                .enumerate()
-                .filter(|(c, _)| c > &j)
-                .all(|(_, v)| v < tree_height);
-
-            // check vertical
-            let check_top: bool = tree_grid.cols[j]
-                .iter()
-                .enumerate()
-                .filter(|(r, _)| r < &i)
-                .all(|(_, v)| v < tree_height);
-            let check_bottom: bool = tree_grid.cols[j]
-                .iter()
-                .enumerate()
-                .filter(|(r, _)| r > &i)
-                .all(|(_, v)| v < tree_height);
-
-            if check_left || check_right || check_top || check_bottom {
-                visible_trees += 1;
+    // iterate through all trees, where those on the edge see nothing towards it and score 0
+    for tree in grid.points() {
+        let tree_height = grid[tree];
+
+        // the viewing distance in every direction, up to and including the first tree as tall
+        let scenic_score = Vector::ORTHOGONAL.into_iter().map(|direction| {
+            let mut distance: u32 = 0;
+            for neighbour in view(grid, tree, direction) {
+                distance += 1;
+                if neighbour >= tree_height {
+                    break;
+                }
             }
-        }
+
+            distance
+        });
+
+        scenic_scores.push(scenic_score.product());
     }
 
-    Some(visible_trees)
+    // an empty grid has no tree to score
+    scenic_scores.into_iter().max()
 }
 
-fn part_2(grid: &Vec<Vec<u8>>) -> Option<u32> {
-    let tree_grid = TreeGrid::new(grid);
-
-    let grid_height: usize = tree_grid.get_height();
-    let grid_width: usize = tree_grid.get_width();
+fn sight_lines(line: &[u8]) -> Vec<(bool, u32)> {
+    /* For every tree in the line, look back towards the start of the line: is the tree visible
+     * from that edge, and how far is the view? A monotonic stack holds the trees that can still
+     * block the view of later trees, i.e. those not overshadowed by a taller tree after them. */
+    let mut stack: Vec<usize> = Vec::new();
+    let mut sights: Vec<(bool, u32)> = Vec::with_capacity(line.len());
+
+    for (j, height) in line.iter().enumerate() {
+        while let Some(&k) = stack.last() {
+            if &line[k] >= height {
+                break;
+            }
+            stack.pop();
+        }
 
-    let mut scenic_scores: Vec<u32> = Vec::new();
+        sights.push(match stack.last() {
+            Some(&k) => (false, (j - k) as u32),
+            None => (true, j as u32),
+        });
+        stack.push(j);
+    }
 
-    // iterate through all trees
-    for i in 1..(grid_width - 1) {
-        for j in 1..(grid_height - 1) {
-            let mut scenic_score: Vec<u32> = vec![0; 4];
+    sights
+}
 
-            // check horizontal
-            let row_left = tree_grid.rows[i][0..j].iter().rev();
+fn sight_grid(grid: &Grid<u8>) -> Grid<(bool, u32)> {
+    /* Combine the sight lines in all four directions: a tree is visible if it is visible from
+     * any edge, and its scenic score is the product of its viewing distances. */
+    let mut sights: Grid<(bool, u32)> = grid.map(|_| (false, 1));
 
-            for neighbour in row_left {
-                scenic_score[0] += 1;
-                if neighbour >= &grid[i][j] {
-                    break;
-                }
-            }
+    let mut combine = |x: usize, y: usize, sight: (bool, u32)| {
+        let cell = &mut sights[Point::at(x, y)];
+        cell.0 |= sight.0;
+        cell.1 *= sight.1;
+    };
 
-            let row_right = tree_grid.rows[i][(j + 1)..].iter();
-            for neighbour in row_right {
-                scenic_score[1] += 1;
-                if neighbour >= &grid[i][j] {
-                    break;
-                }
-            }
+    for (y, row) in grid.rows().enumerate() {
+        let reversed: Vec<u8> = row.iter().rev().copied().collect();
+        let n = row.len();
 
-            // check vertical
-            let col_top = tree_grid.cols[j][0..i].iter().rev();
-            for neighbour in col_top {
-                scenic_score[2] += 1;
-                if neighbour >= &grid[i][j] {
-                    break;
-                }
-            }
+        for (x, sight) in sight_lines(row).into_iter().enumerate() {
+            combine(x, y, sight);
+        }
+        for (x, sight) in sight_lines(&reversed).into_iter().enumerate() {
+            combine(n - 1 - x, y, sight);
+        }
+    }
 
-            let col_bottom = tree_grid.cols[j][i + 1..].iter();
-            for neighbour in col_bottom {
-                scenic_score[3] += 1;
-                if neighbour >= &grid[i][j] {
-                    break;
-                }
-            }
+    for x in 0..grid.width() {
+        let col: Vec<u8> = grid.column(x).copied().collect();
+        let reversed: Vec<u8> = col.iter().rev().copied().collect();
+        let n = col.len();
 
-            scenic_scores.push(scenic_score.into_iter().product());
+        for (y, sight) in sight_lines(&col).into_iter().enumerate() {
+            combine(x, y, sight);
+        }
+        for (y, sight) in sight_lines(&reversed).into_iter().enumerate() {
+            combine(x, n - 1 - y, sight);
         }
     }
 
-    Some(scenic_scores.into_iter().max().unwrap())
+    sights
+}
+
+fn part_1_stacks(grid: &Grid<u8>) -> Option<u64> {
+    Some(
+        sight_grid(grid)
+            .iter()
+            .filter(|(_, (visible, _))| *visible)
+            .count() as u64,
+    )
+}
+
+fn part_2_stacks(grid: &Grid<u8>) -> Option<u32> {
+    sight_grid(grid)
+        .iter()
+        .map(|(_, (_, scenic_score))| *scenic_score)
+        .max()
 }
@@ -1,35 +1,25 @@
-use itertools::Itertools;
+use crate::geometry::{Point, Vector};
+use crate::parsing::parse_all;
+use crate::registry::Solver;
+use crate::simulation::{Simulation, Stepper};
+use serde::{Deserialize, Serialize};
 use std::collections::HashSet;
 use std::io;
 use std::io::prelude::*;
 
-pub fn solve(input: impl BufRead, part: u8) -> io::Result<()> {
-    let parsed_input = parse(input)?;
-
-    let now = std::time::Instant::now();
-    let solution = match part {
-        0 => (part_1(&parsed_input), part_2(&parsed_input)),
-        1 => (part_1(&parsed_input), None),
-        2 => (None, part_2(&parsed_input)),
-        _ => unimplemented!(),
-    };
-    let time = now.elapsed().as_micros();
-
-    match solution.0 {
-        Some(x) => println!("Part 1: {}", x),
-        None => println!(),
-    }
-    match solution.1 {
-        Some(x) => println!("Part 2: {}", x),
-        None => println!(),
-    }
-
-    println!("Time elapsed: {} µs", time);
-
-    Ok(())
+pub fn solvers() -> Vec<Solver> {
+    vec![
+        Solver::new(9, 1, |input, _| {
+            Ok(part_1(&parse(input.as_bytes())?).map(|x| x.to_string()))
+        }),
+        Solver::new(9, 2, |input, _| {
+            Ok(part_2(&parse(input.as_bytes())?).map(|x| x.to_string()))
+        }),
+    ]
 }
 
-enum Move {
+#[derive(Debug, Clone, Copy)]
+pub enum Move {
     Up(i32),
     Down(i32),
     Left(i32),
@@ -37,48 +27,53 @@ enum Move {
 }
 
 impl Move {
-    fn from(s: &(String, String)) -> Move {
-        let direction = s.0.as_str();
-        let steps = s.1.parse::<i32>().unwrap();
-
-        match direction {
-            "U" => Move::Up(steps),
-            "D" => Move::Down(steps),
-            "L" => Move::Left(steps),
-            "R" => Move::Right(steps),
-            _ => panic!("Invalid movement!"),
+    /// The direction of a single step of the head.
+    fn direction(self) -> Vector {
+        match self {
+            Move::Up(_) => Vector::UP,
+            Move::Down(_) => Vector::DOWN,
+            Move::Left(_) => Vector::LEFT,
+            Move::Right(_) => Vector::RIGHT,
         }
     }
-}
-
-#[derive(Clone)]
-struct Knot {
-    x: i32,
-    y: i32,
-}
 
-impl Knot {
-    fn new() -> Self {
-        Knot { x: 0, y: 0 }
-    }
-
-    fn get_pos(&self) -> (i32, i32) {
-        (self.x, self.y)
+    fn steps(self) -> i32 {
+        match self {
+            Move::Up(x) | Move::Down(x) | Move::Left(x) | Move::Right(x) => x,
+        }
     }
 }
 
+/// The rope following the motions of its head, where each step of the simulation moves the head
+/// by one.
 struct Rope {
-    knots: Vec<Knot>,
+    knots: Vec<Point>,
     tracked_knot_id: usize,
-    tracker: HashSet<(i32, i32)>,
+    tracker: HashSet<Point>,
+    motions: Vec<Move>,
+    /// The current motion, and the steps of it taken so far.
+    motion: usize,
+    taken: i32,
+}
+
+/// Everything of a rope that changes as it moves.
+#[derive(Clone, Serialize, Deserialize)]
+struct RopeSnapshot {
+    knots: Vec<Point>,
+    tracker: HashSet<Point>,
+    motion: usize,
+    taken: i32,
 }
 
 impl Rope {
-    fn new(n_knots: usize) -> Self {
+    fn new(n_knots: usize, motions: &[Move]) -> Self {
         let mut rope = Rope {
-            knots: vec![Knot::new(); n_knots],
+            knots: vec![Point::ORIGIN; n_knots],
             tracked_knot_id: n_knots - 1,
             tracker: HashSet::new(),
+            motions: motions.to_vec(),
+            motion: 0,
+            taken: 0,
         };
 
         rope.track();
@@ -86,94 +81,101 @@ impl Rope {
         rope
     }
 
-    fn move_head(&mut self, motion: Move) -> () {
-        match motion {
-            Move::Up(x) => {
-                for _ in 0..x {
-                    self.knots[0].y += 1;
-                    self.move_knots();
-                }
-            }
-            Move::Down(x) => {
-                for _ in 0..x {
-                    self.knots[0].y -= 1;
-                    self.move_knots();
-                }
-            }
-            Move::Left(x) => {
-                for _ in 0..x {
-                    self.knots[0].x -= 1;
-                    self.move_knots();
-                }
-            }
-            Move::Right(x) => {
-                for _ in 0..x {
-                    self.knots[0].x += 1;
-                    self.move_knots();
-                }
-            }
-        };
-
-        self.track();
-    }
-
-    fn move_knots(&mut self) -> () {
+    fn move_knots(&mut self) {
         for i in 1..self.knots.len() {
-            let (dx, dy) = get_relative_pos(&self.knots[i - 1], &self.knots[i]);
-            if dx.abs() == 2 || dy.abs() == 2 {
-                if dy == 0 {
-                    self.knots[i].x = self.knots[i].x + dx.signum();
-                } else if dx == 0 {
-                    self.knots[i].y = self.knots[i].y + dy.signum();
-                } else {
-                    self.knots[i].x = self.knots[i].x + dx.signum();
-                    self.knots[i].y = self.knots[i].y + dy.signum();
-                }
+            // a knot only follows once it no longer touches the one in front of it
+            if self.knots[i].chebyshev(self.knots[i - 1]) == 2 {
+                self.knots[i] = self.knots[i].step_towards(self.knots[i - 1]);
             }
 
             self.track();
         }
     }
 
-    fn track(&mut self) -> () {
-        self.tracker
-            .insert(self.knots[self.tracked_knot_id].get_pos());
+    fn track(&mut self) {
+        self.tracker.insert(self.knots[self.tracked_knot_id]);
     }
 }
 
-fn get_relative_pos(knot_1: &Knot, knot_2: &Knot) -> (i32, i32) {
-    (knot_1.x - knot_2.x, knot_1.y - knot_2.y)
+impl Simulation for Rope {
+    type Snapshot = RopeSnapshot;
+
+    fn step(&mut self) -> bool {
+        // move on to the next motion with steps left to take
+        while self
+            .motions
+            .get(self.motion)
+            .is_some_and(|m| self.taken >= m.steps())
+        {
+            self.motion += 1;
+            self.taken = 0;
+        }
+        let Some(motion) = self.motions.get(self.motion) else {
+            return false;
+        };
+
+        self.knots[0] += motion.direction();
+        self.move_knots();
+        self.taken += 1;
+
+        true
+    }
+
+    fn snapshot(&self) -> RopeSnapshot {
+        RopeSnapshot {
+            knots: self.knots.clone(),
+            tracker: self.tracker.clone(),
+            motion: self.motion,
+            taken: self.taken,
+        }
+    }
+
+    fn restore(&mut self, snapshot: RopeSnapshot) {
+        self.knots = snapshot.knots;
+        self.tracker = snapshot.tracker;
+        self.motion = snapshot.motion;
+        self.taken = snapshot.taken;
+    }
 }
 
-fn parse(input: impl BufRead) -> io::Result<Vec<(String, String)>> {
-    Ok(input
-        .lines()
-        .map(|x| {
-            x.unwrap()
-                .split_whitespace()
-                .map(|x| x.to_string())
-                .collect_tuple()
-                .unwrap()
-        })
-        .collect::<Vec<(String, String)>>())
+pub fn parse(input: impl BufRead) -> io::Result<Vec<Move>> {
+    // one move per line, e.g. "R 4"
+    parse_all(input, |cursor| {
+        cursor
+            .lines()
+            .into_iter()
+            .map(|line| {
+                line.all(|l| {
+                    let start = *l;
+                    let direction = l.word()?;
+                    l.expect(" ")?;
+                    let steps = l.integer::<i32>()?;
+
+                    match direction {
+                        "U" => Ok(Move::Up(steps)),
+                        "D" => Ok(Move::Down(steps)),
+                        "L" => Ok(Move::Left(steps)),
+                        "R" => Ok(Move::Right(steps)),
+                        _ => Err(start.error(format!("invalid direction \"{}\"", direction))),
+                    }
+                })
+            })
+            .collect()
+    })
 }
 
-fn part_1(movements: &Vec<(String, String)>) -> Option<usize> {
-    let mut rope = Rope::new(2);
+fn part_1(movements: &[Move]) -> Option<usize> {
+    let mut rope = Stepper::new(Rope::new(2, movements));
 
-    movements
-        .into_iter()
-        .for_each(|x| rope.move_head(Move::from(x)));
+    while rope.step() {}
 
-    Some(rope.tracker.len())
+    Some(rope.simulation().tracker.len())
 }
 
-fn part_2(movements: &Vec<(String, String)>) -> Option<usize> {
-    let mut rope = Rope::new(10);
+fn part_2(movements: &[Move]) -> Option<usize> {
+    let mut rope = Stepper::new(Rope::new(10, movements));
 
-    movements
-        .into_iter()
-        .for_each(|x| rope.move_head(Move::from(x)));
+    while rope.step() {}
 
-    Some(rope.tracker.len())
+    Some(rope.simulation().tracker.len())
 }
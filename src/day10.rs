@@ -1,72 +1,124 @@
+use crate::geometry::Point;
+use crate::grid::Grid;
+use crate::parsing::parse_all;
+use crate::registry::Solver;
+use crate::simulation::{Simulation, Stepper};
+use serde::{Deserialize, Serialize};
 use std::io;
 use std::io::prelude::*;
 
-pub fn solve(input: impl BufRead, part: u8) -> io::Result<()> {
-    let parsed_input = parse(input)?;
-
-    let now = std::time::Instant::now();
-    let solution = match part {
-        0 => (part_1(&parsed_input), part_2(&parsed_input)),
-        1 => (part_1(&parsed_input), None),
-        2 => (None, part_2(&parsed_input)),
-        _ => unimplemented!(),
-    };
-    let time = now.elapsed().as_micros();
-
-    match solution.0 {
-        Some(x) => println!("Part 1: {}", x),
-        None => println!(),
-    }
-    match solution.1 {
-        Some(x) => println!("Part 2: {}", x),
-        None => println!(),
-    }
+pub fn solvers() -> Vec<Solver> {
+    vec![
+        Solver::new(10, 1, |input, _| {
+            Ok(part_1(&parse(input.as_bytes())?).map(|x| x.to_string()))
+        }),
+        Solver::new(10, 2, |input, _| {
+            Ok(part_2(&parse(input.as_bytes())?)?.map(|x| x.to_string()))
+        }),
+    ]
+}
 
-    println!("Time elapsed: {} µs", time);
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Instruction {
+    Noop,
+    Addx(i32),
+}
 
-    Ok(())
+pub fn parse(input: impl BufRead) -> io::Result<Vec<Instruction>> {
+    // one instruction per line, "noop" or e.g. "addx -5"
+    parse_all(input, |cursor| {
+        cursor
+            .lines()
+            .into_iter()
+            .map(|line| {
+                line.all(|l| match l.eat("noop") {
+                    true => Ok(Instruction::Noop),
+                    false => {
+                        l.expect("addx ")?;
+                        Ok(Instruction::Addx(l.integer()?))
+                    }
+                })
+            })
+            .collect()
+    })
 }
 
-fn parse(input: impl BufRead) -> io::Result<Vec<Vec<String>>> {
-    // parse commands
-    Ok(input
-        .lines()
-        .map(|x| {
-            x.unwrap()
-                .split_whitespace()
-                .map(|s| s.to_string())
-                .collect()
-        })
-        .collect())
+/// The CPU running the program, where each step of the simulation is a clock cycle.
+struct Cpu {
+    program: Vec<Instruction>,
+    state: CpuState,
 }
 
-fn part_1(commands: &Vec<Vec<String>>) -> Option<i32> {
-    let mut cycle: i32 = 0;
-    let mut x: i32 = 1;
-    let mut sum: i32 = 0;
-    let mut counter: i32 = 0;
-
-    for command in commands.iter() {
-        match command.len() {
-            1 => {
-                cycle += 1;
-                if cycle - (20 + counter * 40) == 0 {
-                    sum += cycle * x;
-                    counter += 1;
-                }
-            }
-            2 => {
-                for _ in 0..2 {
-                    cycle += 1;
-                    if cycle - (20 + counter * 40) == 0 {
-                        sum += cycle * x;
-                        counter += 1;
-                    }
-                }
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+struct CpuState {
+    /// The instruction being executed, and whether its first cycle is done.
+    pc: usize,
+    busy: bool,
+    /// The cycles completed so far.
+    cycle: i32,
+    /// The register as it was during the last cycle, and as it is after it.
+    during: i32,
+    x: i32,
+}
+
+impl Cpu {
+    fn new(program: &[Instruction]) -> Self {
+        Cpu {
+            program: program.to_vec(),
+            state: CpuState {
+                pc: 0,
+                busy: false,
+                cycle: 0,
+                during: 1,
+                x: 1,
+            },
+        }
+    }
+}
 
-                x += command[1].parse::<i32>().unwrap();
+impl Simulation for Cpu {
+    type Snapshot = CpuState;
+
+    fn step(&mut self) -> bool {
+        let state = &mut self.state;
+        let Some(instruction) = self.program.get(state.pc) else {
+            return false;
+        };
+
+        state.cycle += 1;
+        state.during = state.x;
+
+        match instruction {
+            Instruction::Addx(_) if !state.busy => state.busy = true,
+            Instruction::Addx(value) => {
+                state.x += value;
+                state.busy = false;
+                state.pc += 1;
             }
-            _ => panic!("Invalid length!"),
+            Instruction::Noop => state.pc += 1,
+        }
+
+        true
+    }
+
+    fn snapshot(&self) -> CpuState {
+        self.state
+    }
+
+    fn restore(&mut self, state: CpuState) {
+        self.state = state;
+    }
+}
+
+fn part_1(commands: &[Instruction]) -> Option<i32> {
+    let mut cpu = Stepper::new(Cpu::new(commands));
+    let mut sum: i32 = 0;
+
+    // the signal strength during the 20th cycle and every 40 cycles after that
+    while cpu.step() {
+        let state = cpu.simulation().state;
+        if (state.cycle - 20) % 40 == 0 {
+            sum += state.cycle * state.during;
         }
     }
 
@@ -74,65 +126,46 @@ fn part_1(commands: &Vec<Vec<String>>) -> Option<i32> {
 }
 
 struct Screen {
-    width: usize,
-    pixels: Vec<bool>,
-    cycle: i32,
-    xpos: i32,
+    pixels: Grid<bool>,
 }
 
 impl Screen {
-    fn new(width: usize, height: usize) -> Self {
-        Screen {
-            width,
-            pixels: vec![false; width * height],
-            cycle: 0,
-            xpos: 1,
-        }
+    fn new(width: usize, height: usize) -> io::Result<Self> {
+        Ok(Screen {
+            pixels: Grid::new(width, height, false)?,
+        })
     }
 
-    fn switch(&mut self) {
-        self.pixels[(self.cycle - 1) as usize] =
-            (0..2).contains(&(self.xpos - (self.cycle - 1) % 40).abs());
+    /// Light the pixel drawn during the given cycle if the sprite covers it.
+    fn switch(&mut self, cycle: i32, xpos: i32) {
+        let width = self.pixels.width();
+        let i = (cycle - 1) as usize;
+        self.pixels[Point::at(i % width, i / width)] = (xpos - (i % width) as i32).abs() < 2;
     }
 
     fn draw(&self) -> String {
         let mut s = String::from("\n");
-        for (i, pixel) in self.pixels.iter().enumerate() {
-            if i % self.width == 0 {
-                s.push_str("\n");
-            }
-
-            if *pixel {
-                s.push('#');
-            } else {
-                s.push('.');
-            }
+        for row in self
+            .pixels
+            .render(|&lit| if lit { '#' } else { '.' })
+            .lines()
+        {
+            s.push('\n');
+            s.push_str(row);
         }
 
         s
     }
 }
 
-fn part_2(commands: &Vec<Vec<String>>) -> Option<String> {
-    let mut crt: Screen = Screen::new(40, 6);
+fn part_2(commands: &[Instruction]) -> io::Result<Option<String>> {
+    let mut crt: Screen = Screen::new(40, 6)?;
+    let mut cpu = Stepper::new(Cpu::new(commands));
 
-    for command in commands.iter() {
-        match command.len() {
-            1 => {
-                crt.cycle += 1;
-                crt.switch();
-            }
-            2 => {
-                crt.cycle += 1;
-                crt.switch();
-                crt.cycle += 1;
-                crt.switch();
-
-                crt.xpos += command[1].parse::<i32>().unwrap();
-            }
-            _ => panic!(),
-        }
+    while cpu.step() {
+        let state = cpu.simulation().state;
+        crt.switch(state.cycle, state.during);
     }
 
-    Some(crt.draw())
+    Ok(Some(crt.draw()))
 }
@@ -1,131 +1,169 @@
-use itertools::Itertools;
+use crate::cache::fnv1a;
+use crate::parsing::{self, parse_all, Cursor};
+use crate::registry::{Context, Solver};
+use crate::simulation::{Simulation, Stepper};
+use serde::{Deserialize, Serialize};
+use std::fs;
 use std::io;
 use std::io::prelude::*;
+use std::path::Path;
+
+pub fn solvers() -> Vec<Solver> {
+    vec![
+        Solver::new(11, 1, |input, ctx| {
+            Ok(part_1(&parse(input.as_bytes())?, ctx)?.map(|x| x.to_string()))
+        }),
+        Solver::new(11, 2, |input, ctx| {
+            Ok(part_2(&parse(input.as_bytes())?, ctx)?.map(|x| x.to_string()))
+        }),
+    ]
+}
 
-pub fn solve(input: impl BufRead, part: u8) -> io::Result<()> {
-    let parsed_input = parse(input)?;
+pub fn parse(input: impl BufRead) -> io::Result<Vec<Monkey>> {
+    parse_all(input, |cursor| {
+        let blocks = cursor.blocks();
+        let mut monkeys: Vec<Monkey> = Vec::new();
+
+        // the monkey business multiplies the inspections of the two busiest monkeys
+        if blocks.len() < 2 {
+            return Err(cursor.error(format!(
+                "expected at least 2 monkeys, found {}",
+                blocks.len()
+            )));
+        }
 
-    let now = std::time::Instant::now();
-    let solution = match part {
-        0 => (part_1(&parsed_input), part_2(&parsed_input)),
-        1 => (part_1(&parsed_input), None),
-        2 => (None, part_2(&parsed_input)),
-        _ => unimplemented!(),
-    };
-    let time = now.elapsed().as_micros();
+        for (i, block) in blocks.iter().enumerate() {
+            let mut block = *block;
+            let start = block;
+            monkeys.push(Monkey::parse(&mut block, i)?);
+            block.end()?;
+
+            let (_, if_true, if_false) = monkeys[i].test;
+            if if_true >= blocks.len() || if_false >= blocks.len() {
+                return Err(start.error(format!(
+                    "monkey {} throws to a monkey that does not exist",
+                    i
+                )));
+            }
+            // the items thrown would come back within the same turn, which would never end
+            if if_true == i || if_false == i {
+                return Err(start.error(format!("monkey {} throws to itself", i)));
+            }
+        }
 
-    match solution.0 {
-        Some(x) => println!("Part 1: {}", x),
-        None => println!(),
-    }
-    match solution.1 {
-        Some(x) => println!("Part 2: {}", x),
-        None => println!(),
-    }
+        Ok(monkeys)
+    })
+}
 
-    println!("Time elapsed: {} µs", time);
+/// The value on the right-hand side of an operation.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+enum Operand {
+    Old,
+    Value(u64),
+}
 
-    Ok(())
+impl Operand {
+    fn value(self, old: u64) -> u64 {
+        match self {
+            Operand::Old => old,
+            Operand::Value(value) => value,
+        }
+    }
 }
 
-fn parse(input: impl BufRead) -> io::Result<Vec<Vec<String>>> {
-    Ok(input
-        .lines()
-        .map(|x| {
-            x.unwrap()
-                .split_whitespace()
-                .skip(2)
-                .map(|s| s.split(',').next().unwrap().to_string())
-                .collect()
-        })
-        .filter(|v: &Vec<String>| !v.is_empty())
-        .collect())
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+enum Operation {
+    Add(Operand),
+    Multiply(Operand),
 }
 
-#[derive(Debug, Clone)]
-struct Monkey {
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Monkey {
     items: Vec<u64>,
-    operation: (String, String),
+    operation: Operation,
+    /// The divisor of the test, and the monkeys to throw to when it passes and when it fails.
     test: (u64, usize, usize),
     inspections: usize,
 }
 
 impl Monkey {
-    fn new(
-        state: (
-            &Vec<String>,
-            &Vec<String>,
-            &Vec<String>,
-            &Vec<String>,
-            &Vec<String>,
-        ),
-    ) -> Self {
-        /* The state input is a bit cryptic due to the way the parsing is handled. Effectively,
-         * each monkey is through the parsing divided into their respective information, with all
-         * lines skipping the first two words as they hold no information for all lines. From here,
-         * which word is of interest depends on the line, and thus we end up with a seemingly
-         * incomprehensible monkey state input. However, it should be as follows:
-         *
-         * state.0: A vector of numbers in String format corresponding to the items.
-         * state.1: A vector of length 4, where the third and fourth element corresponds to the
-         *          operation and operation input.
-         * state.2: A vector of length 2, where the last element is the test value (divisible by X)
-         * state.3: A vector of length 4, where the last element is the target monkey if test is
-         *          true
-         * state.4: A vector of length 4, where the last element is the target monkey if test is
-         *          false
-         *
-         * Everything else is unimportant. */
-
-        // read items
-        let items = state.0.iter().map(|s| s.parse::<u64>().unwrap()).collect();
-
-        // read operation
-        let operation = (state.1[2].clone(), state.1[3].clone());
-
-        // read test
-        let test = (
-            state.2[1].parse::<u64>().unwrap(),
-            state.3[3].parse::<usize>().unwrap(),
-            state.4[3].parse::<usize>().unwrap(),
-        );
+    /// Parse the block describing the `i`th monkey:
+    ///
+    /// Monkey 0:
+    ///   Starting items: 79, 98
+    ///   Operation: new = old * 19
+    ///   Test: divisible by 23
+    ///     If true: throw to monkey 2
+    ///     If false: throw to monkey 3
+    fn parse(cursor: &mut Cursor, i: usize) -> parsing::Result<Self> {
+        cursor.keyword_line("Monkey")?.all(|l| {
+            let start = *l;
+            match l.integer::<usize>()? {
+                n if n == i => l.expect(":"),
+                n => Err(start.error(format!("expected monkey {}, found monkey {}", i, n))),
+            }
+        })?;
+
+        let items = cursor
+            .keyword_line("Starting items:")?
+            .all(|l| match l.is_empty() {
+                true => Ok(Vec::new()),
+                false => l.separated(", ", |n| n.integer()),
+            })?;
+
+        let operation = cursor.keyword_line("Operation:")?.all(|l| {
+            l.expect("new = old ")?;
+            let start = *l;
+            let operator = l.word()?;
+            l.skip_spaces();
+            let operand = match l.eat("old") {
+                true => Operand::Old,
+                false => Operand::Value(l.integer()?),
+            };
+
+            match operator {
+                "+" => Ok(Operation::Add(operand)),
+                "*" => Ok(Operation::Multiply(operand)),
+                _ => Err(start.error(format!(
+                    "invalid operation \"{}\" (expected \"+\" or \"*\")",
+                    operator
+                ))),
+            }
+        })?;
+
+        let divisor = cursor.keyword_line("Test:")?.all(|l| {
+            l.expect("divisible by ")?;
+            let start = *l;
+            match l.integer::<u64>()? {
+                0 => Err(start.error("cannot test for divisibility by 0")),
+                divisor => Ok(divisor),
+            }
+        })?;
+
+        let mut target = |keyword: &str| {
+            cursor.keyword_line(keyword)?.all(|l| {
+                l.expect("throw to monkey ")?;
+                l.integer::<usize>()
+            })
+        };
+        let if_true = target("If true:")?;
+        let if_false = target("If false:")?;
 
-        Monkey {
+        Ok(Monkey {
             items,
             operation,
-            test,
+            test: (divisor, if_true, if_false),
             inspections: 0,
-        }
+        })
     }
 
-    fn inspect_and_throw(&mut self, relief: u64, supermodulo: u64) -> Option<(usize, u64)> {
+    /// Inspect an item, returning the monkey it is thrown to and its new worry level.
+    fn inspect_and_throw(&mut self, mut item: u64, relief: u64, supermodulo: u64) -> (usize, u64) {
         self.inspections += 1;
-        let mut item = match self.items.pop() {
-            Some(x) => x,
-            None => return None,
-        };
 
-        item = match self.operation.0.as_str() {
-            "+" => {
-                (item
-                    + match self.operation.1.as_str() {
-                        "old" => item,
-                        _ => self.operation.1.parse::<u64>().unwrap(),
-                    })
-                    / relief
-            }
-            "*" => {
-                (item
-                    * match self.operation.1.as_str() {
-                        "old" => item,
-                        _ => self.operation.1.parse::<u64>().unwrap(),
-                    })
-                    / relief
-            }
-            _ => panic!(
-                "Invalid operation ({}, but should be \"+\" or \"*\")",
-                self.operation.0
-            ),
+        item = match self.operation {
+            Operation::Add(operand) => (item + operand.value(item)) / relief,
+            Operation::Multiply(operand) => (item * operand.value(item)) / relief,
         };
 
         item = match supermodulo {
@@ -133,104 +171,269 @@ impl Monkey {
             _ => item % supermodulo,
         };
 
-        if item % self.test.0 == 0 {
-            Some((self.test.1, item))
+        if item.is_multiple_of(self.test.0) {
+            (self.test.1, item)
         } else {
-            Some((self.test.2, item))
+            (self.test.2, item)
         }
     }
 }
 
+/// The monkeys playing keep away, where each step of the simulation is a round.
 #[derive(Debug)]
 struct Monkeys {
     monkeys: Vec<Monkey>,
+    /// What the worry levels are divided by after each inspection.
+    relief: u64,
     supermodulo: u64,
 }
 
 impl Monkeys {
-    fn new() -> Self {
+    fn new(monkeys: &[Monkey], relief: u64) -> Self {
         Monkeys {
-            monkeys: Vec::new(),
+            monkeys: monkeys.to_vec(),
+            relief,
             supermodulo: 0,
         }
     }
 
-    fn add(&mut self, monkey: Monkey) {
-        self.monkeys.push(monkey);
-    }
-
     fn compute_supermodulo(&mut self) {
         self.supermodulo = self.monkeys.iter().map(|x| x.test.0).product();
     }
 
-    fn round(&mut self, relief: u64) {
+    fn explain(&self, ctx: &Context) {
+        for (i, monkey) in self.monkeys.iter().enumerate() {
+            ctx.explain(|| format!("Monkey {} inspected items {} times", i, monkey.inspections));
+        }
+    }
+
+    /// The product of the inspections of the two busiest monkeys, or `None` without two monkeys.
+    fn monkey_business(&self) -> Option<usize> {
+        let mut inspections = self
+            .monkeys
+            .iter()
+            .map(|x| x.inspections)
+            .collect::<Vec<usize>>();
+        inspections.sort_unstable_by(|a, b| b.cmp(a));
+
+        match inspections[..] {
+            [first, second, ..] => Some(first * second),
+            _ => None,
+        }
+    }
+}
+
+impl Simulation for Monkeys {
+    type Snapshot = Vec<Monkey>;
+
+    fn step(&mut self) -> bool {
         for i in 0..self.monkeys.len() {
-            while !self.monkeys[i].items.is_empty() {
-                match self.monkeys[i].inspect_and_throw(relief, self.supermodulo) {
-                    Some((target, item)) => self.monkeys[target].items.push(item),
-                    None => panic!(),
-                }
+            while let Some(item) = self.monkeys[i].items.pop() {
+                let (target, item) =
+                    self.monkeys[i].inspect_and_throw(item, self.relief, self.supermodulo);
+                self.monkeys[target].items.push(item);
             }
         }
+
+        true
+    }
+
+    fn snapshot(&self) -> Vec<Monkey> {
+        self.monkeys.clone()
+    }
+
+    fn restore(&mut self, monkeys: Vec<Monkey>) {
+        self.monkeys = monkeys;
     }
 }
 
-fn part_1(input: &Vec<Vec<String>>) -> Option<usize> {
-    let mut monkeys: Monkeys = Monkeys::new();
+/// Identifies a run of the monkeys in a checkpoint: their starting state and the rules of the run.
+fn checkpoint_key(monkeys: &[Monkey], relief: u64, rounds: usize) -> u64 {
+    let state = serde_json::to_string(&(monkeys, relief, rounds)).unwrap();
+    fnv1a(state.as_bytes())
+}
+
+fn part_1(input: &[Monkey], ctx: &Context) -> io::Result<Option<usize>> {
+    let mut monkeys = Stepper::new(Monkeys::new(input, 3));
+
+    monkeys.run_until(|_, rounds| {
+        ctx.check_deadline()?;
+        Ok(rounds == 20)
+    })?;
+
+    monkeys.simulation().explain(ctx);
 
-    for state in input.iter().tuples::<(
-        &Vec<String>,
-        &Vec<String>,
-        &Vec<String>,
-        &Vec<String>,
-        &Vec<String>,
-    )>() {
-        monkeys.add(Monkey::new(state));
+    Ok(monkeys.simulation().monkey_business())
+}
+
+fn part_2(input: &[Monkey], ctx: &Context) -> io::Result<Option<usize>> {
+    const ROUNDS: usize = 10000;
+    const CHECKPOINT_INTERVAL: usize = 1000;
+
+    const RELIEF: u64 = 1;
+
+    let mut simulation = Monkeys::new(input, RELIEF);
+    simulation.compute_supermodulo();
+    let mut monkeys = Stepper::new(simulation);
+
+    // with a checkpoint file, an interrupted run (e.g. one that hit its time limit) carries on
+    // from the last checkpoint the next time, provided it was made with the same monkeys
+    let checkpoint = ctx.params.get("day11.checkpoint", String::new())?;
+    let checkpoint = Some(Path::new(&checkpoint)).filter(|p| !p.as_os_str().is_empty());
+    let key = checkpoint_key(input, RELIEF, ROUNDS);
+    if let Some(path) = checkpoint {
+        if monkeys.resume(path, key)? {
+            ctx.explain(|| {
+                format!(
+                    "Resumed from the checkpoint after round {}",
+                    monkeys.steps()
+                )
+            });
+        }
     }
 
-    for _ in 0..20 {
-        monkeys.round(3);
+    while monkeys.steps() < ROUNDS {
+        let target = (monkeys.steps() / CHECKPOINT_INTERVAL + 1) * CHECKPOINT_INTERVAL;
+        monkeys.run_until(|_, rounds| {
+            ctx.check_deadline()?;
+            ctx.progress(rounds as u64, ROUNDS as u64);
+            Ok(rounds == target.min(ROUNDS))
+        })?;
+
+        if let Some(path) = checkpoint.filter(|_| monkeys.steps() < ROUNDS) {
+            monkeys.checkpoint(path, key)?;
+        }
     }
 
-    let mut inspections = monkeys
-        .monkeys
-        .iter()
-        .map(|x| x.inspections)
-        .collect::<Vec<usize>>();
-    inspections.sort_unstable();
+    // a finished run leaves nothing to resume
+    if let Some(path) = checkpoint.filter(|p| p.is_file()) {
+        fs::remove_file(path)?;
+    }
 
-    let monkey_business = inspections[inspections.len() - 1] * inspections[inspections.len() - 2];
+    monkeys.simulation().explain(ctx);
 
-    Some(monkey_business)
+    Ok(monkeys.simulation().monkey_business())
 }
 
-fn part_2(input: &Vec<Vec<String>>) -> Option<usize> {
-    let mut monkeys: Monkeys = Monkeys::new();
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::registry::Params;
+
+    const EXAMPLE: &str = "\
+Monkey 0:
+  Starting items: 79, 98
+  Operation: new = old * 19
+  Test: divisible by 23
+    If true: throw to monkey 2
+    If false: throw to monkey 3
+
+Monkey 1:
+  Starting items: 54, 65, 75, 74
+  Operation: new = old + 6
+  Test: divisible by 19
+    If true: throw to monkey 2
+    If false: throw to monkey 0
+
+Monkey 2:
+  Starting items: 79, 60, 97
+  Operation: new = old * old
+  Test: divisible by 13
+    If true: throw to monkey 1
+    If false: throw to monkey 3
+
+Monkey 3:
+  Starting items: 74
+  Operation: new = old + 3
+  Test: divisible by 17
+    If true: throw to monkey 0
+    If false: throw to monkey 1
+";
+
+    fn context(checkpoint: &Path) -> Context {
+        let mut params = Params::new();
+        params.insert("day11.checkpoint", checkpoint.to_str().unwrap());
+        Context::new(params, true)
+    }
+
+    /// Leave a checkpoint of part 2 after `rounds` rounds of `monkeys`, as an interrupted run does.
+    fn interrupt(monkeys: &[Monkey], rounds: usize, path: &Path) {
+        let mut simulation = Monkeys::new(monkeys, 1);
+        simulation.compute_supermodulo();
+        let mut stepper = Stepper::new(simulation);
+        stepper.run_until(|_, r| Ok(r == rounds)).unwrap();
+        stepper
+            .checkpoint(path, checkpoint_key(monkeys, 1, 10000))
+            .unwrap();
+    }
 
-    for state in input.iter().tuples::<(
-        &Vec<String>,
-        &Vec<String>,
-        &Vec<String>,
-        &Vec<String>,
-        &Vec<String>,
-    )>() {
-        monkeys.add(Monkey::new(state));
+    fn checkpoint_path(name: &str) -> std::path::PathBuf {
+        std::env::temp_dir().join(format!(
+            "aoc2022-day11-{}-{}.json",
+            name,
+            std::process::id()
+        ))
     }
 
-    monkeys.compute_supermodulo();
+    #[test]
+    fn solves_the_example() {
+        let monkeys = parse(EXAMPLE.as_bytes()).unwrap();
+        let ctx = Context::new(Params::new(), false);
 
-    for _ in 0..10000 {
-        monkeys.round(1);
+        assert_eq!(part_1(&monkeys, &ctx).unwrap(), Some(10605));
     }
 
-    let mut inspections = monkeys
-        .monkeys
-        .iter()
-        .map(|x| x.inspections)
-        .collect::<Vec<usize>>();
-    inspections.sort_unstable();
+    #[test]
+    fn rejects_fewer_than_two_monkeys() {
+        let one = EXAMPLE
+            .split("\n\n")
+            .next()
+            .unwrap()
+            .replace("monkey 2", "monkey 0");
+        let e = parse(one.as_bytes()).unwrap_err();
+        assert!(
+            e.to_string()
+                .contains("expected at least 2 monkeys, found 1"),
+            "{}",
+            e
+        );
 
-    let monkey_business = inspections[inspections.len() - 1] * inspections[inspections.len() - 2];
+        assert!(parse("".as_bytes()).is_err());
+    }
 
-    Some(monkey_business)
+    #[test]
+    fn rejects_a_monkey_throwing_to_itself() {
+        let input = EXAMPLE.replacen(
+            "If false: throw to monkey 3",
+            "If false: throw to monkey 0",
+            1,
+        );
+        let e = parse(input.as_bytes()).unwrap_err();
+        assert!(e.to_string().contains("monkey 0 throws to itself"), "{}", e);
+    }
+
+    #[test]
+    fn resumes_a_checkpoint_of_the_same_input() {
+        let monkeys = parse(EXAMPLE.as_bytes()).unwrap();
+        let path = checkpoint_path("same");
+        interrupt(&monkeys, 3000, &path);
+
+        let ctx = context(&path);
+        assert_eq!(part_2(&monkeys, &ctx).unwrap(), Some(2713310158));
+        assert!(ctx.into_explanation()[0].contains("after round 3000"));
+        assert!(!path.exists());
+    }
+
+    #[test]
+    fn discards_a_checkpoint_of_another_input() {
+        let other = parse(EXAMPLE.replace("79, 98", "80, 98").as_bytes()).unwrap();
+        let path = checkpoint_path("other");
+        interrupt(&other, 3000, &path);
+
+        let monkeys = parse(EXAMPLE.as_bytes()).unwrap();
+        let ctx = context(&path);
+        assert_eq!(part_2(&monkeys, &ctx).unwrap(), Some(2713310158));
+        assert!(!ctx.into_explanation()[0].contains("Resumed"));
+        assert!(!path.exists());
+    }
 }
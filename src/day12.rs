@@ -1,198 +1,95 @@
+use crate::geometry::Point;
+use crate::grid::Grid;
+use crate::parsing::parse_all;
+use crate::registry::{Context, Solver};
 use priority_queue::PriorityQueue;
 use std::cmp::Reverse;
+use std::collections::VecDeque;
 use std::io;
 use std::io::prelude::*;
 
-pub fn solve(input: impl BufRead, part: u8) -> io::Result<()> {
-    let parsed_input = parse(input)?;
-
-    let now = std::time::Instant::now();
-    let solution = match part {
-        0 => (part_1(&parsed_input), part_2(&parsed_input)),
-        1 => (part_1(&parsed_input), None),
-        2 => (None, part_2(&parsed_input)),
-        _ => unimplemented!(),
-    };
-    let time = now.elapsed().as_micros();
-
-    match solution.0 {
-        Some(x) => println!("Part 1: {}", x),
-        None => println!(),
-    }
-    match solution.1 {
-        Some(x) => println!("Part 2: {}", x),
-        None => println!(),
-    }
-
-    println!("Time elapsed: {} µs", time);
-
-    Ok(())
+pub fn solvers() -> Vec<Solver> {
+    vec![
+        Solver::variant(12, 1, "astar", |input, ctx| {
+            Ok(part_1(&parse(input.as_bytes())?, ctx)?.map(|x| x.to_string()))
+        }),
+        Solver::variant(12, 2, "astar", |input, ctx| {
+            Ok(part_2(&parse(input.as_bytes())?, ctx)?.map(|x| x.to_string()))
+        }),
+        Solver::variant(12, 1, "bfs", |input, ctx| {
+            Ok(part_1_bfs(&parse(input.as_bytes())?, ctx).map(|x| x.to_string()))
+        }),
+        Solver::variant(12, 2, "bfs", |input, ctx| {
+            Ok(part_2_bfs(&parse(input.as_bytes())?, ctx).map(|x| x.to_string()))
+        }),
+    ]
 }
 
-fn parse(input: impl BufRead) -> io::Result<Vec<Vec<char>>> {
-    Ok(input
-        .lines()
-        .map(|x| x.unwrap().chars().collect::<Vec<char>>())
-        .collect::<Vec<Vec<char>>>())
-}
-
-fn find_char(map: &Vec<Vec<char>>, target: char) -> Option<(usize, usize)> {
-    for i in 0..map[0].len() {
-        for j in 0..map.len() {
-            if map[j][i] == target {
-                return Some((i, j));
-            }
-        }
-    }
-
-    None
-}
-
-#[derive(Debug, Eq, Hash, Clone)]
-struct Node {
-    x: usize,
-    y: usize,
-}
-
-impl Node {
-    fn new(pos: (usize, usize)) -> Node {
-        Node { x: pos.0, y: pos.1 }
-    }
-
-    fn manhattan(&self, other: &Node) -> Option<u32> {
-        Some((self.x.abs_diff(other.x) + self.y.abs_diff(other.y)) as u32)
-    }
+pub fn parse(input: impl BufRead) -> io::Result<Grid<char>> {
+    // heights a-z, with the start S and the end E
+    parse_all(input, |cursor| {
+        Grid::parse(cursor, |c| {
+            Some(c).filter(|c| c.is_ascii_lowercase() || *c == 'S' || *c == 'E')
+        })
+    })
 }
 
-impl PartialEq for Node {
-    fn eq(&self, other: &Node) -> bool {
-        self.x == other.x && self.y == other.y
-    }
+/// Whether the step from `from` to `to` climbs at most one level.
+fn climbable(map: &Grid<char>, from: Point, to: Point) -> bool {
+    (map[from] as i8 - map[to] as i8) > -2
 }
 
-fn part_1(map: &Vec<Vec<char>>) -> Option<u32> {
+fn part_1(map: &Grid<char>, ctx: &Context) -> io::Result<Option<u32>> {
     let mut map = map.clone();
 
     // get start and goal coordinates
-    let start: Node = Node::new(find_char(&map, 'S').unwrap());
-    let end: Node = Node::new(find_char(&map, 'E').unwrap());
+    let start: Point = map.position(&'S').unwrap();
+    let end: Point = map.position(&'E').unwrap();
 
     // replace "S" and "E" with their altitude values to simplify the A*-algorithm
-    map[start.y][start.x] = 'a';
-    map[end.y][end.x] = 'z';
+    map[start] = 'a';
+    map[end] = 'z';
 
     // initialize the open set and push the coordinates to the starting point ('S')
     // note that the value in the PriorityQueue is the f-value
-    let mut open_set: PriorityQueue<Node, Reverse<u32>> = PriorityQueue::new();
-    open_set.push(start.clone(), Reverse(start.manhattan(&end).unwrap()));
+    let mut open_set: PriorityQueue<Point, Reverse<u32>> = PriorityQueue::new();
+    open_set.push(start, Reverse(start.manhattan(end)));
 
     // initialize the preceeding set, which is initially empty
-    let mut closed_set: PriorityQueue<Node, Reverse<u32>> = PriorityQueue::new();
+    let mut closed_set: PriorityQueue<Point, Reverse<u32>> = PriorityQueue::new();
 
     // A-star
     while !open_set.is_empty() {
+        ctx.check_deadline()?;
+
         let (node, f) = open_set.pop().unwrap();
 
         // compute new g-value from (f - h)
-        let new_g = f.0 - node.manhattan(&end).unwrap() + 1;
+        let new_g = f.0 - node.manhattan(end) + 1;
 
         if node == end {
-            return Some(f.0);
-        }
-
-        let (x, y) = (node.x, node.y);
-
-        // check left
-        if x > 0 {
-            if (map[y][x] as i8 - map[y][x - 1] as i8) > -2 {
-                // create new node
-                let new_node = Node::new((x - 1, y));
-
-                // compute h and f values
-                let new_h: u32 = new_node.manhattan(&end).unwrap();
-                let new_f: u32 = new_g + new_h;
-
-                // check if node exists anywhere, and handle it accordingly
-                match closed_set.get_priority(&new_node) {
-                    Some(p) => {
-                        if p.0 < new_f {
-                            open_set.push_increase(new_node, Reverse(new_f))
-                        } else {
-                            None
-                        }
-                    }
-                    None => open_set.push_increase(new_node, Reverse(new_f)),
-                };
-            }
-        }
-        // check right
-        if x < map[0].len() - 1 {
-            if (map[y][x] as i8 - map[y][x + 1] as i8) > -2 {
-                // create new node
-                let new_node = Node::new((x + 1, y));
-
-                // compute h and f values
-                let new_h: u32 = new_node.manhattan(&end).unwrap();
-                let new_f: u32 = new_g + new_h;
-
-                // check if node exists anywhere, and handle it accordingly
-                match closed_set.get_priority(&new_node) {
-                    Some(p) => {
-                        if p.0 < new_f {
-                            open_set.push_increase(new_node, Reverse(new_f))
-                        } else {
-                            None
-                        }
-                    }
-                    None => open_set.push_increase(new_node, Reverse(new_f)),
-                };
-            }
+            explain_path(start, f.0, ctx);
+            return Ok(Some(f.0));
         }
-        // check down
-        if y < map.len() - 1 {
-            if (map[y][x] as i8 - map[y + 1][x] as i8) > -2 {
-                // create new node
-                let new_node = Node::new((x, y + 1));
 
-                // compute h and f values
-                let new_h: u32 = new_node.manhattan(&end).unwrap();
-                let new_f: u32 = new_g + new_h;
-
-                // check if node exists anywhere, and handle it accordingly
-                match closed_set.get_priority(&new_node) {
-                    Some(p) => {
-                        if p.0 < new_f {
-                            open_set.push_increase(new_node, Reverse(new_f))
-                        } else {
-                            None
-                        }
-                    }
-                    None => open_set.push_increase(new_node, Reverse(new_f)),
-                };
-            }
-        }
-        // check up
-        if y > 0 {
-            if (map[y][x] as i8 - map[y - 1][x] as i8) > -2 {
-                // create new node
-                let new_node = Node::new((x, y - 1));
-
-                // compute h and f values
-                let new_h: u32 = new_node.manhattan(&end).unwrap();
-                let new_f: u32 = new_g + new_h;
-
-                // check if node exists anywhere, and handle it accordingly
-                match closed_set.get_priority(&new_node) {
-                    Some(p) => {
-                        if p.0 < new_f {
-                            open_set.push_increase(new_node, Reverse(new_f))
-                        } else {
-                            None
-                        }
+        for (new_node, _) in map
+            .neighbours(node)
+            .filter(|&(n, _)| climbable(&map, node, n))
+        {
+            // compute f value
+            let new_f: u32 = new_g + new_node.manhattan(end);
+
+            // check if node exists anywhere, and handle it accordingly
+            match closed_set.get_priority(&new_node) {
+                Some(p) => {
+                    if p.0 < new_f {
+                        open_set.push_increase(new_node, Reverse(new_f))
+                    } else {
+                        None
                     }
-                    None => open_set.push_increase(new_node, Reverse(new_f)),
-                };
-            }
+                }
+                None => open_set.push_increase(new_node, Reverse(new_f)),
+            };
         }
 
         closed_set.push(node, f);
@@ -201,162 +98,149 @@ fn part_1(map: &Vec<Vec<char>>) -> Option<u32> {
     panic!("No path found!")
 }
 
-fn part_2(map: &Vec<Vec<char>>) -> Option<u32> {
+fn part_2(map: &Grid<char>, ctx: &Context) -> io::Result<Option<u32>> {
     let mut map = map.clone();
 
     // get start and goal coordinates
-    let start: Node = Node::new(find_char(&map, 'S').unwrap());
-    let end: Node = Node::new(find_char(&map, 'E').unwrap());
+    let start: Point = map.position(&'S').unwrap();
+    let end: Point = map.position(&'E').unwrap();
 
     // replace "S" and "E" with their altitude values to simplify the A*-algorithm
-    map[start.y][start.x] = 'a';
-    map[end.y][end.x] = 'z';
+    map[start] = 'a';
+    map[end] = 'z';
 
     // PART 2 ONLY
     // Find all 'a' to iterate through all possible starting positions
-    let mut vec_start: Vec<Node> = Vec::new();
-    while find_char(&map, 'a').is_some() {
-        let (x, y) = find_char(&map, 'a').unwrap();
-        vec_start.push(Node::new((x, y)));
-        map[y][x] = '.';
-    }
-
-    // iterate through vec_start to replace the dots with 'a' again
-    for node in vec_start.iter() {
-        map[node.y][node.x] = 'a';
-    }
+    let vec_start: Vec<Point> = map.positions(|&c| c == 'a').collect();
 
     // iterate through vec_start and find the route values
     let mut vec_values: Vec<u32> = Vec::new();
+    let mut best_start: Option<(u32, Point)> = None;
 
-    for start in vec_start.into_iter() {
-        println!("RUNNING START: {:?}", start);
+    let total = vec_start.len() as u64;
+    for (i, start) in vec_start.into_iter().enumerate() {
+        ctx.progress(i as u64, total);
 
         // initialize the open set and push the coordinates to the starting point ('S')
         // note that the value in the PriorityQueue is the f-value
-        let mut open_set: PriorityQueue<Node, Reverse<u32>> = PriorityQueue::new();
-        open_set.push(start.clone(), Reverse(start.manhattan(&end).unwrap()));
+        let mut open_set: PriorityQueue<Point, Reverse<u32>> = PriorityQueue::new();
+        open_set.push(start, Reverse(start.manhattan(end)));
 
         // initialize the preceeding set, which is initially empty
-        let mut closed_set: PriorityQueue<Node, Reverse<u32>> = PriorityQueue::new();
+        let mut closed_set: PriorityQueue<Point, Reverse<u32>> = PriorityQueue::new();
 
         // A-star
         while !open_set.is_empty() {
+            ctx.check_deadline()?;
+
             let (node, f) = open_set.pop().unwrap();
 
             // compute new g-value from (f - h)
-            let new_g = f.0 - node.manhattan(&end).unwrap() + 1;
+            let new_g = f.0 - node.manhattan(end) + 1;
 
-            if !vec_values.is_empty() {
-                if &f.0 > vec_values.iter().max().unwrap() {
-                    break;
-                }
+            if !vec_values.is_empty() && &f.0 > vec_values.iter().max().unwrap() {
+                break;
             }
 
             if node == end {
                 vec_values.push(f.0);
-                println!("BREAK!");
+                if best_start.as_ref().is_none_or(|(v, _)| f.0 < *v) {
+                    best_start = Some((f.0, start));
+                }
                 break;
             }
 
-            let (x, y) = (node.x, node.y);
-
-            // check left
-            if x > 0 {
-                if (map[y][x] as i8 - map[y][x - 1] as i8) > -2 {
-                    // create new node
-                    let new_node = Node::new((x - 1, y));
-
-                    // compute h and f values
-                    let new_h: u32 = new_node.manhattan(&end).unwrap();
-                    let new_f: u32 = new_g + new_h;
-
-                    // check if node exists anywhere, and handle it accordingly
-                    match closed_set.get_priority(&new_node) {
-                        Some(p) => {
-                            if p.0 < new_f {
-                                open_set.push_increase(new_node, Reverse(new_f))
-                            } else {
-                                None
-                            }
-                        }
-                        None => open_set.push_increase(new_node, Reverse(new_f)),
-                    };
-                }
-            }
-            // check right
-            if x < map[0].len() - 1 {
-                if (map[y][x] as i8 - map[y][x + 1] as i8) > -2 {
-                    // create new node
-                    let new_node = Node::new((x + 1, y));
-
-                    // compute h and f values
-                    let new_h: u32 = new_node.manhattan(&end).unwrap();
-                    let new_f: u32 = new_g + new_h;
-
-                    // check if node exists anywhere, and handle it accordingly
-                    match closed_set.get_priority(&new_node) {
-                        Some(p) => {
-                            if p.0 < new_f {
-                                open_set.push_increase(new_node, Reverse(new_f))
-                            } else {
-                                None
-                            }
-                        }
-                        None => open_set.push_increase(new_node, Reverse(new_f)),
-                    };
-                }
-            }
-            // check down
-            if y < map.len() - 1 {
-                if (map[y][x] as i8 - map[y + 1][x] as i8) > -2 {
-                    // create new node
-                    let new_node = Node::new((x, y + 1));
-
-                    // compute h and f values
-                    let new_h: u32 = new_node.manhattan(&end).unwrap();
-                    let new_f: u32 = new_g + new_h;
-
-                    // check if node exists anywhere, and handle it accordingly
-                    match closed_set.get_priority(&new_node) {
-                        Some(p) => {
-                            if p.0 < new_f {
-                                open_set.push_increase(new_node, Reverse(new_f))
-                            } else {
-                                None
-                            }
-                        }
-                        None => open_set.push_increase(new_node, Reverse(new_f)),
-                    };
-                }
-            }
-            // check up
-            if y > 0 {
-                if (map[y][x] as i8 - map[y - 1][x] as i8) > -2 {
-                    // create new node
-                    let new_node = Node::new((x, y - 1));
-
-                    // compute h and f values
-                    let new_h: u32 = new_node.manhattan(&end).unwrap();
-                    let new_f: u32 = new_g + new_h;
-
-                    // check if node exists anywhere, and handle it accordingly
-                    match closed_set.get_priority(&new_node) {
-                        Some(p) => {
-                            if p.0 < new_f {
-                                open_set.push_increase(new_node, Reverse(new_f))
-                            } else {
-                                None
-                            }
+            for (new_node, _) in map
+                .neighbours(node)
+                .filter(|&(n, _)| climbable(&map, node, n))
+            {
+                // compute f value
+                let new_f: u32 = new_g + new_node.manhattan(end);
+
+                // check if node exists anywhere, and handle it accordingly
+                match closed_set.get_priority(&new_node) {
+                    Some(p) => {
+                        if p.0 < new_f {
+                            open_set.push_increase(new_node, Reverse(new_f))
+                        } else {
+                            None
                         }
-                        None => open_set.push_increase(new_node, Reverse(new_f)),
-                    };
-                }
+                    }
+                    None => open_set.push_increase(new_node, Reverse(new_f)),
+                };
             }
 
             closed_set.push(node, f);
         }
     }
 
-    vec_values.into_iter().min()
+    ctx.progress(total, total);
+
+    if let Some((length, start)) = &best_start {
+        explain_path(*start, *length, ctx);
+    }
+
+    Ok(vec_values.into_iter().min())
+}
+
+fn distances_to_end(map: &Grid<char>) -> Grid<Option<u32>> {
+    /* Breadth-first search backwards from 'E'. A step from a square to its neighbour is allowed
+     * if the climb from the neighbour to the square is, so a single search gives the length of
+     * the shortest path from every square to the goal. */
+    let mut map = map.clone();
+
+    let start: Point = map.position(&'S').unwrap();
+    let end: Point = map.position(&'E').unwrap();
+
+    map[start] = 'a';
+    map[end] = 'z';
+
+    let mut distances: Grid<Option<u32>> = map.map(|_| None);
+    distances[end] = Some(0);
+
+    let mut queue: VecDeque<Point> = VecDeque::from([end]);
+
+    while let Some(node) = queue.pop_front() {
+        let distance = distances[node].unwrap();
+
+        for (neighbour, _) in map.neighbours(node) {
+            if distances[neighbour].is_none() && climbable(&map, neighbour, node) {
+                distances[neighbour] = Some(distance + 1);
+                queue.push_back(neighbour);
+            }
+        }
+    }
+
+    distances
+}
+
+fn part_1_bfs(map: &Grid<char>, ctx: &Context) -> Option<u32> {
+    let start = map.position(&'S').unwrap();
+
+    let length = distances_to_end(map)[start];
+    if let Some(length) = length {
+        explain_path(start, length, ctx);
+    }
+
+    length
+}
+
+fn part_2_bfs(map: &Grid<char>, ctx: &Context) -> Option<u32> {
+    let distances = distances_to_end(map);
+
+    let best_start = map
+        .positions(|&c| c == 'a' || c == 'S')
+        .filter_map(|p| distances[p].map(|d| (d, p)))
+        .min_by_key(|(d, _)| *d);
+
+    if let Some((length, start)) = &best_start {
+        explain_path(*start, *length, ctx);
+    }
+
+    best_start.map(|(length, _)| length)
+}
+
+fn explain_path(start: Point, length: u32, ctx: &Context) {
+    ctx.explain(|| format!("Starting at {}", start));
+    ctx.explain(|| format!("The shortest path to 'E' takes {} steps", length));
 }
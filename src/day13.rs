@@ -1,88 +1,74 @@
-use itertools::Itertools;
+use crate::parsing::{self, parse_all, Cursor};
+use crate::registry::Solver;
 use std::io;
 use std::io::prelude::*;
 
-pub fn solve(input: impl BufRead, part: u8) -> io::Result<()> {
-    let parsed_input = parse(input)?;
-
-    let now = std::time::Instant::now();
-    let solution = match part {
-        0 => (part_1(&parsed_input), part_2(&parsed_input)),
-        1 => (part_1(&parsed_input), None),
-        2 => (None, part_2(&parsed_input)),
-        _ => unimplemented!(),
-    };
-    let time = now.elapsed().as_micros();
-
-    match solution.0 {
-        Some(x) => println!("Part 1: {}", x),
-        None => println!(),
-    }
-    match solution.1 {
-        Some(x) => println!("Part 2: {}", x),
-        None => println!(),
-    }
-
-    println!("Time elapsed: {} µs", time);
-
-    Ok(())
+pub fn solvers() -> Vec<Solver> {
+    vec![
+        Solver::new(13, 1, |input, _| {
+            Ok(part_1(&parse(input.as_bytes())?).map(|x| x.to_string()))
+        })
+        .stub(),
+        Solver::new(13, 2, |input, _| {
+            Ok(part_2(&parse(input.as_bytes())?).map(|x| x.to_string()))
+        })
+        .stub(),
+    ]
 }
 
-fn parse(input: impl BufRead) -> io::Result<Vec<(Value, Value)>> {
-    Ok(input
-        .lines()
-        .chunks(3)
-        .into_iter()
-        .map(|mut xs| {
-            (
-                Value::parse(&(&mut xs).next().unwrap().unwrap()).0,
-                Value::parse(&(&mut xs).next().unwrap().unwrap()).0,
-            )
-        })
-        .collect::<Vec<(Value, Value)>>())
+pub fn parse(input: impl BufRead) -> io::Result<Vec<(Value, Value)>> {
+    // pairs of packets, one per line, separated by blank lines
+    parse_all(input, |cursor| {
+        cursor
+            .blocks()
+            .into_iter()
+            .map(|block| {
+                block.all(|b| {
+                    let left = b.line()?.all(Value::parse)?;
+                    let right = b.line()?.all(Value::parse)?;
+
+                    Ok((left, right))
+                })
+            })
+            .collect()
+    })
 }
 
 #[derive(Debug)]
-enum Value {
+pub enum Value {
     Single(u8),
     List(Vec<Value>),
 }
 
-impl Value {
-    fn parse(input: &str) -> (Self, &str) {
-        match input.strip_prefix('[') {
-            Some(mut list) => {
-                let mut out = Vec::new();
+/// How deeply the lists of a packet may nest, so that a hostile input fails to parse instead of
+/// overflowing the stack.
+const MAX_DEPTH: usize = 256;
 
-                loop {
-                    if let Some(rest) = list.strip_prefix(',') {
-                        list = rest;
-                    }
-
-                    if let Some(rest) = list.strip_prefix(']') {
-                        return (Value::List(out), rest);
-                    }
+impl Value {
+    /// A packet such as `[1,[2,[]],3]`.
+    fn parse(cursor: &mut Cursor) -> parsing::Result<Self> {
+        Value::parse_nested(cursor, 0)
+    }
 
-                    let (v, rest) = Value::parse(&mut list);
-                    out.push(v);
-                    list = rest;
-                }
+    fn parse_nested(cursor: &mut Cursor, depth: usize) -> parsing::Result<Self> {
+        match cursor.peek() {
+            Some('[') if depth == MAX_DEPTH => {
+                Err(cursor.error(format!("packet nested deeper than {} lists", MAX_DEPTH)))
             }
-            None => {
-                dbg!(input);
-                let ix = input.find(|x: char| !x.is_ascii_digit()).unwrap();
-                let (num, rest) = input.split_at(ix);
-                (Value::Single(num.parse().unwrap()), rest)
+            Some('[') => {
+                Ok(Value::List(cursor.list("[", ",", "]", |c| {
+                    Value::parse_nested(c, depth + 1)
+                })?))
             }
+            _ => Ok(Value::Single(cursor.integer()?)),
         }
     }
 }
 
-fn part_1(map: &[(Value, Value)]) -> Option<u32> {
-    dbg!(map);
+fn part_1(_map: &[(Value, Value)]) -> Option<u32> {
     Some(0)
 }
 
-fn part_2(map: &[(Value, Value)]) -> Option<u32> {
+fn part_2(_map: &[(Value, Value)]) -> Option<u32> {
     Some(0)
 }
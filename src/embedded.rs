@@ -0,0 +1,106 @@
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use crate::vault;
+
+// (path relative to the input directory, content), generated by build.rs
+#[cfg(feature = "embed-inputs")]
+include!(concat!(env!("OUT_DIR"), "/embedded_inputs.rs"));
+
+#[cfg(not(feature = "embed-inputs"))]
+static FILES: &[(&str, &str)] = &[];
+#[cfg(not(feature = "embed-inputs"))]
+static ENCRYPTED: &[(&str, &[u8])] = &[];
+
+/// The key of a file in the tables above, e.g. `day_01/example.txt`.
+fn key(relative: &Path) -> String {
+    let components: Vec<String> = relative
+        .components()
+        .map(|c| c.as_os_str().to_string_lossy().to_string())
+        .collect();
+
+    components.join("/")
+}
+
+/// The embedded copy of a file, given by its path relative to the input directory (e.g.
+/// `day_01.txt` or `day_01/example.txt`). Nothing is embedded without the `embed-inputs` feature.
+pub fn get(relative: &Path) -> Option<&'static str> {
+    let key = key(relative);
+
+    FILES
+        .iter()
+        .find(|(path, _)| *path == key)
+        .map(|(_, content)| *content)
+}
+
+/// The embedded copy of an encrypted file, given by its path relative to the input directory
+/// (e.g. `day_01.txt.enc`).
+pub fn get_encrypted(relative: &Path) -> Option<&'static [u8]> {
+    let key = key(relative);
+
+    ENCRYPTED
+        .iter()
+        .find(|(path, _)| *path == key)
+        .map(|(_, content)| *content)
+}
+
+/// The names (without extension) of the embedded inputs in a subdirectory of the input
+/// directory, e.g. the named inputs of `day_01`, whether in plain text or encrypted.
+pub fn names_in(dir: &str) -> Vec<String> {
+    let plain = FILES.iter().map(|(path, _)| *path);
+    let encrypted = ENCRYPTED
+        .iter()
+        .filter_map(|(path, _)| path.strip_suffix(".enc"));
+
+    let mut names: Vec<String> = plain
+        .chain(encrypted)
+        .filter_map(|path| path.strip_prefix(dir)?.strip_prefix('/'))
+        .filter_map(|name| name.strip_suffix(".txt"))
+        .filter(|name| !name.contains('/'))
+        .map(String::from)
+        .collect();
+    names.sort();
+    names.dedup();
+
+    names
+}
+
+/// The embedded copy of a file below the input directory, if any.
+fn lookup(input_dir: &Path, path: &Path) -> Option<&'static str> {
+    get(path.strip_prefix(input_dir).ok()?)
+}
+
+/// The embedded encrypted copy of a file below the input directory, if any.
+fn lookup_encrypted(input_dir: &Path, path: &Path) -> Option<&'static [u8]> {
+    get_encrypted(&vault::encrypted_path(path.strip_prefix(input_dir).ok()?))
+}
+
+/// Read a file below the input directory. A file on disk takes precedence, then its encrypted
+/// counterpart on disk; when there is neither, the copy embedded at build time is used instead.
+pub fn read_to_string(input_dir: &Path, path: &Path) -> io::Result<String> {
+    match fs::read_to_string(path) {
+        Err(e) if e.kind() == io::ErrorKind::NotFound => {
+            if let Some(content) = vault::read(path)? {
+                return Ok(content);
+            }
+            match (lookup(input_dir, path), lookup_encrypted(input_dir, path)) {
+                (Some(content), _) => Ok(content.to_string()),
+                (None, Some(data)) => {
+                    vault::decrypt_with_key_file(data, &vault::encrypted_path(path))
+                }
+                (None, None) => Err(e),
+            }
+        }
+        result => result,
+    }
+}
+
+/// Whether a file below the input directory exists, on disk or embedded, in plain text or
+/// encrypted.
+pub fn exists(input_dir: &Path, path: &Path) -> bool {
+    path.is_file()
+        || vault::encrypted_path(path).is_file()
+        || lookup(input_dir, path).is_some()
+        || lookup_encrypted(input_dir, path).is_some()
+}
@@ -0,0 +1,99 @@
+use std::io;
+use std::io::prelude::*;
+use std::process::{Command, Stdio};
+use std::thread;
+use std::time::Duration;
+
+use crate::registry::Context;
+
+/// How often a running program is polled while a time limit applies.
+const POLL_INTERVAL: Duration = Duration::from_millis(1);
+
+/// Run an external solver on the input of one part. `command` is the program followed by its
+/// arguments, which are passed as they are, without going through a shell. The protocol is
+/// deliberately small, so that a solver can be written in any language:
+///
+/// - the puzzle input is written to its standard input;
+/// - `AOC_DAY` and `AOC_PART` tell it which part to solve, and `AOC_EXPLAIN` is set to `1` in
+///   explain mode;
+/// - everything it prints to its standard output is the answer, without surrounding whitespace,
+///   and printing nothing means there is no answer;
+/// - in explain mode, the lines it prints to its standard error are the explanation;
+/// - a non-zero exit status is a failure, reported with the last line of its standard error.
+pub fn solve(
+    command: &[String],
+    day: u8,
+    part: u8,
+    input: &str,
+    ctx: &Context,
+) -> io::Result<Option<String>> {
+    let (program, args) = command.split_first().ok_or_else(|| {
+        io::Error::new(io::ErrorKind::InvalidInput, "The external command is empty")
+    })?;
+
+    let mut child = Command::new(program)
+        .args(args)
+        .env("AOC_DAY", day.to_string())
+        .env("AOC_PART", part.to_string())
+        .env("AOC_EXPLAIN", if ctx.explaining() { "1" } else { "0" })
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| io::Error::new(e.kind(), format!("Could not run {}: {}", program, e)))?;
+
+    // feed and drain the pipes on threads of their own, so that a program writing a lot before
+    // reading all of its input cannot block on a full pipe
+    let mut stdin = child.stdin.take().unwrap();
+    let mut stdout = child.stdout.take().unwrap();
+    let mut stderr = child.stderr.take().unwrap();
+    let input = input.to_string();
+    // a program that does not read its input closes the pipe early, which is not an error
+    let writer = thread::spawn(move || {
+        let _ = stdin.write_all(input.as_bytes());
+    });
+    let out_reader = thread::spawn(move || {
+        let mut out = String::new();
+        stdout.read_to_string(&mut out).map(|_| out)
+    });
+    let err_reader = thread::spawn(move || {
+        let mut err = String::new();
+        stderr.read_to_string(&mut err).map(|_| err)
+    });
+
+    let status = match ctx.deadline() {
+        None => child.wait()?,
+        Some(_) => loop {
+            if let Some(status) = child.try_wait()? {
+                break status;
+            }
+            if let Err(e) = ctx.check_deadline() {
+                let _ = child.kill();
+                let _ = child.wait();
+                return Err(e);
+            }
+            thread::sleep(POLL_INTERVAL);
+        },
+    };
+
+    let _ = writer.join();
+    let out = out_reader.join().unwrap()?;
+    let err = err_reader.join().unwrap()?;
+
+    if !status.success() {
+        let message = match err.lines().rev().find(|l| !l.trim().is_empty()) {
+            Some(line) => format!("{} failed ({}): {}", program, status, line.trim()),
+            None => format!("{} failed ({})", program, status),
+        };
+        return Err(io::Error::other(message));
+    }
+
+    for line in err.lines() {
+        ctx.explain(|| line.to_string());
+    }
+
+    match out.trim() {
+        "" => Ok(None),
+        answer => Ok(Some(answer.to_string())),
+    }
+}
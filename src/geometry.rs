@@ -0,0 +1,382 @@
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::ops::{Add, AddAssign, Mul, Neg, Sub, SubAssign};
+
+/// A position on a 2D grid, with `x` growing to the right and `y` growing down, as the columns and
+/// rows of a `Grid` do. Directions and rotations follow the same convention, so `Vector::UP` is
+/// (0, -1) and turning left from `Vector::RIGHT` faces up.
+#[derive(
+    Debug, Clone, Copy, Default, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize,
+)]
+pub struct Point {
+    pub x: i32,
+    pub y: i32,
+}
+
+/// The displacement between two points.
+#[derive(
+    Debug, Clone, Copy, Default, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize,
+)]
+pub struct Vector {
+    pub x: i32,
+    pub y: i32,
+}
+
+impl Point {
+    pub const ORIGIN: Point = Point { x: 0, y: 0 };
+
+    pub const fn new(x: i32, y: i32) -> Self {
+        Point { x, y }
+    }
+
+    /// The point at a column and row of a grid.
+    pub fn at(col: usize, row: usize) -> Self {
+        Point::new(col as i32, row as i32)
+    }
+
+    pub fn manhattan(self, other: Point) -> u32 {
+        (other - self).manhattan()
+    }
+
+    pub fn chebyshev(self, other: Point) -> u32 {
+        (other - self).chebyshev()
+    }
+
+    /// One step towards `other`, moving at most one along each axis (i.e. diagonally when both
+    /// coordinates differ).
+    pub fn step_towards(self, other: Point) -> Point {
+        self + (other - self).signum()
+    }
+
+    /// The four orthogonal neighbours, in the order of `Vector::ORTHOGONAL`.
+    pub fn neighbours(self) -> impl Iterator<Item = Point> {
+        Vector::ORTHOGONAL.into_iter().map(move |d| self + d)
+    }
+
+    /// The eight neighbours, diagonals included, in the order of `Vector::ADJACENT`.
+    pub fn all_neighbours(self) -> impl Iterator<Item = Point> {
+        Vector::ADJACENT.into_iter().map(move |d| self + d)
+    }
+}
+
+impl Vector {
+    pub const ZERO: Vector = Vector { x: 0, y: 0 };
+    pub const UP: Vector = Vector { x: 0, y: -1 };
+    pub const DOWN: Vector = Vector { x: 0, y: 1 };
+    pub const LEFT: Vector = Vector { x: -1, y: 0 };
+    pub const RIGHT: Vector = Vector { x: 1, y: 0 };
+
+    /// The unit steps to the four orthogonal neighbours.
+    pub const ORTHOGONAL: [Vector; 4] = [Vector::LEFT, Vector::RIGHT, Vector::UP, Vector::DOWN];
+
+    /// The unit steps to the eight neighbours, going around clockwise (as seen on a grid whose rows
+    /// go down) from the right.
+    pub const ADJACENT: [Vector; 8] = [
+        Vector::new(1, 0),
+        Vector::new(1, 1),
+        Vector::new(0, 1),
+        Vector::new(-1, 1),
+        Vector::new(-1, 0),
+        Vector::new(-1, -1),
+        Vector::new(0, -1),
+        Vector::new(1, -1),
+    ];
+
+    pub const fn new(x: i32, y: i32) -> Self {
+        Vector { x, y }
+    }
+
+    pub fn manhattan(self) -> u32 {
+        self.x.unsigned_abs() + self.y.unsigned_abs()
+    }
+
+    pub fn chebyshev(self) -> u32 {
+        self.x.unsigned_abs().max(self.y.unsigned_abs())
+    }
+
+    /// The sign of each coordinate, i.e. the single step in the direction of the vector.
+    pub fn signum(self) -> Vector {
+        Vector::new(self.x.signum(), self.y.signum())
+    }
+
+    /// Rotated by 90 degrees counterclockwise, e.g. from right to up.
+    pub fn rotate_left(self) -> Vector {
+        Vector::new(self.y, -self.x)
+    }
+
+    /// Rotated by 90 degrees clockwise, e.g. from right to down.
+    pub fn rotate_right(self) -> Vector {
+        Vector::new(-self.y, self.x)
+    }
+}
+
+impl fmt::Display for Point {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "({}, {})", self.x, self.y)
+    }
+}
+
+impl fmt::Display for Vector {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "<{}, {}>", self.x, self.y)
+    }
+}
+
+impl Add<Vector> for Point {
+    type Output = Point;
+
+    fn add(self, v: Vector) -> Point {
+        Point::new(self.x + v.x, self.y + v.y)
+    }
+}
+
+impl AddAssign<Vector> for Point {
+    fn add_assign(&mut self, v: Vector) {
+        *self = *self + v;
+    }
+}
+
+impl Sub<Vector> for Point {
+    type Output = Point;
+
+    fn sub(self, v: Vector) -> Point {
+        Point::new(self.x - v.x, self.y - v.y)
+    }
+}
+
+impl SubAssign<Vector> for Point {
+    fn sub_assign(&mut self, v: Vector) {
+        *self = *self - v;
+    }
+}
+
+impl Sub for Point {
+    type Output = Vector;
+
+    fn sub(self, other: Point) -> Vector {
+        Vector::new(self.x - other.x, self.y - other.y)
+    }
+}
+
+impl Add for Vector {
+    type Output = Vector;
+
+    fn add(self, v: Vector) -> Vector {
+        Vector::new(self.x + v.x, self.y + v.y)
+    }
+}
+
+impl AddAssign for Vector {
+    fn add_assign(&mut self, v: Vector) {
+        *self = *self + v;
+    }
+}
+
+impl Sub for Vector {
+    type Output = Vector;
+
+    fn sub(self, v: Vector) -> Vector {
+        Vector::new(self.x - v.x, self.y - v.y)
+    }
+}
+
+impl SubAssign for Vector {
+    fn sub_assign(&mut self, v: Vector) {
+        *self = *self - v;
+    }
+}
+
+impl Mul<i32> for Vector {
+    type Output = Vector;
+
+    fn mul(self, k: i32) -> Vector {
+        Vector::new(self.x * k, self.y * k)
+    }
+}
+
+impl Neg for Vector {
+    type Output = Vector;
+
+    fn neg(self) -> Vector {
+        Vector::new(-self.x, -self.y)
+    }
+}
+
+/// The smallest axis-aligned rectangle holding a set of points, corners included. A box whose
+/// `min` is past its `max` on either axis is empty: it has no points and a width or height of 0.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct BoundingBox {
+    pub min: Point,
+    pub max: Point,
+}
+
+impl BoundingBox {
+    /// The box of a `width` by `height` grid, with its corner at the origin (and empty when either
+    /// side is 0).
+    pub fn grid(width: usize, height: usize) -> Self {
+        BoundingBox {
+            min: Point::ORIGIN,
+            max: Point::new(width as i32 - 1, height as i32 - 1),
+        }
+    }
+
+    /// The box around all the points, or `None` if there are none.
+    pub fn around(points: impl IntoIterator<Item = Point>) -> Option<Self> {
+        let mut points = points.into_iter();
+        let first = points.next()?;
+        let mut bounds = BoundingBox {
+            min: first,
+            max: first,
+        };
+        points.for_each(|p| bounds.extend(p));
+
+        Some(bounds)
+    }
+
+    /// Grow the box to hold `p`.
+    pub fn extend(&mut self, p: Point) {
+        if self.is_empty() {
+            *self = BoundingBox { min: p, max: p };
+            return;
+        }
+
+        self.min = Point::new(self.min.x.min(p.x), self.min.y.min(p.y));
+        self.max = Point::new(self.max.x.max(p.x), self.max.y.max(p.y));
+    }
+
+    pub fn contains(&self, p: Point) -> bool {
+        (self.min.x..=self.max.x).contains(&p.x) && (self.min.y..=self.max.y).contains(&p.y)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.min.x > self.max.x || self.min.y > self.max.y
+    }
+
+    /// The number of columns, or 0 for an empty box.
+    pub fn width(&self) -> usize {
+        match self.is_empty() {
+            true => 0,
+            false => (self.max.x as i64 - self.min.x as i64 + 1) as usize,
+        }
+    }
+
+    /// The number of rows, or 0 for an empty box.
+    pub fn height(&self) -> usize {
+        match self.is_empty() {
+            true => 0,
+            false => (self.max.y as i64 - self.min.y as i64 + 1) as usize,
+        }
+    }
+
+    /// Every point of the box, row by row.
+    pub fn points(&self) -> impl Iterator<Item = Point> {
+        let (min, max) = (self.min, self.max);
+        (min.y..=max.y).flat_map(move |y| (min.x..=max.x).map(move |x| Point::new(x, y)))
+    }
+
+    /// The points of the box that are not on its edge, which is an empty box for a box less than
+    /// 3 wide or high.
+    pub fn interior(&self) -> BoundingBox {
+        BoundingBox {
+            min: self.min + Vector::new(1, 1),
+            max: self.max - Vector::new(1, 1),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn directions_follow_the_rows_of_a_grid() {
+        assert_eq!(Point::new(2, 2) + Vector::UP, Point::new(2, 1));
+        assert_eq!(Point::new(2, 2) + Vector::DOWN, Point::new(2, 3));
+        assert_eq!(Vector::ADJACENT[2], Vector::DOWN);
+        assert_eq!(Vector::ADJACENT[6], Vector::UP);
+    }
+
+    #[test]
+    fn rotations() {
+        assert_eq!(Vector::RIGHT.rotate_left(), Vector::UP);
+        assert_eq!(Vector::UP.rotate_left(), Vector::LEFT);
+        assert_eq!(Vector::RIGHT.rotate_right(), Vector::DOWN);
+        assert_eq!(Vector::DOWN.rotate_right(), Vector::LEFT);
+
+        let v = Vector::new(3, -2);
+        assert_eq!(v.rotate_left().rotate_right(), v);
+        assert_eq!(v.rotate_left().rotate_left(), -v);
+        assert_eq!(
+            v.rotate_right()
+                .rotate_right()
+                .rotate_right()
+                .rotate_right(),
+            v
+        );
+    }
+
+    #[test]
+    fn distances() {
+        let (a, b) = (Point::new(1, -2), Point::new(-3, 4));
+
+        assert_eq!(a.manhattan(b), 10);
+        assert_eq!(a.chebyshev(b), 6);
+        assert_eq!(b.manhattan(a), 10);
+        assert_eq!(a.chebyshev(a), 0);
+        assert_eq!(Vector::new(i32::MIN, 0).manhattan(), 1 << 31);
+    }
+
+    #[test]
+    fn steps_towards_a_point() {
+        let p = Point::new(0, 0);
+
+        assert_eq!(p.step_towards(Point::new(5, -3)), Point::new(1, -1));
+        assert_eq!(p.step_towards(Point::new(0, 2)), Point::new(0, 1));
+        assert_eq!(p.step_towards(p), p);
+    }
+
+    #[test]
+    fn boxes_around_points() {
+        let points = [Point::new(2, 5), Point::new(-1, 3), Point::new(0, 7)];
+        let bounds = BoundingBox::around(points).unwrap();
+
+        assert_eq!(bounds.min, Point::new(-1, 3));
+        assert_eq!(bounds.max, Point::new(2, 7));
+        assert_eq!((bounds.width(), bounds.height()), (4, 5));
+        assert_eq!(bounds.points().count(), 20);
+        assert!(points.iter().all(|&p| bounds.contains(p)));
+        assert!(!bounds.contains(Point::new(3, 5)));
+        assert_eq!(BoundingBox::around([]), None);
+    }
+
+    #[test]
+    fn interiors() {
+        let interior = BoundingBox::grid(4, 3).interior();
+        assert_eq!(
+            (interior.min, interior.max),
+            (Point::new(1, 1), Point::new(2, 1))
+        );
+        assert_eq!((interior.width(), interior.height()), (2, 1));
+
+        for (width, height) in [(2, 2), (1, 5), (5, 2), (0, 0)] {
+            let interior = BoundingBox::grid(width, height).interior();
+            assert!(interior.is_empty(), "{}x{}", width, height);
+            assert_eq!(interior.width() * interior.height(), 0);
+            assert_eq!(interior.points().count(), 0);
+        }
+    }
+
+    #[test]
+    fn empty_boxes() {
+        let mut empty = BoundingBox::grid(0, 0);
+
+        assert!(empty.is_empty());
+        assert_eq!((empty.width(), empty.height()), (0, 0));
+        assert_eq!(BoundingBox::grid(0, 3).width(), 0);
+        assert_eq!(empty.points().count(), 0);
+        assert!(!empty.contains(Point::ORIGIN));
+
+        empty.extend(Point::new(5, 6));
+        assert_eq!(empty, BoundingBox::around([Point::new(5, 6)]).unwrap());
+    }
+}
@@ -0,0 +1,392 @@
+use std::fmt;
+use std::io;
+use std::iter;
+use std::ops::{Index, IndexMut};
+
+use crate::geometry::{BoundingBox, Point, Vector};
+use crate::parsing::{self, Cursor};
+
+/// A dense rectangular grid of cells, stored row by row. Cells are addressed by points, with `x`
+/// the column and `y` the row counting down from the top.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Grid<T> {
+    width: usize,
+    height: usize,
+    cells: Vec<T>,
+}
+
+impl<T> Grid<T> {
+    /// A `width` by `height` grid with every cell set to `fill`, or an error if it would have
+    /// more cells than can be counted. A grid without cells is 0x0, whichever of its sides is 0.
+    pub fn new(width: usize, height: usize, fill: T) -> io::Result<Self>
+    where
+        T: Clone,
+    {
+        let size = width.checked_mul(height).ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("A {}x{} grid has too many cells", width, height),
+            )
+        })?;
+        let (width, height) = match size {
+            0 => (0, 0),
+            _ => (width, height),
+        };
+
+        Ok(Grid {
+            width,
+            height,
+            cells: vec![fill; size],
+        })
+    }
+
+    /// The grid made of the given rows, or `None` if they are not all as wide.
+    pub fn from_rows(rows: Vec<Vec<T>>) -> Option<Self> {
+        let width = rows.first().map_or(0, |r| r.len());
+        if rows.iter().any(|r| r.len() != width) {
+            return None;
+        }
+
+        // rows without cells make a 0x0 grid, as in `new`
+        let height = match width {
+            0 => 0,
+            _ => rows.len(),
+        };
+
+        Some(Grid {
+            width,
+            height,
+            cells: rows.into_iter().flatten().collect(),
+        })
+    }
+
+    /// Parse a character map, one row per line, turning each character into a cell with `cell`.
+    pub fn parse(
+        cursor: &mut Cursor,
+        cell: impl FnMut(char) -> Option<T>,
+    ) -> parsing::Result<Self> {
+        let rows = cursor.grid(cell)?;
+
+        Ok(Grid::from_rows(rows).expect("the rows of a parsed grid are all as wide"))
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.cells.is_empty()
+    }
+
+    pub fn bounds(&self) -> BoundingBox {
+        BoundingBox::grid(self.width, self.height)
+    }
+
+    pub fn contains(&self, p: Point) -> bool {
+        (0..self.width as i32).contains(&p.x) && (0..self.height as i32).contains(&p.y)
+    }
+
+    fn offset(&self, p: Point) -> Option<usize> {
+        match self.contains(p) {
+            true => Some(p.y as usize * self.width + p.x as usize),
+            false => None,
+        }
+    }
+
+    pub fn get(&self, p: Point) -> Option<&T> {
+        self.offset(p).map(|i| &self.cells[i])
+    }
+
+    pub fn get_mut(&mut self, p: Point) -> Option<&mut T> {
+        self.offset(p).map(|i| &mut self.cells[i])
+    }
+
+    /// Every point of the grid, row by row.
+    pub fn points(&self) -> impl Iterator<Item = Point> {
+        let width = self.width;
+        (0..self.cells.len()).map(move |i| Point::at(i % width, i / width))
+    }
+
+    /// Every cell with its point, row by row.
+    pub fn iter(&self) -> impl Iterator<Item = (Point, &T)> {
+        self.points().zip(self.cells.iter())
+    }
+
+    pub fn row(&self, y: usize) -> &[T] {
+        if y >= self.height {
+            panic!(
+                "row {} is outside of the {}x{} grid",
+                y, self.width, self.height
+            );
+        }
+
+        &self.cells[y * self.width..(y + 1) * self.width]
+    }
+
+    pub fn rows(&self) -> impl Iterator<Item = &[T]> {
+        // chunks of an empty grid would still need a non-zero size
+        self.cells.chunks(self.width.max(1))
+    }
+
+    pub fn column(&self, x: usize) -> impl Iterator<Item = &T> {
+        if x >= self.width {
+            panic!(
+                "column {} is outside of the {}x{} grid",
+                x, self.width, self.height
+            );
+        }
+
+        self.cells.iter().skip(x).step_by(self.width)
+    }
+
+    /// The cells from `from` (exclusive) to the edge of the grid, one `direction` at a time.
+    pub fn ray(&self, from: Point, direction: Vector) -> impl Iterator<Item = (Point, &T)> {
+        iter::successors(Some(from + direction), move |&p| Some(p + direction))
+            .map_while(move |p| Some((p, self.get(p)?)))
+    }
+
+    /// The cells of the diagonal through `p` running down to the right, from edge to edge.
+    pub fn diagonal(&self, p: Point) -> impl Iterator<Item = (Point, &T)> {
+        self.line_through(p, Vector::new(1, 1))
+    }
+
+    /// The cells of the diagonal through `p` running down to the left, from edge to edge.
+    pub fn anti_diagonal(&self, p: Point) -> impl Iterator<Item = (Point, &T)> {
+        self.line_through(p, Vector::new(-1, 1))
+    }
+
+    fn line_through(&self, p: Point, direction: Vector) -> impl Iterator<Item = (Point, &T)> {
+        let start = self.ray(p, -direction).last().map_or(p, |(first, _)| first);
+
+        self.get(start)
+            .map(|cell| (start, cell))
+            .into_iter()
+            .chain(self.ray(start, direction))
+    }
+
+    /// The orthogonal neighbours of `p` inside the grid, in the order of `Vector::ORTHOGONAL`.
+    pub fn neighbours(&self, p: Point) -> impl Iterator<Item = (Point, &T)> {
+        p.neighbours().filter_map(move |n| Some((n, self.get(n)?)))
+    }
+
+    /// The neighbours of `p` inside the grid, diagonals included.
+    pub fn all_neighbours(&self, p: Point) -> impl Iterator<Item = (Point, &T)> {
+        p.all_neighbours()
+            .filter_map(move |n| Some((n, self.get(n)?)))
+    }
+
+    /// The first point, row by row, whose cell satisfies `predicate`.
+    pub fn find(&self, predicate: impl Fn(&T) -> bool) -> Option<Point> {
+        self.iter().find(|(_, c)| predicate(c)).map(|(p, _)| p)
+    }
+
+    /// The first point, row by row, holding `value`.
+    pub fn position(&self, value: &T) -> Option<Point>
+    where
+        T: PartialEq,
+    {
+        self.find(|c| c == value)
+    }
+
+    /// Every point whose cell satisfies `predicate`, row by row.
+    pub fn positions<'a>(
+        &'a self,
+        predicate: impl Fn(&T) -> bool + 'a,
+    ) -> impl Iterator<Item = Point> + 'a {
+        self.iter()
+            .filter(move |(_, c)| predicate(c))
+            .map(|(p, _)| p)
+    }
+
+    /// A grid of the same size with `f` applied to every cell.
+    pub fn map<U>(&self, f: impl FnMut(&T) -> U) -> Grid<U> {
+        Grid {
+            width: self.width,
+            height: self.height,
+            cells: self.cells.iter().map(f).collect(),
+        }
+    }
+
+    /// Draw the grid with one character per cell, each row on a line of its own.
+    pub fn render(&self, cell: impl Fn(&T) -> char) -> String {
+        let mut s = String::with_capacity((self.width + 1) * self.height);
+        for row in self.rows() {
+            s.extend(row.iter().map(&cell));
+            s.push('\n');
+        }
+
+        s
+    }
+}
+
+impl<T> Index<Point> for Grid<T> {
+    type Output = T;
+
+    fn index(&self, p: Point) -> &T {
+        match self.get(p) {
+            Some(cell) => cell,
+            None => panic!(
+                "{} is outside of the {}x{} grid",
+                p, self.width, self.height
+            ),
+        }
+    }
+}
+
+impl<T> IndexMut<Point> for Grid<T> {
+    fn index_mut(&mut self, p: Point) -> &mut T {
+        let (width, height) = (self.width, self.height);
+        match self.get_mut(p) {
+            Some(cell) => cell,
+            None => panic!("{} is outside of the {}x{} grid", p, width, height),
+        }
+    }
+}
+
+impl<T: fmt::Display> fmt::Display for Grid<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for row in self.rows() {
+            for cell in row.iter() {
+                write!(f, "{}", cell)?;
+            }
+            writeln!(f)?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A 4x3 grid holding the offset of each cell:
+    ///
+    ///  0  1  2  3
+    ///  4  5  6  7
+    ///  8  9 10 11
+    fn numbered() -> Grid<usize> {
+        Grid::from_rows(
+            (0..3)
+                .map(|y| (0..4).map(|x| 4 * y + x).collect())
+                .collect(),
+        )
+        .unwrap()
+    }
+
+    fn cells<'a>(line: impl Iterator<Item = (Point, &'a usize)>) -> Vec<usize> {
+        line.map(|(_, &c)| c).collect()
+    }
+
+    #[test]
+    fn new_grids() {
+        let grid = Grid::new(3, 2, 'x').unwrap();
+        assert_eq!((grid.width(), grid.height()), (3, 2));
+        assert_eq!(grid.render(|&c| c), "xxx\nxxx\n");
+
+        let error = Grid::new(usize::MAX, 2, 0u8).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidInput);
+    }
+
+    #[test]
+    fn empty_grids_are_0x0() {
+        for (width, height) in [(0, 0), (0, 5), (5, 0)] {
+            let grid = Grid::new(width, height, 0).unwrap();
+            assert_eq!((grid.width(), grid.height()), (0, 0));
+            assert!(grid.is_empty());
+            assert_eq!(grid.rows().count(), 0);
+            assert_eq!(grid.points().count(), 0);
+            assert!(grid.bounds().is_empty());
+        }
+
+        let grid = Grid::<u8>::from_rows(vec![vec![], vec![]]).unwrap();
+        assert_eq!((grid.width(), grid.height()), (0, 0));
+        assert_eq!(Grid::from_rows(vec![vec![1], vec![]]), None);
+    }
+
+    #[test]
+    fn rows_and_columns() {
+        let grid = numbered();
+
+        assert_eq!(grid.row(1), &[4, 5, 6, 7]);
+        assert_eq!(grid.column(2).copied().collect::<Vec<_>>(), vec![2, 6, 10]);
+        assert_eq!(grid.rows().count(), 3);
+    }
+
+    #[test]
+    #[should_panic(expected = "row 3 is outside of the 4x3 grid")]
+    fn rows_out_of_range() {
+        numbered().row(3);
+    }
+
+    #[test]
+    #[should_panic(expected = "column 4 is outside of the 4x3 grid")]
+    fn columns_out_of_range() {
+        let _ = numbered().column(4);
+    }
+
+    #[test]
+    #[should_panic(expected = "row 0 is outside of the 0x0 grid")]
+    fn rows_of_an_empty_grid() {
+        Grid::new(0, 0, 0).unwrap().row(0);
+    }
+
+    #[test]
+    fn rays() {
+        let grid = numbered();
+
+        assert_eq!(cells(grid.ray(Point::new(1, 1), Vector::RIGHT)), vec![6, 7]);
+        assert_eq!(cells(grid.ray(Point::new(1, 1), Vector::UP)), vec![1]);
+        assert_eq!(
+            cells(grid.ray(Point::new(1, 1), Vector::new(1, 1))),
+            vec![10]
+        );
+        assert_eq!(
+            cells(grid.ray(Point::new(3, 1), Vector::RIGHT)),
+            Vec::<usize>::new()
+        );
+        // from outside of the grid, a ray only sees the cells it reaches
+        assert_eq!(
+            cells(grid.ray(Point::new(-1, 2), Vector::RIGHT)),
+            vec![8, 9, 10, 11]
+        );
+    }
+
+    #[test]
+    fn diagonals() {
+        let grid = numbered();
+
+        assert_eq!(cells(grid.diagonal(Point::new(2, 1))), vec![1, 6, 11]);
+        assert_eq!(cells(grid.diagonal(Point::new(0, 2))), vec![8]);
+        assert_eq!(cells(grid.anti_diagonal(Point::new(2, 1))), vec![3, 6, 9]);
+        assert_eq!(cells(grid.anti_diagonal(Point::new(0, 0))), vec![0]);
+        assert_eq!(cells(grid.diagonal(Point::new(7, 7))), Vec::<usize>::new());
+    }
+
+    #[test]
+    fn neighbours() {
+        let grid = numbered();
+
+        assert_eq!(cells(grid.neighbours(Point::new(1, 1))), vec![4, 6, 1, 9]);
+        assert_eq!(cells(grid.neighbours(Point::new(0, 0))), vec![1, 4]);
+        assert_eq!(grid.all_neighbours(Point::new(1, 1)).count(), 8);
+        assert_eq!(grid.all_neighbours(Point::new(3, 2)).count(), 3);
+    }
+
+    #[test]
+    fn searches() {
+        let grid = numbered();
+
+        assert_eq!(grid.find(|&c| c > 4), Some(Point::new(1, 1)));
+        assert_eq!(grid.find(|&c| c > 11), None);
+        assert_eq!(grid.position(&7), Some(Point::new(3, 1)));
+        assert_eq!(
+            grid.positions(|&c| c % 5 == 0).collect::<Vec<_>>(),
+            vec![Point::new(0, 0), Point::new(1, 1), Point::new(2, 2)]
+        );
+    }
+}
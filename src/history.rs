@@ -0,0 +1,458 @@
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fs::{self, OpenOptions};
+use std::io;
+use std::io::prelude::*;
+use std::path::Path;
+use std::process::Command;
+use std::str::FromStr;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::runner::DayRuns;
+
+/// Default location of the history, kept in the working directory as it belongs to the checkout.
+pub const HISTORY_FILE: &str = ".aoc-history.jsonl";
+
+/// Number of most recent times shown in the trend of a part.
+const TREND_LENGTH: usize = 12;
+
+/// A single recorded run, stored as one JSON object per line.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Record {
+    /// Seconds since the Unix epoch.
+    pub timestamp: u64,
+    /// The commit that was checked out, if the run happened inside a git repository.
+    pub commit: Option<String>,
+    pub day: u8,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub input_set: Option<String>,
+    pub part: u8,
+    pub variant: String,
+    pub time_us: u64,
+}
+
+/// What the latest time of a part is compared with to tell whether it regressed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Baseline {
+    Previous,
+    Best,
+}
+
+impl FromStr for Baseline {
+    type Err = io::Error;
+
+    fn from_str(s: &str) -> io::Result<Self> {
+        match s {
+            "previous" => Ok(Baseline::Previous),
+            "best" => Ok(Baseline::Best),
+            _ => Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("Invalid baseline \"{}\" (expected previous or best)", s),
+            )),
+        }
+    }
+}
+
+impl fmt::Display for Baseline {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Baseline::Previous => write!(f, "previous"),
+            Baseline::Best => write!(f, "best"),
+        }
+    }
+}
+
+/// The recorded times of one part (of one variant, on one input), oldest first.
+#[derive(Debug, Clone, Serialize)]
+pub struct Trend {
+    pub day: u8,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub input_set: Option<String>,
+    pub part: u8,
+    pub variant: String,
+    pub runs: usize,
+    pub best_us: u64,
+    pub latest_us: u64,
+    pub latest_commit: Option<String>,
+    pub latest_timestamp: u64,
+    /// The time the latest one is compared with, if there is an earlier run.
+    pub baseline_us: Option<u64>,
+    /// Relative change of the latest time compared with the baseline, in percent.
+    pub change: Option<f64>,
+    pub regression: bool,
+    #[serde(skip)]
+    recent_us: Vec<u64>,
+}
+
+/// Append the computed answers among the results to the history. Cached answers and failed runs
+/// say nothing about the speed of the solvers, so they are left out.
+pub fn record(path: &Path, results: &[DayRuns]) -> io::Result<usize> {
+    let timestamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+    let commit = current_commit();
+
+    let mut lines = String::new();
+    let mut recorded: usize = 0;
+    for day_runs in results.iter() {
+        for run in day_runs.runs.iter().flatten() {
+            if run.cached || run.answer.is_err() {
+                continue;
+            }
+
+            let record = Record {
+                timestamp,
+                commit: commit.clone(),
+                day: run.day,
+                input_set: day_runs.input_set.clone(),
+                part: run.part,
+                variant: run.variant.to_string(),
+                time_us: run.time.as_micros() as u64,
+            };
+            lines.push_str(&serde_json::to_string(&record)?);
+            lines.push('\n');
+            recorded += 1;
+        }
+    }
+
+    if recorded > 0 {
+        let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+        file.write_all(lines.as_bytes())?;
+    }
+
+    Ok(recorded)
+}
+
+/// Read the whole history. A missing file is an empty history.
+pub fn load(path: &Path) -> io::Result<Vec<Record>> {
+    let content = match fs::read_to_string(path) {
+        Ok(content) => content,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(e),
+    };
+
+    content
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(i, line)| {
+            serde_json::from_str(line).map_err(|e| {
+                io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!(
+                        "Invalid record on line {} of {}: {}",
+                        i + 1,
+                        path.display(),
+                        e
+                    ),
+                )
+            })
+        })
+        .collect()
+}
+
+/// Group the records by part, variant and input, comparing the latest time of each with the
+/// baseline. A slowdown of more than `threshold` percent is a regression.
+pub fn trends(records: &[Record], baseline: Baseline, threshold: f64) -> Vec<Trend> {
+    type Key = (u8, u8, Option<String>, String);
+    let mut groups: BTreeMap<Key, Vec<&Record>> = BTreeMap::new();
+    for record in records.iter() {
+        let key = (
+            record.day,
+            record.part,
+            record.input_set.clone(),
+            record.variant.clone(),
+        );
+        groups.entry(key).or_default().push(record);
+    }
+
+    groups
+        .into_iter()
+        .map(|((day, part, input_set, variant), mut records)| {
+            records.sort_by_key(|r| r.timestamp);
+
+            let (latest, earlier) = records.split_last().unwrap();
+            let baseline_us = match baseline {
+                Baseline::Previous => earlier.last().map(|r| r.time_us),
+                Baseline::Best => earlier.iter().map(|r| r.time_us).min(),
+            };
+            let change = baseline_us
+                .filter(|&b| b > 0)
+                .map(|b| (latest.time_us as f64 - b as f64) / b as f64 * 100.0);
+
+            Trend {
+                day,
+                input_set,
+                part,
+                variant,
+                runs: records.len(),
+                best_us: records.iter().map(|r| r.time_us).min().unwrap(),
+                latest_us: latest.time_us,
+                latest_commit: latest.commit.clone(),
+                latest_timestamp: latest.timestamp,
+                baseline_us,
+                change,
+                regression: change.is_some_and(|c| c > threshold),
+                recent_us: records
+                    .iter()
+                    .rev()
+                    .take(TREND_LENGTH)
+                    .rev()
+                    .map(|r| r.time_us)
+                    .collect(),
+            }
+        })
+        .collect()
+}
+
+pub fn render(trends: &[Trend], baseline: Baseline) -> String {
+    let mut s = String::new();
+    if trends.is_empty() {
+        s.push_str("No runs recorded yet (run with --record to record them)\n");
+        return s;
+    }
+
+    s.push_str(&format!(
+        "{:>3}  {:>4}  {:<10}  {:<8}  {:>4}  {:>10}  {:>10}  {:>8}  {:<12}  {:<9}  {}\n",
+        "Day",
+        "Part",
+        "Variant",
+        "Input",
+        "Runs",
+        "Best",
+        "Latest",
+        "Change",
+        "Trend",
+        "Commit",
+        "Recorded"
+    ));
+
+    for trend in trends.iter() {
+        let change = match trend.change {
+            Some(c) => format!("{:+.1}%", c),
+            None => "-".to_string(),
+        };
+
+        s.push_str(&format!(
+            "{:>3}  {:>4}  {:<10}  {:<8}  {:>4}  {:>7} µs  {:>7} µs  {:>8}  {:<12}  {:<9}  {}{}\n",
+            trend.day,
+            trend.part,
+            trend.variant,
+            trend.input_set.as_deref().unwrap_or("default"),
+            trend.runs,
+            trend.best_us,
+            trend.latest_us,
+            change,
+            sparkline(&trend.recent_us),
+            trend.latest_commit.as_deref().unwrap_or("-"),
+            format_timestamp(trend.latest_timestamp),
+            if trend.regression { "  REGRESSION" } else { "" }
+        ));
+    }
+
+    let regressions = trends.iter().filter(|t| t.regression).count();
+    s.push_str(&format!(
+        "\n{} regression(s) compared with the {} run\n",
+        regressions, baseline
+    ));
+
+    s
+}
+
+/// The abbreviated hash of the checked out commit, marked when there are uncommitted changes.
+fn current_commit() -> Option<String> {
+    let output = Command::new("git")
+        .args(["rev-parse", "--short", "HEAD"])
+        .output()
+        .ok()
+        .filter(|o| o.status.success())?;
+    let mut commit = String::from_utf8_lossy(&output.stdout).trim().to_string();
+
+    let dirty = Command::new("git")
+        .args(["status", "--porcelain", "--untracked-files=no"])
+        .output()
+        .is_ok_and(|o| !o.stdout.is_empty());
+    if dirty {
+        commit.push_str("-dirty");
+    }
+
+    Some(commit)
+}
+
+/// Draw the times as a row of bars, from the fastest (lowest) to the slowest (highest).
+fn sparkline(times: &[u64]) -> String {
+    const BARS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+
+    let min = times.iter().copied().min().unwrap_or(0);
+    let max = times.iter().copied().max().unwrap_or(0);
+
+    times
+        .iter()
+        .map(|&t| match max - min {
+            0 => BARS[0],
+            range => BARS[((t - min) * 7 / range) as usize],
+        })
+        .collect()
+}
+
+/// Format seconds since the Unix epoch as a UTC date, e.g. `2022-12-01`.
+fn format_timestamp(timestamp: u64) -> String {
+    // days to civil date, after Howard Hinnant's algorithm
+    let days = (timestamp / 86400) as i64 + 719468;
+    let era = days.div_euclid(146097);
+    let doe = days.rem_euclid(146097);
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + if month <= 2 { 1 } else { 0 };
+
+    format!("{:04}-{:02}-{:02}", year, month, day)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::env;
+
+    fn record(timestamp: u64, part: u8, input_set: Option<&str>, time_us: u64) -> Record {
+        Record {
+            timestamp,
+            commit: Some(format!("c{}", timestamp)),
+            day: 1,
+            input_set: input_set.map(str::to_string),
+            part,
+            variant: "default".to_string(),
+            time_us,
+        }
+    }
+
+    #[test]
+    fn compares_the_latest_time_with_the_baseline() {
+        // recorded out of order, as concurrent runs may append them
+        let records = [
+            record(3, 1, None, 130),
+            record(1, 1, None, 100),
+            record(2, 1, None, 200),
+        ];
+
+        let trends = trends(&records, Baseline::Previous, 10.0);
+        assert_eq!(trends.len(), 1);
+        let trend = &trends[0];
+        assert_eq!(trend.runs, 3);
+        assert_eq!(trend.best_us, 100);
+        assert_eq!(trend.latest_us, 130);
+        assert_eq!(trend.latest_commit.as_deref(), Some("c3"));
+        assert_eq!(trend.baseline_us, Some(200));
+        assert_eq!(trend.change, Some(-35.0));
+        assert!(!trend.regression);
+        assert_eq!(trend.recent_us, vec![100, 200, 130]);
+
+        let trend = &super::trends(&records, Baseline::Best, 10.0)[0];
+        assert_eq!(trend.baseline_us, Some(100));
+        assert_eq!(trend.change, Some(30.0));
+        assert!(trend.regression);
+    }
+
+    #[test]
+    fn only_a_slowdown_beyond_the_threshold_is_a_regression() {
+        let records = [record(1, 1, None, 100), record(2, 1, None, 110)];
+
+        assert!(!trends(&records, Baseline::Previous, 10.0)[0].regression);
+        assert!(trends(&records, Baseline::Previous, 9.9)[0].regression);
+    }
+
+    #[test]
+    fn a_single_run_has_nothing_to_compare_with() {
+        let trend = &trends(&[record(1, 1, None, 100)], Baseline::Previous, 0.0)[0];
+
+        assert_eq!(trend.baseline_us, None);
+        assert_eq!(trend.change, None);
+        assert!(!trend.regression);
+
+        // nor has a run after one too quick to measure
+        let records = [record(1, 1, None, 0), record(2, 1, None, 100)];
+        let trend = &trends(&records, Baseline::Previous, 0.0)[0];
+        assert_eq!(trend.baseline_us, Some(0));
+        assert_eq!(trend.change, None);
+        assert!(!trend.regression);
+    }
+
+    #[test]
+    fn parts_and_inputs_are_compared_separately() {
+        let mut records = vec![
+            record(1, 1, None, 100),
+            record(2, 2, None, 1000),
+            record(3, 1, Some("alice"), 500),
+            record(4, 1, None, 105),
+        ];
+        records.push(Record {
+            variant: "naive".to_string(),
+            ..record(5, 1, None, 9000)
+        });
+
+        let trends = trends(&records, Baseline::Previous, 10.0);
+        let keys: Vec<(u8, Option<&str>, &str, usize)> = trends
+            .iter()
+            .map(|t| (t.part, t.input_set.as_deref(), t.variant.as_str(), t.runs))
+            .collect();
+        assert_eq!(
+            keys,
+            vec![
+                (1, None, "default", 2),
+                (1, None, "naive", 1),
+                (1, Some("alice"), "default", 1),
+                (2, None, "default", 1),
+            ]
+        );
+        assert!(trends.iter().all(|t| !t.regression));
+    }
+
+    #[test]
+    fn the_trend_shows_the_most_recent_times() {
+        let records: Vec<Record> = (0..20).map(|i| record(i, 1, None, i)).collect();
+        let trend = &trends(&records, Baseline::Previous, 10.0)[0];
+
+        assert_eq!(trend.recent_us, (8..20).collect::<Vec<u64>>());
+        assert_eq!(sparkline(&trend.recent_us[..2]), "▁█");
+        assert_eq!(sparkline(&[5, 5, 5]), "▁▁▁");
+    }
+
+    #[test]
+    fn reads_back_what_it_wrote() {
+        let path = env::temp_dir().join(format!("aoc2022-history-{}.jsonl", std::process::id()));
+        let records = [record(1, 1, None, 100), record(2, 1, Some("alice"), 200)];
+        let lines: Vec<String> = records
+            .iter()
+            .map(|r| serde_json::to_string(r).unwrap())
+            .collect();
+        fs::write(&path, format!("{}\n\n{}\n", lines[0], lines[1])).unwrap();
+
+        let loaded = load(&path).unwrap();
+        assert_eq!(loaded.len(), 2);
+        assert_eq!(loaded[1].input_set.as_deref(), Some("alice"));
+        assert_eq!(loaded[1].time_us, 200);
+
+        fs::write(&path, format!("{}\nnot json\n", lines[0])).unwrap();
+        let e = load(&path).unwrap_err();
+        assert_eq!(e.kind(), io::ErrorKind::InvalidData);
+        assert!(e.to_string().contains("line 2"), "{}", e);
+
+        fs::remove_file(&path).unwrap();
+        assert!(load(&path).unwrap().is_empty());
+    }
+
+    #[test]
+    fn baselines_and_dates() {
+        assert_eq!("best".parse::<Baseline>().unwrap(), Baseline::Best);
+        assert_eq!("previous".parse::<Baseline>().unwrap(), Baseline::Previous);
+        assert!("latest".parse::<Baseline>().is_err());
+
+        assert_eq!(format_timestamp(0), "1970-01-01");
+        assert_eq!(format_timestamp(1669852800), "2022-12-01");
+        assert_eq!(format_timestamp(951782400), "2000-02-29");
+    }
+}
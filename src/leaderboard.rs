@@ -0,0 +1,447 @@
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use crate::status::CALENDAR_DAYS;
+
+/// Puzzles unlock at midnight EST, i.e. at 05:00 UTC.
+const UNLOCK_HOUR_UTC: i64 = 5;
+
+/// The JSON export of a private leaderboard, as downloaded from the Advent of Code website.
+#[derive(Debug, Deserialize)]
+struct Export {
+    event: String,
+    members: BTreeMap<String, Member>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Member {
+    id: u64,
+    name: Option<String>,
+    /// Keyed by day, then by part (both as strings).
+    #[serde(default)]
+    completion_day_level: BTreeMap<String, BTreeMap<String, Star>>,
+}
+
+/// When a member collected each of their stars, keyed by (day, part).
+type Stars = BTreeMap<(u8, u8), i64>;
+
+#[derive(Debug, Deserialize)]
+struct Star {
+    get_star_ts: i64,
+}
+
+/// The analysis of a leaderboard export.
+#[derive(Debug, Serialize)]
+pub struct Report {
+    pub event: String,
+    /// Members ordered by their final rank.
+    pub members: Vec<MemberReport>,
+    /// Only the days on which at least one star was collected.
+    pub days: Vec<DayReport>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct MemberReport {
+    pub name: String,
+    pub stars: usize,
+    /// The local score, replayed from the stars so that it matches the ranks.
+    pub local_score: u64,
+    /// The rank after each day of the calendar that has been played, starting with day 1.
+    pub ranks: Vec<usize>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct DayReport {
+    pub day: u8,
+    pub completions: Vec<Completion>,
+}
+
+/// How long after the puzzle unlocked a member collected the stars of a day, in seconds.
+#[derive(Debug, Serialize)]
+pub struct Completion {
+    pub name: String,
+    pub part_1: Option<i64>,
+    pub part_2: Option<i64>,
+    /// Time between the two stars.
+    pub delta: Option<i64>,
+}
+
+pub fn load(path: &Path) -> io::Result<Report> {
+    let export: Export = serde_json::from_str(&fs::read_to_string(path)?).map_err(|e| {
+        io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("Invalid leaderboard export {}: {}", path.display(), e),
+        )
+    })?;
+    let year: i64 = export.event.parse().map_err(|_| {
+        io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("Invalid event \"{}\" in {}", export.event, path.display()),
+        )
+    })?;
+
+    // the stars of every member, keyed by (day, part)
+    let members: Vec<(String, Stars)> = export
+        .members
+        .values()
+        .map(|m| {
+            let name = match &m.name {
+                Some(name) => name.clone(),
+                None => format!("(anonymous user #{})", m.id),
+            };
+            let mut stars = BTreeMap::new();
+            for (day, parts) in m.completion_day_level.iter() {
+                for (part, star) in parts.iter() {
+                    if let (Ok(day), Ok(part)) = (day.parse::<u8>(), part.parse::<u8>()) {
+                        stars.insert((day, part), star.get_star_ts);
+                    }
+                }
+            }
+            (name, stars)
+        })
+        .collect();
+
+    let last_day = members
+        .iter()
+        .flat_map(|(_, stars)| stars.keys().map(|&(day, _)| day))
+        .max()
+        .unwrap_or(0)
+        .min(CALENDAR_DAYS);
+
+    // replay the local score star by star: the first member to get a star gets as many points as
+    // there are members, the second one point less, and so on
+    let mut scores: Vec<u64> = vec![0; members.len()];
+    let mut ranks: Vec<Vec<usize>> = vec![Vec::new(); members.len()];
+    let mut days: Vec<DayReport> = Vec::new();
+
+    for day in 1..=last_day {
+        for part in 1..=2 {
+            let mut finishers: Vec<(i64, usize)> = members
+                .iter()
+                .enumerate()
+                .filter_map(|(i, (_, stars))| stars.get(&(day, part)).map(|&ts| (ts, i)))
+                .collect();
+            finishers.sort();
+
+            for (position, (_, i)) in finishers.into_iter().enumerate() {
+                scores[i] += (members.len() - position) as u64;
+            }
+        }
+
+        for (i, rank) in rank_by_score(&scores).into_iter().enumerate() {
+            ranks[i].push(rank);
+        }
+
+        let unlock = unlock_time(year, day);
+        let mut completions: Vec<Completion> = members
+            .iter()
+            .filter(|(_, stars)| stars.contains_key(&(day, 1)))
+            .map(|(name, stars)| {
+                let part_1 = stars.get(&(day, 1)).map(|ts| ts - unlock);
+                let part_2 = stars.get(&(day, 2)).map(|ts| ts - unlock);
+                Completion {
+                    name: name.clone(),
+                    part_1,
+                    part_2,
+                    delta: part_1.zip(part_2).map(|(p1, p2)| p2 - p1),
+                }
+            })
+            .collect();
+        completions.sort_by_key(|c| (c.part_2.unwrap_or(i64::MAX), c.part_1));
+
+        if !completions.is_empty() {
+            days.push(DayReport { day, completions });
+        }
+    }
+
+    let mut report_members: Vec<MemberReport> = members
+        .iter()
+        .zip(scores)
+        .zip(ranks)
+        .map(|(((name, stars), local_score), ranks)| MemberReport {
+            name: name.clone(),
+            stars: stars.len(),
+            local_score,
+            ranks,
+        })
+        .collect();
+    report_members.sort_by(|a, b| {
+        b.local_score
+            .cmp(&a.local_score)
+            .then(b.stars.cmp(&a.stars))
+            .then(a.name.cmp(&b.name))
+    });
+
+    Ok(Report {
+        event: export.event,
+        members: report_members,
+        days,
+    })
+}
+
+pub fn render(report: &Report) -> String {
+    let mut s = String::new();
+    let name_width = report
+        .members
+        .iter()
+        .map(|m| m.name.chars().count())
+        .max()
+        .unwrap_or(0)
+        .max("Member".len());
+
+    s.push_str(&format!("# ---- LEADERBOARD {} ---- #\n", report.event));
+    s.push_str(&format!(
+        "{:>4}  {:<nw$}  {:>5}  {:>5}\n",
+        "Rank",
+        "Member",
+        "Stars",
+        "Score",
+        nw = name_width
+    ));
+    for (i, member) in report.members.iter().enumerate() {
+        s.push_str(&format!(
+            "{:>4}  {:<nw$}  {:>5}  {:>5}\n",
+            i + 1,
+            member.name,
+            member.stars,
+            member.local_score,
+            nw = name_width
+        ));
+    }
+    s.push('\n');
+
+    for day in report.days.iter() {
+        s.push_str(&format!("# ---- DAY {:0>2} ---- #\n", day.day));
+        s.push_str(&format!(
+            "{:<nw$}  {:>10}  {:>10}  {:>10}\n",
+            "Member",
+            "Part 1",
+            "Part 2",
+            "Delta",
+            nw = name_width
+        ));
+        for completion in day.completions.iter() {
+            s.push_str(&format!(
+                "{:<nw$}  {:>10}  {:>10}  {:>10}\n",
+                completion.name,
+                format_duration(completion.part_1),
+                format_duration(completion.part_2),
+                format_duration(completion.delta),
+                nw = name_width
+            ));
+        }
+        s.push('\n');
+    }
+
+    if let Some(first) = report.members.first() {
+        let days = first.ranks.len();
+
+        s.push_str("# ---- RANKING ---- #\n");
+        s.push_str(&format!("{:<nw$}", "Member", nw = name_width));
+        for day in 1..=days {
+            s.push_str(&format!("  {:>2}", day));
+        }
+        s.push_str("  Change\n");
+
+        for member in report.members.iter() {
+            s.push_str(&format!("{:<nw$}", member.name, nw = name_width));
+            for rank in member.ranks.iter() {
+                s.push_str(&format!("  {:>2}", rank));
+            }
+
+            // positive when the member climbed since the first day
+            let change = match (member.ranks.first(), member.ranks.last()) {
+                (Some(&first), Some(&last)) => first as i64 - last as i64,
+                _ => 0,
+            };
+            s.push_str(&format!("  {:>+6}\n", change));
+        }
+        s.push('\n');
+    }
+
+    s
+}
+
+/// The rank of every score, where equal scores share a rank.
+fn rank_by_score(scores: &[u64]) -> Vec<usize> {
+    scores
+        .iter()
+        .map(|score| scores.iter().filter(|&other| other > score).count() + 1)
+        .collect()
+}
+
+/// The Unix timestamp at which the puzzle of the day unlocked.
+fn unlock_time(year: i64, day: u8) -> i64 {
+    days_from_civil(year, 12, day as i64) * 86400 + UNLOCK_HOUR_UTC * 3600
+}
+
+/// Days since the Unix epoch of a date, after Howard Hinnant's algorithm.
+fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let yoe = year.rem_euclid(400);
+    let mp = (month + 9) % 12;
+    let doy = (153 * mp + 2) / 5 + day - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+
+    era * 146097 + doe - 719468
+}
+
+/// Format a number of seconds as `h:mm:ss`, with hours going beyond 24 if need be.
+fn format_duration(seconds: Option<i64>) -> String {
+    match seconds {
+        Some(s) if s >= 0 => format!("{}:{:02}:{:02}", s / 3600, s / 60 % 60, s % 60),
+        _ => "-".to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::env;
+    use std::path::PathBuf;
+
+    /// 2022-12-01 05:00 UTC, when the first puzzle unlocked.
+    const DAY_1: i64 = 1669870800;
+    const DAY_2: i64 = DAY_1 + 86400;
+
+    /// A completion as (name, part 1, part 2, delta).
+    type Times<'a> = (&'a str, Option<i64>, Option<i64>, Option<i64>);
+
+    fn export(name: &str, content: &str) -> PathBuf {
+        let path = env::temp_dir().join(format!(
+            "aoc2022-leaderboard-{}-{}.json",
+            name,
+            std::process::id()
+        ));
+        fs::write(&path, content).unwrap();
+
+        path
+    }
+
+    fn example() -> String {
+        format!(
+            r#"{{
+  "event": "2022",
+  "owner_id": 1,
+  "members": {{
+    "1": {{ "id": 1, "name": "alice", "stars": 2, "local_score": 0,
+      "completion_day_level": {{
+        "1": {{ "1": {{ "get_star_ts": {a11} }}, "2": {{ "get_star_ts": {a12} }} }}
+      }} }},
+    "2": {{ "id": 2, "name": "bob", "stars": 3, "local_score": 0,
+      "completion_day_level": {{
+        "1": {{ "1": {{ "get_star_ts": {b11} }}, "2": {{ "get_star_ts": {b12} }} }},
+        "2": {{ "1": {{ "get_star_ts": {b21} }} }},
+        "x": {{ "1": {{ "get_star_ts": 0 }} }}
+      }} }},
+    "3": {{ "id": 3, "name": null, "stars": 2, "local_score": 0,
+      "completion_day_level": {{
+        "2": {{ "1": {{ "get_star_ts": {c21} }}, "2": {{ "get_star_ts": {c22} }} }}
+      }} }}
+  }}
+}}"#,
+            a11 = DAY_1 + 100,
+            a12 = DAY_1 + 200,
+            b11 = DAY_1 + 50,
+            b12 = DAY_1 + 300,
+            b21 = DAY_2 + 10,
+            c21 = DAY_2 + 20,
+            c22 = DAY_2 + 30,
+        )
+    }
+
+    #[test]
+    fn ranks_the_members_by_their_replayed_score() {
+        let report = load(&export("ranking", &example())).unwrap();
+        let members: Vec<(&str, usize, u64, &[usize])> = report
+            .members
+            .iter()
+            .map(|m| (m.name.as_str(), m.stars, m.local_score, m.ranks.as_slice()))
+            .collect();
+
+        // alice and bob tie after day 1, and the anonymous member catches up with alice on day 2,
+        // coming first by name as they have as many stars
+        assert_eq!(
+            members,
+            vec![
+                ("bob", 3, 8, &[1, 1][..]),
+                ("(anonymous user #3)", 2, 5, &[3, 2][..]),
+                ("alice", 2, 5, &[1, 2][..]),
+            ]
+        );
+    }
+
+    #[test]
+    fn times_the_stars_from_when_the_puzzle_unlocked() {
+        let report = load(&export("days", &example())).unwrap();
+        let days: Vec<(u8, Vec<Times>)> = report
+            .days
+            .iter()
+            .map(|d| {
+                let completions = d
+                    .completions
+                    .iter()
+                    .map(|c| (c.name.as_str(), c.part_1, c.part_2, c.delta))
+                    .collect();
+                (d.day, completions)
+            })
+            .collect();
+
+        // ordered by when they got the second star, those with only the first one last
+        assert_eq!(
+            days,
+            vec![
+                (
+                    1,
+                    vec![
+                        ("alice", Some(100), Some(200), Some(100)),
+                        ("bob", Some(50), Some(300), Some(250)),
+                    ]
+                ),
+                (
+                    2,
+                    vec![
+                        ("(anonymous user #3)", Some(20), Some(30), Some(10)),
+                        ("bob", Some(10), None, None),
+                    ]
+                ),
+            ]
+        );
+        assert_eq!(unlock_time(2022, 1), DAY_1);
+    }
+
+    #[test]
+    fn rejects_what_is_not_an_export() {
+        for (name, content) in [
+            ("not-json", "members"),
+            ("no-members", r#"{ "event": "2022" }"#),
+            ("bad-event", r#"{ "event": "last year", "members": {} }"#),
+        ] {
+            let e = load(&export(name, content)).unwrap_err();
+            assert_eq!(e.kind(), io::ErrorKind::InvalidData, "{}", name);
+        }
+
+        let report = load(&export("empty", r#"{ "event": "2022", "members": {} }"#)).unwrap();
+        assert!(report.members.is_empty());
+        assert!(report.days.is_empty());
+    }
+
+    #[test]
+    fn equal_scores_share_a_rank() {
+        assert_eq!(rank_by_score(&[5, 8, 5, 0]), vec![2, 1, 2, 4]);
+        assert_eq!(rank_by_score(&[0, 0]), vec![1, 1]);
+    }
+
+    #[test]
+    fn durations() {
+        assert_eq!(format_duration(Some(0)), "0:00:00");
+        assert_eq!(format_duration(Some(3723)), "1:02:03");
+        assert_eq!(format_duration(Some(90000)), "25:00:00");
+        assert_eq!(format_duration(Some(-1)), "-");
+        assert_eq!(format_duration(None), "-");
+    }
+}
@@ -1,10 +1,116 @@
 /* https://github.com/basile-henry/aoc2020/blob/main/src/lib.rs */
+use std::collections::BTreeSet;
+use std::fs;
 use std::fs::File;
 use std::io;
-use std::io::BufReader;
+use std::io::{BufRead, BufReader};
+use std::path::{Path, PathBuf};
 
-pub fn input_file(day: u8) -> io::Result<BufReader<File>> {
-    let input_path = format!("inputs/day_{:0>2}.txt", day);
+pub mod answers;
+pub mod cache;
+pub mod config;
+pub mod dashboard;
+pub mod embedded;
+pub mod external;
+pub mod geometry;
+pub mod grid;
+pub mod history;
+pub mod leaderboard;
+pub mod parsing;
+pub mod progress;
+pub mod registry;
+pub mod runner;
+pub mod server;
+pub mod simulation;
+pub mod snapshot;
+pub mod status;
+pub mod vault;
 
-    Ok(BufReader::new(File::open(input_path)?))
+pub mod day01;
+pub mod day02;
+pub mod day03;
+pub mod day04;
+pub mod day05;
+pub mod day06;
+pub mod day07;
+pub mod day08;
+pub mod day09;
+pub mod day10;
+pub mod day11;
+pub mod day12;
+pub mod day13;
+
+pub fn input_path(input_dir: impl AsRef<Path>, day: u8) -> PathBuf {
+    input_dir.as_ref().join(format!("day_{:0>2}.txt", day))
+}
+
+/// One of the inputs of a day: either the `day_XX.txt` file, or one of the named inputs in the
+/// `day_XX/` directory (e.g. `day_01/alice.txt`), which let the solvers be checked against the
+/// inputs of several people.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InputSet {
+    /// The file name without its extension, or `None` for the `day_XX.txt` file.
+    pub name: Option<String>,
+    pub path: PathBuf,
+}
+
+impl InputSet {
+    pub fn display_name(&self) -> &str {
+        self.name.as_deref().unwrap_or("default")
+    }
+}
+
+/// All inputs of a day, starting with the `day_XX.txt` file and followed by the named inputs in
+/// alphabetical order, including the encrypted ones and those embedded in the binary. A day
+/// without any input still gets its (missing) `day_XX.txt` file, so that reading it reports what
+/// is missing.
+pub fn input_sets(input_dir: impl AsRef<Path>, day: u8) -> io::Result<Vec<InputSet>> {
+    let default = InputSet {
+        name: None,
+        path: input_path(&input_dir, day),
+    };
+
+    let set_dir = input_dir.as_ref().join(format!("day_{:0>2}", day));
+    // named inputs on disk, in plain text or encrypted, and those embedded in the binary
+    let mut names: BTreeSet<String> = embedded::names_in(&format!("day_{:0>2}", day))
+        .into_iter()
+        .collect();
+    if set_dir.is_dir() {
+        for entry in fs::read_dir(&set_dir)? {
+            let file_name = entry?.file_name().to_string_lossy().to_string();
+            let plain = file_name
+                .strip_suffix(&format!(".{}", vault::EXTENSION))
+                .unwrap_or(&file_name);
+            if let Some(name) = plain.strip_suffix(".txt") {
+                names.insert(name.to_string());
+            }
+        }
+    }
+    let mut sets: Vec<InputSet> = names
+        .into_iter()
+        .map(|name| InputSet {
+            path: set_dir.join(format!("{}.txt", name)),
+            name: Some(name),
+        })
+        .collect();
+
+    if embedded::exists(input_dir.as_ref(), &default.path) || sets.is_empty() {
+        sets.insert(0, default);
+    }
+
+    Ok(sets)
+}
+
+/// The input of a day, read from disk or, failing that, decrypted from its encrypted counterpart
+/// or taken from the copy embedded in the binary.
+pub fn input_file(input_dir: impl AsRef<Path>, day: u8) -> io::Result<Box<dyn BufRead>> {
+    let path = input_path(&input_dir, day);
+    match File::open(&path) {
+        Ok(file) => Ok(Box::new(BufReader::new(file))),
+        Err(e) if e.kind() == io::ErrorKind::NotFound => {
+            let content = embedded::read_to_string(input_dir.as_ref(), &path)?;
+            Ok(Box::new(io::Cursor::new(content)))
+        }
+        Err(e) => Err(e),
+    }
 }
@@ -1,21 +1,22 @@
 extern crate clap;
 
-use clap::{App, Arg};
+use clap::{App, AppSettings, Arg, ArgMatches};
 use std::io;
+use std::io::IsTerminal;
+use std::path::{Path, PathBuf};
 
-mod day01;
-mod day02;
-mod day03;
-mod day04;
-mod day05;
-mod day06;
-mod day07;
-mod day08;
-mod day09;
-mod day10;
-mod day11;
-mod day12;
-mod day13;
+use aoc2022::answers::Answers;
+use aoc2022::cache::Cache;
+use aoc2022::config::{Config, OutputFormat, Setting, Source};
+use aoc2022::dashboard::Dashboard;
+use aoc2022::history;
+use aoc2022::leaderboard;
+use aoc2022::registry;
+use aoc2022::runner::{self, RunOptions};
+use aoc2022::server;
+use aoc2022::snapshot::{self, Outcome};
+use aoc2022::status::{self, PartStatus};
+use aoc2022::vault;
 
 fn main() -> io::Result<()> {
     let args = App::new("Advent of Code 2022")
@@ -24,8 +25,312 @@ fn main() -> io::Result<()> {
         .about("My solution code to the Advent of Code 2022")
         .arg(Arg::new("day").required(false))
         .arg(Arg::new("part").required(false))
+        .arg(
+            Arg::new("input-dir")
+                .long("input-dir")
+                .takes_value(true)
+                .global(true)
+                .help("Directory holding the day_XX.txt input files"),
+        )
+        .arg(
+            Arg::new("format")
+                .long("format")
+                .takes_value(true)
+                .possible_values(["text", "json"])
+                .global(true)
+                .help("Output format of the results"),
+        )
+        .arg(
+            Arg::new("jobs")
+                .short('j')
+                .long("jobs")
+                .takes_value(true)
+                .global(true)
+                .help("Number of days to run in parallel"),
+        )
+        .arg(
+            Arg::new("variant")
+                .long("variant")
+                .takes_value(true)
+                .global(true)
+                .help("Name of the solution variant to run, e.g. bfs"),
+        )
+        .arg(
+            Arg::new("input-set")
+                .long("input-set")
+                .takes_value(true)
+                .global(true)
+                .help("Name of the input to run on, e.g. alice for day_01/alice.txt"),
+        )
+        .arg(
+            Arg::new("explain")
+                .long("explain")
+                .global(true)
+                .help("Print how each answer was derived"),
+        )
+        .arg(
+            Arg::new("cache")
+                .long("cache")
+                .global(true)
+                .help("Look up answers in the result cache and store new ones"),
+        )
+        .arg(
+            Arg::new("no-cache")
+                .long("no-cache")
+                .global(true)
+                .conflicts_with("cache")
+                .help("Compute every answer, ignoring the result cache"),
+        )
+        .arg(
+            Arg::new("record")
+                .long("record")
+                .global(true)
+                .help("Append the timings of the run to the history"),
+        )
+        .arg(
+            Arg::new("timeout")
+                .long("timeout")
+                .takes_value(true)
+                .global(true)
+                .help("Time limit of each part in seconds, 0 for no limit"),
+        )
+        .arg(
+            Arg::new("param")
+                .long("param")
+                .takes_value(true)
+                .multiple_occurrences(true)
+                .global(true)
+                .help("Solver parameter as key=value, e.g. day07.threshold=50000"),
+        )
+        .subcommand(
+            App::new("config")
+                .about("Inspect the runner configuration")
+                .setting(AppSettings::SubcommandRequiredElseHelp)
+                .subcommand(
+                    App::new("show").about("Print the effective configuration and its origin"),
+                ),
+        )
+        .subcommand(
+            App::new("compare")
+                .about("Run all variants of a day on the same input and compare them")
+                .arg(Arg::new("day").required(true))
+                .arg(Arg::new("part").required(false)),
+        )
+        .subcommand(
+            App::new("check")
+                .about("Run the days and check the answers against the expected ones")
+                .arg(Arg::new("day").required(false))
+                .arg(Arg::new("part").required(false)),
+        )
+        .subcommand(
+            App::new("status")
+                .about("Show which days are implemented, have an input and are verified"),
+        )
+        .subcommand(
+            App::new("history")
+                .about("Show the recorded timings and flag regressions")
+                .arg(Arg::new("day").required(false))
+                .arg(
+                    Arg::new("threshold")
+                        .long("threshold")
+                        .takes_value(true)
+                        .help("Slowdown in percent flagged as a regression"),
+                )
+                .arg(
+                    Arg::new("against")
+                        .long("against")
+                        .takes_value(true)
+                        .possible_values(["previous", "best"])
+                        .help("Compare the latest run with the previous or the best one"),
+                ),
+        )
+        .subcommand(
+            App::new("leaderboard")
+                .about("Analyze the JSON export of a private leaderboard")
+                .arg(Arg::new("file").required(true)),
+        )
+        .subcommand(
+            App::new("snapshot")
+                .about("Compare the answers and explanations with their golden files")
+                .arg(Arg::new("day").required(false))
+                .arg(Arg::new("part").required(false))
+                .arg(
+                    Arg::new("update")
+                        .long("update")
+                        .help("Write the golden files instead of comparing with them"),
+                )
+                .arg(
+                    Arg::new("snapshot-dir")
+                        .long("snapshot-dir")
+                        .takes_value(true)
+                        .default_value(snapshot::SNAPSHOT_DIR)
+                        .help("Directory holding the golden files"),
+                ),
+        )
+        .subcommand(
+            App::new("serve")
+                .about("Serve the solvers as a JSON API over HTTP")
+                .arg(
+                    Arg::new("addr")
+                        .long("addr")
+                        .takes_value(true)
+                        .default_value(server::DEFAULT_ADDR)
+                        .help("Address to listen on"),
+                ),
+        )
+        .subcommand(
+            App::new("dashboard")
+                .about("Browse and run the days in an interactive terminal dashboard"),
+        )
+        .subcommand(
+            App::new("cache")
+                .about("Manage the result cache")
+                .setting(AppSettings::SubcommandRequiredElseHelp)
+                .subcommand(App::new("clear").about("Remove all cached answers")),
+        )
+        .subcommand(
+            App::new("inputs")
+                .about("Keep the inputs encrypted at rest")
+                .setting(AppSettings::SubcommandRequiredElseHelp)
+                .subcommand(
+                    App::new("encrypt")
+                        .about("Encrypt every input, creating the key if there is none")
+                        .arg(
+                            Arg::new("remove-plain")
+                                .long("remove-plain")
+                                .help("Delete the plain text inputs once encrypted"),
+                        ),
+                )
+                .subcommand(
+                    App::new("rotate-key").about("Re-encrypt every encrypted input with a new key"),
+                ),
+        )
         .get_matches();
 
+    match args.subcommand() {
+        Some(("config", config_args)) => match config_args.subcommand() {
+            Some(("show", show_args)) => print!("{}", load_config(show_args)?.show()),
+            _ => unreachable!(),
+        },
+        Some(("cache", cache_args)) => match cache_args.subcommand() {
+            Some(("clear", clear_args)) => {
+                let config = load_config(clear_args)?;
+                let removed = Cache::new(&config.cache_dir.value).clear()?;
+                println!(
+                    "Removed {} cached answer(s) from {}",
+                    removed,
+                    config.cache_dir.value.display()
+                );
+            }
+            _ => unreachable!(),
+        },
+        Some(("inputs", inputs_args)) => match inputs_args.subcommand() {
+            Some(("encrypt", encrypt_args)) => {
+                let config = load_config(encrypt_args)?;
+                let encrypted = vault::encrypt_inputs(
+                    &config.input_dir.value,
+                    &config.key_file.value,
+                    encrypt_args.is_present("remove-plain"),
+                )?;
+                println!(
+                    "Encrypted {} input(s) with the key {}",
+                    encrypted.len(),
+                    config.key_file.value.display()
+                );
+            }
+            Some(("rotate-key", rotate_args)) => {
+                let config = load_config(rotate_args)?;
+                let (count, backup) =
+                    vault::rotate_key(&config.input_dir.value, &config.key_file.value)?;
+                println!(
+                    "Re-encrypted {} input(s) with a new key {}, keeping the previous one as {}",
+                    count,
+                    config.key_file.value.display(),
+                    backup.display()
+                );
+            }
+            _ => unreachable!(),
+        },
+        Some(("check", check_args)) => check(check_args, &load_config(check_args)?)?,
+        Some(("status", status_args)) => status(&load_config(status_args)?)?,
+        Some(("history", history_args)) => history(history_args, &load_config(history_args)?)?,
+        Some(("leaderboard", leaderboard_args)) => {
+            let config = load_config(leaderboard_args)?;
+            let report = leaderboard::load(Path::new(leaderboard_args.value_of("file").unwrap()))?;
+
+            match config.format.value {
+                OutputFormat::Text => print!("{}", leaderboard::render(&report)),
+                OutputFormat::Json => {
+                    println!("{}", serde_json::to_string_pretty(&report).unwrap())
+                }
+            }
+        }
+        Some(("snapshot", snapshot_args)) => snapshot(snapshot_args, &load_config(snapshot_args)?)?,
+        Some(("serve", serve_args)) => {
+            let config = load_config(serve_args)?;
+            let options = RunOptions {
+                progress: false,
+                ..run_options(serve_args, &config, 0)
+            };
+            server::serve(serve_args.value_of("addr").unwrap(), options)?
+        }
+        Some(("dashboard", dashboard_args)) => {
+            let config = load_config(dashboard_args)?;
+            // the dashboard owns the terminal, so it draws no progress bars of its own
+            let options = RunOptions {
+                progress: false,
+                ..run_options(dashboard_args, &config, 0)
+            };
+            Dashboard::new(&config.input_dir.value, options, &config.history_file.value)?.run()?
+        }
+        Some(("compare", compare_args)) => compare(compare_args, &load_config(compare_args)?)?,
+        _ => run(&args, &load_config(&args)?)?,
+    }
+
+    Ok(())
+}
+
+/// Load the configuration files and apply the flags given on the command line on top.
+fn load_config(args: &ArgMatches) -> io::Result<Config> {
+    let mut config = Config::load()?;
+
+    if let Some(s) = args.value_of("input-dir") {
+        config.input_dir = Setting::new(PathBuf::from(s), Source::CommandLine);
+    }
+    if let Some(s) = args.value_of("format") {
+        config.format = Setting::new(s.parse()?, Source::CommandLine);
+    }
+    if let Some(s) = args.value_of("jobs") {
+        let jobs = match s.parse::<usize>() {
+            Ok(j) => j.max(1),
+            Err(_e) => panic!("Error parsing the number of jobs!"),
+        };
+        config.jobs = Setting::new(jobs, Source::CommandLine);
+    }
+    if args.is_present("cache") {
+        config.cache = Setting::new(true, Source::CommandLine);
+    }
+    if args.is_present("record") {
+        config.record = Setting::new(true, Source::CommandLine);
+    }
+    if args.is_present("no-cache") {
+        config.cache = Setting::new(false, Source::CommandLine);
+    }
+    if let Some(s) = args.value_of("timeout") {
+        config.set_timeout(s)?;
+    }
+    if let Some(pairs) = args.values_of("param") {
+        for pair in pairs {
+            config.set_param(pair)?;
+        }
+    }
+    registry::register_external(config.external_solvers())?;
+    vault::set_key_file(&config.key_file.value);
+
+    Ok(config)
+}
+
+fn day_and_part(args: &ArgMatches) -> (u8, u8) {
     let day = match args.value_of("day") {
         Some(s) => match s.parse::<u8>() {
             Ok(d) => d,
@@ -42,36 +347,279 @@ fn main() -> io::Result<()> {
         None => 0,
     };
 
-    // run the day
-    let mut days_to_run: Vec<u8> = match day {
-        0 => (1..9).map(|i| i).collect::<Vec<u8>>(),
+    (day, part)
+}
+
+fn run_options(args: &ArgMatches, config: &Config, part: u8) -> RunOptions {
+    RunOptions {
+        part,
+        variant: args.value_of("variant").map(|v| v.to_string()),
+        input_set: args.value_of("input-set").map(|s| s.to_string()),
+        params: config.solver_params(),
+        explain: args.is_present("explain"),
+        cache: match config.cache.value {
+            true => Some(Cache::new(&config.cache_dir.value)),
+            false => None,
+        },
+        time_limits: config.time_limits(),
+        // a bar on a terminal only, where it does not get in the way of machine-readable output
+        progress: config.format.value == OutputFormat::Text && io::stderr().is_terminal(),
+    }
+}
+
+fn days_to_run(day: u8) -> Vec<u8> {
+    match day {
+        0 => registry::days(),
         _ => vec![day],
+    }
+}
+
+fn run(args: &ArgMatches, config: &Config) -> io::Result<()> {
+    let (day, part) = day_and_part(args);
+
+    let options = run_options(args, config, part);
+    let results = runner::run_days(
+        &days_to_run(day),
+        &config.input_dir.value,
+        &options,
+        config.jobs.value,
+    );
+    runner::print(&results, config.format.value);
+    record(config, &results)?;
+
+    exit_status(&results)
+}
+
+fn record(config: &Config, results: &[runner::DayRuns]) -> io::Result<()> {
+    if config.record.value {
+        history::record(&config.history_file.value, results)?;
+    }
+
+    Ok(())
+}
+
+/// Report failures through the exit status: the error itself if there is only one, otherwise how
+/// many there were (they are listed in the summary).
+fn exit_status(results: &[runner::DayRuns]) -> io::Result<()> {
+    let failures = runner::failures(results);
+
+    match failures.as_slice() {
+        [] => Ok(()),
+        [failure] => Err(io::Error::new(
+            failure.error.kind(),
+            failure.error.to_string(),
+        )),
+        _ => Err(io::Error::other(format!("{} failures", failures.len()))),
+    }
+}
+
+fn check(args: &ArgMatches, config: &Config) -> io::Result<()> {
+    let (day, part) = day_and_part(args);
+    let answers = Answers::load(&config.input_dir.value)?;
+
+    let options = run_options(args, config, part);
+    let results = runner::run_days(
+        &days_to_run(day),
+        &config.input_dir.value,
+        &options,
+        config.jobs.value,
+    );
+    runner::print_checked(&results, &answers, config.format.value);
+    record(config, &results)?;
+
+    let wrong = runner::wrong_answers(&results, &answers);
+    if !wrong.is_empty() {
+        return Err(io::Error::other(format!(
+            "{} answer(s) differ from the expected ones",
+            wrong.len()
+        )));
+    }
+
+    exit_status(&results)
+}
+
+fn status(config: &Config) -> io::Result<()> {
+    let answers = Answers::load(&config.input_dir.value)?;
+    let mut calendar = status::calendar(&config.input_dir.value, &answers);
+
+    // an expected answer only verifies a part once the solver is seen to give it
+    let days: Vec<u8> = calendar
+        .iter()
+        .filter(|d| d.parts.contains(&PartStatus::Unchecked))
+        .map(|d| d.day)
+        .collect();
+    let options = RunOptions {
+        params: config.solver_params(),
+        cache: match config.cache.value {
+            true => Some(Cache::new(&config.cache_dir.value)),
+            false => None,
+        },
+        time_limits: config.time_limits(),
+        ..Default::default()
+    };
+    let results = runner::run_days(&days, &config.input_dir.value, &options, config.jobs.value);
+    status::check(&mut calendar, &results, &answers);
+
+    match config.format.value {
+        OutputFormat::Text => print!("{}", status::render(&calendar)),
+        OutputFormat::Json => println!("{}", serde_json::to_string_pretty(&calendar).unwrap()),
+    }
+
+    let wrong = calendar
+        .iter()
+        .flat_map(|d| d.parts.iter())
+        .filter(|&&p| p == PartStatus::Wrong)
+        .count();
+    match wrong {
+        0 => Ok(()),
+        _ => Err(io::Error::other(format!(
+            "{} part(s) did not give the expected answer",
+            wrong
+        ))),
+    }
+}
+
+fn history(args: &ArgMatches, config: &Config) -> io::Result<()> {
+    // the options of the history command only exist on it, so they are applied here
+    let mut config = config.clone();
+    if let Some(s) = args.value_of("threshold") {
+        let threshold = s.parse::<f64>().map_err(|_| {
+            io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("Invalid threshold \"{}\" (expected a percentage)", s),
+            )
+        })?;
+        config.regression_threshold = Setting::new(threshold, Source::CommandLine);
+    }
+    if let Some(s) = args.value_of("against") {
+        config.regression_baseline = Setting::new(s.parse()?, Source::CommandLine);
+    }
+
+    let records = history::load(&config.history_file.value)?;
+    let records: Vec<history::Record> = match args.value_of("day") {
+        Some(s) => {
+            let day = match s.parse::<u8>() {
+                Ok(d) => d,
+                Err(_e) => panic!("Error parsing the day!"),
+            };
+            records.into_iter().filter(|r| r.day == day).collect()
+        }
+        None => records,
+    };
+
+    let baseline = config.regression_baseline.value;
+    let trends = history::trends(&records, baseline, config.regression_threshold.value);
+    match config.format.value {
+        OutputFormat::Text => print!("{}", history::render(&trends, baseline)),
+        OutputFormat::Json => println!("{}", serde_json::to_string_pretty(&trends).unwrap()),
+    }
+
+    let regressions = trends.iter().filter(|t| t.regression).count();
+    if regressions > 0 {
+        return Err(io::Error::other(format!(
+            "{} part(s) got slower by more than {}%",
+            regressions, config.regression_threshold.value
+        )));
+    }
+
+    Ok(())
+}
+
+fn snapshot(args: &ArgMatches, config: &Config) -> io::Result<()> {
+    let (day, part) = day_and_part(args);
+    let dir = PathBuf::from(args.value_of("snapshot-dir").unwrap());
+    let mode = match args.is_present("update") {
+        true => snapshot::Mode::Update,
+        false => snapshot::Mode::Check,
+    };
+
+    // the golden files hold the explanations as well as the answers
+    let options = RunOptions {
+        explain: true,
+        ..run_options(args, config, part)
     };
+    let results = runner::run_days(
+        &days_to_run(day),
+        &config.input_dir.value,
+        &options,
+        config.jobs.value,
+    );
+
+    let outcomes = snapshot::check_all(&dir, &results, mode)?;
+    let mut failed: usize = 0;
+    for (path, outcome) in outcomes.iter() {
+        match outcome {
+            Outcome::Matched => println!("{}: ok", path.display()),
+            Outcome::Written => println!("{}: written", path.display()),
+            Outcome::Missing => {
+                println!(
+                    "{}: missing (run with --update to create it)",
+                    path.display()
+                );
+                failed += 1;
+            }
+            Outcome::Mismatched(diff) => {
+                println!("{}: MISMATCH", path.display());
+                for line in diff.lines() {
+                    println!("    {}", line);
+                }
+                failed += 1;
+            }
+        }
+    }
+
+    // days that could not run at all have nothing to compare
+    for day_runs in results.iter() {
+        if let Err(e) = &day_runs.runs {
+            println!("Day {}: {}", day_runs.day, e);
+            failed += 1;
+        }
+    }
+
+    println!(
+        "\n{} of {} snapshot(s) differ or are missing",
+        failed,
+        outcomes.len()
+    );
+
+    match failed {
+        0 => Ok(()),
+        _ => Err(io::Error::other(format!("{} snapshot(s) failed", failed))),
+    }
+}
+
+fn compare(args: &ArgMatches, config: &Config) -> io::Result<()> {
+    let (day, part) = day_and_part(args);
+
+    let options = RunOptions {
+        part,
+        input_set: args.value_of("input-set").map(|s| s.to_string()),
+        params: config.solver_params(),
+        time_limits: config.time_limits(),
+        ..Default::default()
+    };
+
+    let results = runner::run_variants(day, &config.input_dir.value, &options);
+    for day_runs in results.iter() {
+        runner::print_comparison(day_runs, config.format.value);
+    }
+
+    for day_runs in results.iter() {
+        let disagreements = match &day_runs.runs {
+            Ok(runs) => runner::disagreements(runs),
+            Err(e) => return Err(io::Error::new(e.kind(), e.to_string())),
+        };
 
-    while !days_to_run.is_empty() {
-        let day_to_run = days_to_run.pop().unwrap();
-
-        // get input file for the day
-        let input = aoc2022::input_file(day_to_run)?;
-
-        println!("# ---- DAY {:0>2} ---- #", day_to_run);
-        match day_to_run {
-            1 => day01::solve(input, part)?,
-            2 => day02::solve(input, part)?,
-            3 => day03::solve(input, part)?,
-            4 => day04::solve(input, part)?,
-            5 => day05::solve(input, part)?,
-            6 => day06::solve(input, part)?,
-            7 => day07::solve(input, part)?,
-            8 => day08::solve(input, part)?,
-            9 => day09::solve(input, part)?,
-            10 => day10::solve(input, part)?,
-            11 => day11::solve(input, part)?,
-            12 => day12::solve(input, part)?,
-            13 => day13::solve(input, part)?,
-            _ => unimplemented!(),
+        if !disagreements.is_empty() {
+            let input = match &day_runs.input_set {
+                Some(name) => format!(" on input {}", name),
+                None => String::new(),
+            };
+            return Err(io::Error::other(format!(
+                "The variants of day {} disagree on part(s) {:?}{}",
+                day, disagreements, input
+            )));
         }
-        println!("");
     }
 
     Ok(())
@@ -0,0 +1,468 @@
+use std::error::Error;
+use std::fmt;
+use std::io;
+use std::io::prelude::*;
+use std::str::FromStr;
+
+/// A parse failure, located by the (1-based) line and column of the input where it happened.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    pub line: usize,
+    pub column: usize,
+    pub message: String,
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "line {}, column {}: {}",
+            self.line, self.column, self.message
+        )
+    }
+}
+
+impl Error for ParseError {}
+
+impl From<ParseError> for io::Error {
+    fn from(e: ParseError) -> Self {
+        io::Error::new(io::ErrorKind::InvalidData, e)
+    }
+}
+
+pub type Result<T> = std::result::Result<T, ParseError>;
+
+/// Read the whole input and parse it with `f`, which has to consume all of it (but for trailing
+/// whitespace).
+pub fn parse_all<T>(
+    mut input: impl BufRead,
+    f: impl FnOnce(&mut Cursor) -> Result<T>,
+) -> io::Result<T> {
+    let mut text = String::new();
+    input.read_to_string(&mut text)?;
+
+    let mut cursor = Cursor::new(&text);
+    let value = f(&mut cursor)?;
+    cursor.skip_whitespace();
+    cursor.end()?;
+
+    Ok(value)
+}
+
+/// A position in the input, from which the parsers below consume text. A cursor can be limited to
+/// a part of the input, such as a single line, while still reporting positions in the whole input.
+#[derive(Debug, Clone, Copy)]
+pub struct Cursor<'a> {
+    src: &'a str,
+    pos: usize,
+    end: usize,
+}
+
+impl<'a> Cursor<'a> {
+    pub fn new(src: &'a str) -> Self {
+        Cursor {
+            src,
+            pos: 0,
+            end: src.len(),
+        }
+    }
+
+    /// The text left to parse.
+    pub fn rest(&self) -> &'a str {
+        &self.src[self.pos..self.end]
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.pos == self.end
+    }
+
+    pub fn peek(&self) -> Option<char> {
+        self.rest().chars().next()
+    }
+
+    /// An error at the current position.
+    pub fn error(&self, message: impl Into<String>) -> ParseError {
+        let before = &self.src[..self.pos];
+        let line_start = before.rfind('\n').map(|i| i + 1).unwrap_or(0);
+
+        ParseError {
+            line: before.matches('\n').count() + 1,
+            column: before[line_start..].chars().count() + 1,
+            message: message.into(),
+        }
+    }
+
+    /// An error telling what was expected, and what was found instead.
+    fn expected(&self, what: &str) -> ParseError {
+        match self.peek() {
+            Some(c) => self.error(format!("expected {}, found {:?}", what, c)),
+            None => self.error(format!("expected {}, found the end of the input", what)),
+        }
+    }
+
+    /// Fail unless all of the text was consumed.
+    pub fn end(&self) -> Result<()> {
+        match self.peek() {
+            Some(c) => Err(self.error(format!("unexpected {:?}", c))),
+            None => Ok(()),
+        }
+    }
+
+    /// Consume a literal if the text starts with it, telling whether it did.
+    pub fn eat(&mut self, literal: &str) -> bool {
+        match self.rest().starts_with(literal) {
+            true => {
+                self.pos += literal.len();
+                true
+            }
+            false => false,
+        }
+    }
+
+    /// Consume a literal, such as the keyword starting a line.
+    pub fn expect(&mut self, literal: &str) -> Result<()> {
+        match self.eat(literal) {
+            true => Ok(()),
+            false => Err(self.expected(&format!("{:?}", literal))),
+        }
+    }
+
+    /// Consume the longest prefix whose characters all satisfy the predicate, which may be empty.
+    pub fn take_while(&mut self, pred: impl Fn(char) -> bool) -> &'a str {
+        let rest = self.rest();
+        let len = rest.find(|c: char| !pred(c)).unwrap_or(rest.len());
+        self.pos += len;
+
+        &rest[..len]
+    }
+
+    /// Skip spaces and tabs, but not line breaks.
+    pub fn skip_spaces(&mut self) {
+        self.take_while(|c| c == ' ' || c == '\t');
+    }
+
+    pub fn skip_whitespace(&mut self) {
+        self.take_while(char::is_whitespace);
+    }
+
+    /// A non-empty run of characters up to the next whitespace.
+    pub fn word(&mut self) -> Result<&'a str> {
+        match self.take_while(|c| !c.is_whitespace()) {
+            "" => Err(self.expected("a word")),
+            word => Ok(word),
+        }
+    }
+
+    /// An integer, with an optional sign.
+    pub fn integer<T: FromStr>(&mut self) -> Result<T> {
+        let start = *self;
+        let sign = match self.eat("-") || self.eat("+") {
+            true => 1,
+            false => 0,
+        };
+
+        let digits = self.take_while(|c| c.is_ascii_digit());
+        if digits.is_empty() {
+            *self = start;
+            return Err(self.expected("an integer"));
+        }
+
+        let text = &start.rest()[..sign + digits.len()];
+        text.parse::<T>().map_err(|_| {
+            let e = start.error(format!("integer {} is out of range", text));
+            *self = start;
+            e
+        })
+    }
+
+    /// One or more items separated by `sep`.
+    pub fn separated<T>(
+        &mut self,
+        sep: &str,
+        mut item: impl FnMut(&mut Self) -> Result<T>,
+    ) -> Result<Vec<T>> {
+        let mut items = vec![item(self)?];
+        while self.eat(sep) {
+            items.push(item(self)?);
+        }
+
+        Ok(items)
+    }
+
+    /// A (possibly empty and possibly nested) list such as `[1,[2,3]]`, where nesting is left to
+    /// `item` calling `list` again. Bounding the depth of that recursion is up to `item` as well.
+    pub fn list<T>(
+        &mut self,
+        open: &str,
+        sep: &str,
+        close: &str,
+        mut item: impl FnMut(&mut Self) -> Result<T>,
+    ) -> Result<Vec<T>> {
+        self.expect(open)?;
+
+        let mut items = Vec::new();
+        if self.eat(close) {
+            return Ok(items);
+        }
+
+        loop {
+            items.push(item(self)?);
+            if self.eat(sep) {
+                continue;
+            }
+            self.expect(close)?;
+
+            return Ok(items);
+        }
+    }
+
+    /// Split off the next line, without its line break (`\n` or `\r\n`), and move past it.
+    pub fn line(&mut self) -> Result<Self> {
+        if self.is_empty() {
+            return Err(self.expected("a line"));
+        }
+
+        let len = self.rest().find('\n').unwrap_or(self.rest().len());
+        let text = &self.rest()[..len];
+        let line = Cursor {
+            src: self.src,
+            pos: self.pos,
+            end: self.pos + text.strip_suffix('\r').unwrap_or(text).len(),
+        };
+        self.pos = (self.pos + len + 1).min(self.end);
+
+        Ok(line)
+    }
+
+    /// Split the rest of the text into lines, consuming all of it. A final line break does not
+    /// start another line.
+    pub fn lines(&mut self) -> Vec<Self> {
+        let mut lines = Vec::new();
+        while !self.is_empty() {
+            lines.extend(self.line());
+        }
+
+        lines
+    }
+
+    /// A line holding a keyword followed by a value, e.g. `Test: divisible by 19`, as a cursor
+    /// over the text after the keyword.
+    pub fn keyword_line(&mut self, keyword: &str) -> Result<Self> {
+        self.skip_spaces();
+        self.expect(keyword)?;
+        let mut line = self.line()?;
+        line.skip_spaces();
+
+        Ok(line)
+    }
+
+    /// Split the rest of the text into blocks separated by blank lines, consuming all of it.
+    pub fn blocks(&mut self) -> Vec<Self> {
+        let mut blocks: Vec<Self> = Vec::new();
+        let mut block: Option<Self> = None;
+
+        for line in self.lines() {
+            match (line.rest().trim().is_empty(), &mut block) {
+                (true, _) => blocks.extend(block.take()),
+                (false, Some(block)) => block.end = line.end,
+                (false, None) => block = Some(line),
+            }
+        }
+        blocks.extend(block);
+
+        blocks
+    }
+
+    /// A rectangular grid of characters, one row per line, with every character turned into a
+    /// cell by `cell` (or rejected when it gives `None`).
+    pub fn grid<T>(&mut self, mut cell: impl FnMut(char) -> Option<T>) -> Result<Vec<Vec<T>>> {
+        let mut rows: Vec<Vec<T>> = Vec::new();
+
+        for mut line in self.lines() {
+            let mut row = Vec::new();
+            while let Some(c) = line.peek() {
+                row.push(cell(c).ok_or_else(|| line.error(format!("invalid cell {:?}", c)))?);
+                line.pos += c.len_utf8();
+            }
+
+            if let Some(first) = rows.first() {
+                if row.len() != first.len() {
+                    return Err(line.error(format!(
+                        "row of width {} in a grid of width {}",
+                        row.len(),
+                        first.len()
+                    )));
+                }
+            }
+            rows.push(row);
+        }
+
+        Ok(rows)
+    }
+
+    /// Parse the whole text with `f`, failing unless all of it is consumed.
+    pub fn all<T>(mut self, f: impl FnOnce(&mut Self) -> Result<T>) -> Result<T> {
+        let value = f(&mut self)?;
+        self.end()?;
+
+        Ok(value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The numbers of a line such as `1,2,3`.
+    fn numbers(line: Cursor) -> Result<Vec<i32>> {
+        line.all(|l| l.separated(",", |n| n.integer()))
+    }
+
+    fn parse_lines(text: &str) -> io::Result<Vec<Vec<i32>>> {
+        parse_all(text.as_bytes(), |cursor| {
+            cursor.lines().into_iter().map(numbers).collect()
+        })
+    }
+
+    #[test]
+    fn errors_carry_their_position() {
+        let mut cursor = Cursor::new("ab\ncdé\nf");
+        cursor.line().unwrap();
+        cursor.expect("cdé").unwrap();
+
+        let e = cursor.error("oops");
+        assert_eq!((e.line, e.column), (2, 4));
+        assert_eq!(e.to_string(), "line 2, column 4: oops");
+    }
+
+    #[test]
+    fn errors_point_at_what_was_found() {
+        let e = parse_lines("1,2\n3,x\n").unwrap_err();
+        assert_eq!(
+            e.to_string(),
+            "line 2, column 3: expected an integer, found 'x'"
+        );
+
+        let e = parse_lines("1,2\n3,\n").unwrap_err();
+        assert_eq!(
+            e.to_string(),
+            "line 2, column 3: expected an integer, found the end of the input"
+        );
+    }
+
+    #[test]
+    fn bad_separators_are_left_unconsumed() {
+        let e = parse_lines("1,2;3\n").unwrap_err();
+        assert_eq!(e.to_string(), "line 1, column 4: unexpected ';'");
+
+        let e = parse_lines("1, 2\n").unwrap_err();
+        assert_eq!(
+            e.to_string(),
+            "line 1, column 3: expected an integer, found ' '"
+        );
+    }
+
+    #[test]
+    fn lines_end_with_or_without_a_line_break() {
+        for text in ["1,2\n3\n", "1,2\n3", "1,2\r\n3\r\n", "1,2\r\n3"] {
+            assert_eq!(
+                parse_lines(text).unwrap(),
+                vec![vec![1, 2], vec![3]],
+                "{:?}",
+                text
+            );
+        }
+    }
+
+    #[test]
+    fn blank_lines_are_lines() {
+        // only the final line break is dropped, a blank line after it is an (empty) line
+        let mut cursor = Cursor::new("1\n\n");
+        let lines: Vec<&str> = cursor.lines().iter().map(|l| l.rest()).collect();
+        assert_eq!(lines, vec!["1", ""]);
+
+        let e = parse_lines("1,2\n3\n\n").unwrap_err();
+        assert_eq!(
+            e.to_string(),
+            "line 3, column 1: expected an integer, found the end of the input"
+        );
+    }
+
+    #[test]
+    fn crlf_errors_keep_their_position() {
+        let e = parse_lines("1\r\n2,x\r\n").unwrap_err();
+        assert_eq!(
+            e.to_string(),
+            "line 2, column 3: expected an integer, found 'x'"
+        );
+    }
+
+    #[test]
+    fn blocks_are_separated_by_blank_lines() {
+        let mut cursor = Cursor::new("1\n2\n\n3\r\n\r\n\n4\n");
+        let blocks: Vec<&str> = cursor.blocks().iter().map(|b| b.rest()).collect();
+
+        assert_eq!(blocks, vec!["1\n2", "3", "4"]);
+        assert!(cursor.is_empty());
+    }
+
+    #[test]
+    fn integers() {
+        assert_eq!(Cursor::new("-12").integer::<i32>(), Ok(-12));
+        assert_eq!(Cursor::new("+7x").integer::<u8>(), Ok(7));
+
+        let mut cursor = Cursor::new("300");
+        let e = cursor.integer::<u8>().unwrap_err();
+        assert_eq!(e.message, "integer 300 is out of range");
+        // a failed integer consumes nothing
+        assert_eq!(cursor.rest(), "300");
+
+        let mut cursor = Cursor::new("-x");
+        assert!(cursor.integer::<i32>().is_err());
+        assert_eq!(cursor.rest(), "-x");
+    }
+
+    #[test]
+    fn lists() {
+        let mut cursor = Cursor::new("[1,[],3]");
+        let items = cursor.list("[", ",", "]", |c| match c.peek() {
+            Some('[') => c
+                .list("[", ",", "]", |c| c.integer::<i32>())
+                .map(|l| l.len() as i32),
+            _ => c.integer(),
+        });
+
+        assert_eq!(items, Ok(vec![1, 0, 3]));
+        assert!(cursor.is_empty());
+
+        let e = Cursor::new("[1;2]")
+            .list("[", ",", "]", |c| c.integer::<i32>())
+            .unwrap_err();
+        assert_eq!(e.message, "expected \"]\", found ';'");
+        assert_eq!(e.column, 3);
+    }
+
+    #[test]
+    fn grids() {
+        let cell = |c: char| c.to_digit(10);
+
+        assert_eq!(
+            Cursor::new("12\r\n34\n").grid(cell),
+            Ok(vec![vec![1, 2], vec![3, 4]])
+        );
+
+        let e = Cursor::new("12\n3\n").grid(cell).unwrap_err();
+        assert_eq!((e.line, e.column), (2, 2));
+        assert_eq!(e.message, "row of width 1 in a grid of width 2");
+
+        let e = Cursor::new("12\n3x\n").grid(cell).unwrap_err();
+        assert_eq!((e.line, e.column), (2, 2));
+    }
+
+    #[test]
+    fn trailing_text_is_an_error() {
+        let e = parse_all("1\n2 x".as_bytes(), |c| c.integer::<i32>()).unwrap_err();
+        assert_eq!(e.kind(), io::ErrorKind::InvalidData);
+        assert_eq!(e.to_string(), "line 2, column 1: unexpected '2'");
+    }
+}
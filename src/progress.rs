@@ -0,0 +1,89 @@
+use std::fmt;
+use std::io;
+use std::io::prelude::*;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// Receives the progress of a long-running solver, e.g. the rounds played so far out of all
+/// rounds. Solvers report through `Context::progress` and never see the reporter itself.
+pub trait Reporter: fmt::Debug + Send + Sync {
+    fn update(&self, done: u64, total: u64);
+
+    /// The solver stopped, whether it finished or not.
+    fn finish(&self);
+}
+
+/// How long a solver runs before its bar shows up, so that fast solvers do not flicker.
+const DELAY: Duration = Duration::from_millis(300);
+/// How often the bar is redrawn at most.
+const INTERVAL: Duration = Duration::from_millis(100);
+const WIDTH: usize = 30;
+
+/// Whether some bar is on the last line of stderr. Runs of several days in parallel share that
+/// line, which shows whichever bar was updated last.
+static DRAWN: Mutex<bool> = Mutex::new(false);
+
+/// A progress bar drawn on the last line of stderr, meant for a terminal.
+#[derive(Debug)]
+pub struct Bar {
+    label: String,
+    start: Instant,
+    last_draw: Mutex<Option<Instant>>,
+}
+
+impl Bar {
+    pub fn new(label: impl Into<String>) -> Self {
+        Bar {
+            label: label.into(),
+            start: Instant::now(),
+            last_draw: Mutex::new(None),
+        }
+    }
+}
+
+impl Reporter for Bar {
+    fn update(&self, done: u64, total: u64) {
+        let now = Instant::now();
+        if now - self.start < DELAY {
+            return;
+        }
+
+        let mut last_draw = self.last_draw.lock().unwrap();
+        if last_draw.is_some_and(|t| now - t < INTERVAL) {
+            return;
+        }
+        *last_draw = Some(now);
+
+        let fraction = match total {
+            0 => 1.0,
+            _ => done.min(total) as f64 / total as f64,
+        };
+        let filled = (fraction * WIDTH as f64) as usize;
+
+        let mut drawn = DRAWN.lock().unwrap();
+        eprint!(
+            "\r\x1b[2K{} [{}{}] {:>3}% ({}/{})",
+            self.label,
+            "#".repeat(filled),
+            "-".repeat(WIDTH - filled),
+            (fraction * 100.0) as u32,
+            done,
+            total
+        );
+        let _ = io::stderr().flush();
+        *drawn = true;
+    }
+
+    fn finish(&self) {
+        if self.last_draw.lock().unwrap().is_none() {
+            return;
+        }
+
+        let mut drawn = DRAWN.lock().unwrap();
+        if *drawn {
+            eprint!("\r\x1b[2K");
+            let _ = io::stderr().flush();
+            *drawn = false;
+        }
+    }
+}
@@ -0,0 +1,284 @@
+use std::cell::RefCell;
+use std::collections::BTreeMap;
+use std::fmt::Display;
+use std::io;
+use std::str::FromStr;
+use std::sync::{Arc, OnceLock};
+use std::time::Instant;
+
+use crate::progress::Reporter;
+use crate::*;
+
+/// Signature shared by all solvers: the raw puzzle input and the run context in, the answer
+/// (if any) out.
+pub type SolveFn = fn(&str, &Context) -> io::Result<Option<String>>;
+
+/// Name of the variant registered by `Solver::new`.
+pub const DEFAULT_VARIANT: &str = "default";
+
+/// How a solver computes its answer: a function of this crate, or an external program given as
+/// its command line (see the `external` module).
+#[derive(Clone, Copy)]
+pub enum Solve {
+    Native(SolveFn),
+    External(&'static [String]),
+}
+
+/// A single solution to one part of one day. A part can have several solutions, told apart by
+/// their variant name, e.g. a naive and an optimized one.
+#[derive(Clone, Copy)]
+pub struct Solver {
+    pub day: u8,
+    pub part: u8,
+    pub variant: &'static str,
+    pub solve: Solve,
+    /// Whether the solver is a placeholder that does not compute the actual answer yet.
+    pub stub: bool,
+}
+
+impl Solver {
+    pub fn new(day: u8, part: u8, solve: SolveFn) -> Self {
+        Solver::variant(day, part, DEFAULT_VARIANT, solve)
+    }
+
+    pub fn variant(day: u8, part: u8, variant: &'static str, solve: SolveFn) -> Self {
+        Solver {
+            day,
+            part,
+            variant,
+            solve: Solve::Native(solve),
+            stub: false,
+        }
+    }
+
+    /// A solver running an external program, registered from the configuration.
+    pub fn external(day: u8, part: u8, variant: &'static str, command: &'static [String]) -> Self {
+        Solver {
+            day,
+            part,
+            variant,
+            solve: Solve::External(command),
+            stub: false,
+        }
+    }
+
+    pub fn is_external(&self) -> bool {
+        matches!(self.solve, Solve::External(_))
+    }
+
+    /// Compute the answer for the given input.
+    pub fn answer(&self, input: &str, ctx: &Context) -> io::Result<Option<String>> {
+        match self.solve {
+            Solve::Native(solve) => solve(input, ctx),
+            Solve::External(command) => external::solve(command, self.day, self.part, input, ctx),
+        }
+    }
+
+    /// Mark the solver as a placeholder.
+    pub fn stub(mut self) -> Self {
+        self.stub = true;
+        self
+    }
+}
+
+/// Named parameters handed to the solvers, such as `day07.threshold`. Keys are prefixed with the
+/// day they belong to, and solvers fall back to the puzzle's own values for missing keys.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Params(BTreeMap<String, String>);
+
+impl Params {
+    pub fn new() -> Self {
+        Params(BTreeMap::new())
+    }
+
+    pub fn insert(&mut self, key: &str, value: &str) {
+        self.0.insert(key.to_string(), value.to_string());
+    }
+
+    pub fn get<T>(&self, key: &str, default: T) -> io::Result<T>
+    where
+        T: FromStr,
+        T::Err: Display,
+    {
+        match self.0.get(key) {
+            Some(value) => value.parse::<T>().map_err(|e| {
+                io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    format!("Invalid value \"{}\" for parameter {}: {}", value, key, e),
+                )
+            }),
+            None => Ok(default),
+        }
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&String, &String)> {
+        self.0.iter()
+    }
+}
+
+/// Everything a solver is handed besides its input: the run parameters, a place to report how
+/// the answer was derived when running in explain mode, the time by which it should give up and
+/// where to report how far along it is.
+#[derive(Debug, Default)]
+pub struct Context {
+    pub params: Params,
+    explanation: Option<RefCell<Vec<String>>>,
+    deadline: Option<Instant>,
+    progress: Option<Arc<dyn Reporter>>,
+}
+
+impl Context {
+    pub fn new(params: Params, explain: bool) -> Self {
+        Context {
+            params,
+            explanation: match explain {
+                true => Some(RefCell::new(Vec::new())),
+                false => None,
+            },
+            deadline: None,
+            progress: None,
+        }
+    }
+
+    pub fn with_progress(mut self, reporter: Arc<dyn Reporter>) -> Self {
+        self.progress = Some(reporter);
+        self
+    }
+
+    /// Report that `done` out of `total` steps of a long-running loop are done. Without a
+    /// reporter, e.g. in machine-readable output modes, this does nothing.
+    pub fn progress(&self, done: u64, total: u64) {
+        if let Some(progress) = &self.progress {
+            progress.update(done, total);
+        }
+    }
+
+    pub fn with_deadline(mut self, deadline: Instant) -> Self {
+        self.deadline = Some(deadline);
+        self
+    }
+
+    pub fn deadline(&self) -> Option<Instant> {
+        self.deadline
+    }
+
+    /// Fail with `TimedOut` once the deadline of the run has passed. Long-running loops call this
+    /// regularly, so that an overrunning solver stops instead of hogging a thread.
+    pub fn check_deadline(&self) -> io::Result<()> {
+        match self.deadline {
+            Some(deadline) if Instant::now() > deadline => Err(io::Error::new(
+                io::ErrorKind::TimedOut,
+                "Cancelled after reaching the time limit",
+            )),
+            _ => Ok(()),
+        }
+    }
+
+    pub fn explaining(&self) -> bool {
+        self.explanation.is_some()
+    }
+
+    /// Record a line of the explanation. The line is only built when running in explain mode.
+    pub fn explain(&self, line: impl FnOnce() -> String) {
+        if let Some(explanation) = &self.explanation {
+            explanation.borrow_mut().push(line());
+        }
+    }
+
+    pub fn into_explanation(self) -> Vec<String> {
+        match self.explanation {
+            Some(explanation) => explanation.into_inner(),
+            None => Vec::new(),
+        }
+    }
+}
+
+/// Solvers registered at runtime, i.e. the external programs declared in the configuration.
+static EXTERNAL: OnceLock<Vec<Solver>> = OnceLock::new();
+
+/// Register the external solvers. They come after the solvers of this crate, so they are never
+/// the default variant of a part that has a solver of its own.
+pub fn register_external(external: Vec<Solver>) -> io::Result<()> {
+    let native = solvers();
+    for solver in external.iter() {
+        if native
+            .iter()
+            .any(|s| (s.day, s.part, s.variant) == (solver.day, solver.part, solver.variant))
+        {
+            return Err(io::Error::new(
+                io::ErrorKind::AlreadyExists,
+                format!(
+                    "Day {} part {} already has a variant \"{}\"",
+                    solver.day, solver.part, solver.variant
+                ),
+            ));
+        }
+    }
+
+    EXTERNAL.set(external).map_err(|_| {
+        io::Error::new(
+            io::ErrorKind::AlreadyExists,
+            "The external solvers are already registered",
+        )
+    })
+}
+
+/// All registered solvers, ordered by day and part. Variants of the same part keep the order in
+/// which their day registers them, followed by the external ones.
+pub fn solvers() -> Vec<Solver> {
+    let mut solvers = [
+        day01::solvers(),
+        day02::solvers(),
+        day03::solvers(),
+        day04::solvers(),
+        day05::solvers(),
+        day06::solvers(),
+        day07::solvers(),
+        day08::solvers(),
+        day09::solvers(),
+        day10::solvers(),
+        day11::solvers(),
+        day12::solvers(),
+        day13::solvers(),
+        EXTERNAL.get().cloned().unwrap_or_default(),
+    ]
+    .concat();
+    solvers.sort_by_key(|s| (s.day, s.part));
+
+    solvers
+}
+
+/// The days that have at least one registered solver.
+pub fn days() -> Vec<u8> {
+    let mut days: Vec<u8> = solvers().iter().map(|s| s.day).collect();
+    days.dedup();
+
+    days
+}
+
+/// All variants of a given day, optionally restricted to a single part (part 0 selects both).
+pub fn variants(day: u8, part: u8) -> Vec<Solver> {
+    solvers()
+        .into_iter()
+        .filter(|s| s.day == day && (part == 0 || s.part == part))
+        .collect()
+}
+
+/// One solver per part of a given day: the named variant if given, otherwise the first variant
+/// registered for the part. Parts without the named variant are left out.
+pub fn find(day: u8, part: u8, variant: Option<&str>) -> Vec<Solver> {
+    let mut found: Vec<Solver> = Vec::new();
+
+    for solver in variants(day, part).into_iter() {
+        let selected = match variant {
+            Some(v) => solver.variant == v,
+            None => !found.iter().any(|s| s.part == solver.part),
+        };
+
+        if selected {
+            found.push(solver);
+        }
+    }
+
+    found
+}
@@ -0,0 +1,729 @@
+use serde::Serialize;
+use std::cell::{Cell, RefCell};
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::fmt;
+use std::io;
+use std::io::prelude::*;
+use std::panic::{self, AssertUnwindSafe};
+use std::path::Path;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{mpsc, Arc, Mutex, Once};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::answers::{Answers, Verdict};
+use crate::cache::Cache;
+use crate::config::OutputFormat;
+use crate::progress::{Bar, Reporter};
+use crate::registry::{self, Context, Params, Solver};
+use crate::{embedded, input_file, input_sets};
+
+/// What to run for each selected day, and how.
+#[derive(Debug, Clone, Default)]
+pub struct RunOptions {
+    /// The part to run, or 0 for both parts.
+    pub part: u8,
+    /// The variant to run, or `None` for the default variant of each part.
+    pub variant: Option<String>,
+    pub params: Params,
+    /// Whether the solvers should report how they derived their answers.
+    pub explain: bool,
+    /// Where to look up and store answers, if anywhere.
+    pub cache: Option<Cache>,
+    /// The input to run each day on, or `None` for every input of the day.
+    pub input_set: Option<String>,
+    pub time_limits: TimeLimits,
+    /// Whether to draw a progress bar for long-running solvers.
+    pub progress: bool,
+}
+
+/// How long each part may run before it is reported as a timeout. A limit can be set for all
+/// days, for a single day, or for a single part of a day, with the most specific one applying.
+#[derive(Debug, Clone, Default)]
+pub struct TimeLimits {
+    default: Option<Duration>,
+    /// Keyed by (day, part), with part 0 covering both parts of the day.
+    overrides: BTreeMap<(u8, u8), Duration>,
+}
+
+impl TimeLimits {
+    /// Limits of `default` for every part. A zero duration means no limit.
+    pub fn new(default: Duration) -> Self {
+        TimeLimits {
+            default: Some(default).filter(|d| !d.is_zero()),
+            overrides: BTreeMap::new(),
+        }
+    }
+
+    /// Set the limit of a day (part 0) or of a single part. A zero duration lifts the limit.
+    pub fn set(&mut self, day: u8, part: u8, limit: Duration) {
+        self.overrides.insert((day, part), limit);
+    }
+
+    pub fn get(&self, day: u8, part: u8) -> Option<Duration> {
+        match self
+            .overrides
+            .get(&(day, part))
+            .or_else(|| self.overrides.get(&(day, 0)))
+        {
+            Some(limit) => Some(*limit).filter(|d| !d.is_zero()),
+            None => self.default,
+        }
+    }
+}
+
+/// The outcome of running a single solver.
+#[derive(Debug)]
+pub struct Run {
+    pub day: u8,
+    pub part: u8,
+    pub variant: &'static str,
+    pub answer: io::Result<Option<String>>,
+    pub time: Duration,
+    pub explanation: Vec<String>,
+    /// Whether the answer was taken from the cache rather than computed.
+    pub cached: bool,
+}
+
+/// A panic caught while running a solver, reported as the error of its run.
+#[derive(Debug, Clone)]
+pub struct Panic {
+    pub message: String,
+    /// Where the panic happened, as `file:line:column`.
+    pub location: Option<String>,
+}
+
+impl fmt::Display for Panic {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match &self.location {
+            Some(location) => write!(f, "panicked at {}: {}", location, self.message),
+            None => write!(f, "panicked: {}", self.message),
+        }
+    }
+}
+
+impl Error for Panic {}
+
+thread_local! {
+    /// Whether panics on this thread are caught by `catch_panic`, and the last one caught.
+    static CATCHING: Cell<bool> = const { Cell::new(false) };
+    static CAUGHT: RefCell<Option<Panic>> = const { RefCell::new(None) };
+}
+
+/// Run `f`, turning a panic into an error instead of unwinding any further. The panic is not
+/// printed when it happens, but reported through the returned error.
+fn catch_panic<T>(f: impl FnOnce() -> io::Result<T>) -> io::Result<T> {
+    static HOOK: Once = Once::new();
+    HOOK.call_once(|| {
+        let default_hook = panic::take_hook();
+        panic::set_hook(Box::new(move |info| {
+            if !CATCHING.get() {
+                return default_hook(info);
+            }
+
+            let payload = info.payload();
+            let message = match (
+                payload.downcast_ref::<&str>(),
+                payload.downcast_ref::<String>(),
+            ) {
+                (Some(s), _) => s.to_string(),
+                (_, Some(s)) => s.clone(),
+                _ => "Box<dyn Any>".to_string(),
+            };
+            let location = info
+                .location()
+                .map(|l| format!("{}:{}:{}", l.file(), l.line(), l.column()));
+
+            CAUGHT.set(Some(Panic { message, location }));
+        }));
+    });
+
+    CATCHING.set(true);
+    let result = panic::catch_unwind(AssertUnwindSafe(f));
+    CATCHING.set(false);
+
+    match result {
+        Ok(result) => result,
+        Err(_) => {
+            let panic = CAUGHT.take().unwrap_or(Panic {
+                message: "unknown panic".to_string(),
+                location: None,
+            });
+            Err(io::Error::other(panic))
+        }
+    }
+}
+
+/// The panic that stopped the run, if it panicked.
+pub fn panic_of(run: &Run) -> Option<&Panic> {
+    run.answer
+        .as_ref()
+        .err()
+        .and_then(|e| e.get_ref())
+        .and_then(|e| e.downcast_ref::<Panic>())
+}
+
+/// All runs of a single day on one of its inputs, or the error that prevented the day from running
+/// at all.
+#[derive(Debug)]
+pub struct DayRuns {
+    pub day: u8,
+    /// The name of the input, or `None` for the `day_XX.txt` input.
+    pub input_set: Option<String>,
+    pub runs: io::Result<Vec<Run>>,
+}
+
+impl DayRuns {
+    fn header(&self) -> String {
+        match &self.input_set {
+            Some(name) => format!("# ---- DAY {:0>2} ({}) ---- #", self.day, name),
+            None => format!("# ---- DAY {:0>2} ---- #", self.day),
+        }
+    }
+}
+
+pub fn read_input(input_dir: &Path, day: u8) -> io::Result<String> {
+    let mut input = String::new();
+    input_file(input_dir, day)?.read_to_string(&mut input)?;
+
+    Ok(input)
+}
+
+pub fn run(solver: &Solver, input: &str, options: &RunOptions) -> Run {
+    // explanations are not cached, so explain mode always computes the answer; neither are the
+    // answers of external programs, which can change without the cache noticing
+    let cache = options
+        .cache
+        .as_ref()
+        .filter(|_| !options.explain && !solver.is_external());
+
+    if let Some(cache) = cache {
+        let now = Instant::now();
+        if let Some(entry) = cache.get(solver, input, &options.params) {
+            return Run {
+                day: solver.day,
+                part: solver.part,
+                variant: solver.variant,
+                answer: Ok(entry.answer),
+                time: now.elapsed(),
+                explanation: Vec::new(),
+                cached: true,
+            };
+        }
+    }
+
+    let mut ctx = Context::new(options.params.clone(), options.explain);
+    let progress: Option<Arc<dyn Reporter>> = match options.progress {
+        true => Some(Arc::new(Bar::new(format!(
+            "Day {} part {}",
+            solver.day, solver.part
+        )))),
+        false => None,
+    };
+    if let Some(progress) = &progress {
+        ctx = ctx.with_progress(progress.clone());
+    }
+
+    let (answer, time, explanation) = match options.time_limits.get(solver.day, solver.part) {
+        Some(limit) => solve_with_limit(solver, input, ctx, limit),
+        None => {
+            let now = Instant::now();
+            let answer = catch_panic(|| solver.answer(input, &ctx));
+            (answer, now.elapsed(), ctx.into_explanation())
+        }
+    };
+
+    if let Some(progress) = progress {
+        progress.finish();
+    }
+
+    if let (Some(cache), Ok(answer)) = (cache, &answer) {
+        if let Err(e) = cache.put(solver, input, &options.params, answer, time) {
+            eprintln!("Could not write to the cache: {}", e);
+        }
+    }
+
+    Run {
+        day: solver.day,
+        part: solver.part,
+        variant: solver.variant,
+        answer,
+        time,
+        explanation,
+        cached: false,
+    }
+}
+
+type Solution = (io::Result<Option<String>>, Duration, Vec<String>);
+
+/// Run the solver on a thread of its own and stop waiting for it once the limit is reached. The
+/// solver is told about the deadline through its context, so that cooperating solvers stop
+/// working as well; the others are left to finish in the background.
+fn solve_with_limit(solver: &Solver, input: &str, ctx: Context, limit: Duration) -> Solution {
+    let timeout = || {
+        io::Error::new(
+            io::ErrorKind::TimedOut,
+            format!("Exceeded the time limit of {} ms", limit.as_millis()),
+        )
+    };
+
+    let (sender, receiver) = mpsc::channel();
+    let solver = *solver;
+    let input = input.to_string();
+    let now = Instant::now();
+    let ctx = ctx.with_deadline(now + limit);
+
+    thread::spawn(move || {
+        let answer = catch_panic(|| solver.answer(&input, &ctx));
+        // the runner may have stopped waiting, in which case nobody is listening anymore
+        let _ = sender.send((answer, now.elapsed(), ctx.into_explanation()));
+    });
+
+    match receiver.recv_timeout(limit) {
+        Ok((answer, time, explanation)) if time <= limit => (answer, time, explanation),
+        // finished (or gave up) just after the limit
+        Ok((_, time, explanation)) => (Err(timeout()), time, explanation),
+        Err(mpsc::RecvTimeoutError::Timeout) => (Err(timeout()), now.elapsed(), Vec::new()),
+        Err(mpsc::RecvTimeoutError::Disconnected) => (
+            Err(io::Error::other("The solver stopped without an answer")),
+            now.elapsed(),
+            Vec::new(),
+        ),
+    }
+}
+
+/// Whether the run was stopped for exceeding its time limit.
+pub fn timed_out(run: &Run) -> bool {
+    matches!(&run.answer, Err(e) if e.kind() == io::ErrorKind::TimedOut)
+}
+
+/// Run the solvers on every selected input of the day, giving the runs of each input.
+fn run_solvers(
+    day: u8,
+    solvers: &[Solver],
+    input_dir: &Path,
+    options: &RunOptions,
+) -> Vec<DayRuns> {
+    let failed = |error: io::Error| {
+        vec![DayRuns {
+            day,
+            input_set: options.input_set.clone(),
+            runs: Err(error),
+        }]
+    };
+
+    let sets = match input_sets(input_dir, day) {
+        Ok(sets) => sets,
+        Err(e) => return failed(e),
+    };
+    let sets: Vec<_> = match &options.input_set {
+        Some(name) => sets
+            .into_iter()
+            .filter(|s| s.display_name() == name)
+            .collect(),
+        None => sets,
+    };
+
+    if let (true, Some(name)) = (sets.is_empty(), &options.input_set) {
+        return failed(io::Error::new(
+            io::ErrorKind::NotFound,
+            format!("Day {} has no input \"{}\"", day, name),
+        ));
+    }
+
+    sets.into_iter()
+        .map(|set| {
+            let runs = embedded::read_to_string(input_dir, &set.path).map(|input| {
+                solvers
+                    .iter()
+                    .map(|solver| run(solver, &input, options))
+                    .collect()
+            });
+
+            DayRuns {
+                day,
+                input_set: set.name,
+                runs,
+            }
+        })
+        .collect()
+}
+
+pub fn run_day(day: u8, input_dir: &Path, options: &RunOptions) -> Vec<DayRuns> {
+    let solvers = registry::find(day, options.part, options.variant.as_deref());
+    if solvers.is_empty() {
+        if let Some(variant) = &options.variant {
+            let error = io::Error::new(
+                io::ErrorKind::NotFound,
+                format!("Day {} has no variant \"{}\"", day, variant),
+            );
+            return vec![DayRuns {
+                day,
+                input_set: None,
+                runs: Err(error),
+            }];
+        }
+    }
+
+    run_solvers(day, &solvers, input_dir, options)
+}
+
+/// Run every variant of the day (or of the selected part) on the same inputs.
+pub fn run_variants(day: u8, input_dir: &Path, options: &RunOptions) -> Vec<DayRuns> {
+    let solvers = registry::variants(day, options.part);
+
+    run_solvers(day, &solvers, input_dir, options)
+}
+
+/// A part that failed to produce an answer, or a day that could not run at all (without a part).
+#[derive(Debug)]
+pub struct Failure<'a> {
+    pub day: u8,
+    pub input_set: Option<&'a str>,
+    pub part: Option<u8>,
+    pub error: &'a io::Error,
+}
+
+/// Every failure among the results, in the order they were run.
+pub fn failures(results: &[DayRuns]) -> Vec<Failure<'_>> {
+    let mut failures: Vec<Failure> = Vec::new();
+
+    for day_runs in results.iter() {
+        match &day_runs.runs {
+            Ok(runs) => failures.extend(runs.iter().filter_map(|r| {
+                r.answer.as_ref().err().map(|error| Failure {
+                    day: r.day,
+                    input_set: day_runs.input_set.as_deref(),
+                    part: Some(r.part),
+                    error,
+                })
+            })),
+            Err(error) => failures.push(Failure {
+                day: day_runs.day,
+                input_set: day_runs.input_set.as_deref(),
+                part: None,
+                error,
+            }),
+        }
+    }
+
+    failures
+}
+
+/// The parts whose variants did not all produce the same answer.
+pub fn disagreements(runs: &[Run]) -> Vec<u8> {
+    let mut parts: Vec<u8> = runs.iter().map(|r| r.part).collect();
+    parts.dedup();
+
+    parts
+        .into_iter()
+        .filter(|&part| {
+            // surrounding whitespace is not part of an answer, as with the expected answers
+            let answers: Vec<Option<Option<&str>>> = runs
+                .iter()
+                .filter(|r| r.part == part)
+                .map(|r| r.answer.as_ref().ok().map(|a| a.as_deref().map(str::trim)))
+                .collect();
+
+            // a failing variant counts as a disagreement
+            answers.iter().any(|a| a.is_none() || a != &answers[0])
+        })
+        .collect()
+}
+
+/// Run the given days, spreading them over `jobs` threads. The results keep the order of `days`,
+/// with one entry per input of each day.
+pub fn run_days(days: &[u8], input_dir: &Path, options: &RunOptions, jobs: usize) -> Vec<DayRuns> {
+    let next = AtomicUsize::new(0);
+    let results: Mutex<Vec<Option<Vec<DayRuns>>>> =
+        Mutex::new((0..days.len()).map(|_| None).collect());
+
+    thread::scope(|s| {
+        for _ in 0..jobs.clamp(1, days.len().max(1)) {
+            s.spawn(|| loop {
+                let i = next.fetch_add(1, Ordering::SeqCst);
+                if i >= days.len() {
+                    break;
+                }
+
+                let day_runs = run_day(days[i], input_dir, options);
+                results.lock().unwrap()[i] = Some(day_runs);
+            });
+        }
+    });
+
+    results
+        .into_inner()
+        .unwrap()
+        .into_iter()
+        .flatten()
+        .flatten()
+        .collect()
+}
+
+/// A run in the form reported as JSON, both on the command line and by the server.
+#[derive(Serialize)]
+pub(crate) struct RunReport<'a> {
+    day: u8,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    input_set: Option<&'a str>,
+    part: Option<u8>,
+    variant: Option<&'a str>,
+    answer: Option<&'a str>,
+    time_us: Option<u128>,
+    error: Option<String>,
+    #[serde(skip_serializing_if = "<[String]>::is_empty")]
+    explanation: &'a [String],
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    cached: bool,
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    timeout: bool,
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    panicked: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    verdict: Option<&'static str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    expected: Option<&'a str>,
+}
+
+impl<'a> RunReport<'a> {
+    pub(crate) fn new(
+        run: &'a Run,
+        input_set: Option<&'a str>,
+        answers: Option<&'a Answers>,
+    ) -> Self {
+        let (answer, error) = match &run.answer {
+            Ok(x) => (x.as_deref(), None),
+            Err(e) => (None, Some(e.to_string())),
+        };
+        let verdict = match (answers, error.is_none()) {
+            (Some(answers), true) => Some(answers.verdict(run.day, input_set, run.part, answer)),
+            _ => None,
+        };
+
+        RunReport {
+            day: run.day,
+            input_set,
+            part: Some(run.part),
+            variant: Some(run.variant),
+            answer,
+            time_us: Some(run.time.as_micros()),
+            error,
+            explanation: &run.explanation,
+            cached: run.cached,
+            timeout: timed_out(run),
+            panicked: panic_of(run).is_some(),
+            verdict: verdict.as_ref().map(|v| match v {
+                Verdict::Correct => "correct",
+                Verdict::Wrong(_) => "wrong",
+                Verdict::Unknown => "unknown",
+            }),
+            expected: answers.and_then(|a| a.get_for(run.day, input_set, run.part)),
+        }
+    }
+}
+
+pub fn print(results: &[DayRuns], format: OutputFormat) {
+    match format {
+        OutputFormat::Text => print_text(results, None),
+        OutputFormat::Json => print_json(results, None),
+    }
+}
+
+/// Print the results along with whether they match the expected answers.
+pub fn print_checked(results: &[DayRuns], answers: &Answers, format: OutputFormat) {
+    match format {
+        OutputFormat::Text => print_text(results, Some(answers)),
+        OutputFormat::Json => print_json(results, Some(answers)),
+    }
+}
+
+/// The runs whose answer differs from the expected one.
+pub fn wrong_answers<'a>(results: &'a [DayRuns], answers: &Answers) -> Vec<&'a Run> {
+    let mut wrong: Vec<&Run> = Vec::new();
+
+    for day_runs in results.iter() {
+        let input_set = day_runs.input_set.as_deref();
+        let runs = day_runs.runs.iter().flatten().filter(|r| {
+            let answer = r.answer.as_ref().ok().and_then(|a| a.as_deref());
+            matches!(
+                answers.verdict(r.day, input_set, r.part, answer),
+                Verdict::Wrong(_)
+            )
+        });
+        wrong.extend(runs);
+    }
+
+    wrong
+}
+
+/// Print the runs of all variants of a day side by side, flagging the parts they disagree on.
+pub fn print_comparison(day_runs: &DayRuns, format: OutputFormat) {
+    let runs = match (&day_runs.runs, format) {
+        (Ok(runs), OutputFormat::Text) => runs,
+        _ => return print(std::slice::from_ref(day_runs), format),
+    };
+
+    let answer = |run: &Run| match &run.answer {
+        Ok(Some(x)) => x.clone(),
+        Ok(None) => "-".to_string(),
+        Err(_) if timed_out(run) => "TIMEOUT".to_string(),
+        Err(e) => format!("error: {}", e),
+    };
+
+    // multi-line answers are shown on their own lines below the table
+    let single_line = runs.iter().all(|r| !answer(r).contains('\n'));
+    let width = runs
+        .iter()
+        .map(|r| if single_line { answer(r).len() } else { 0 })
+        .max()
+        .unwrap_or(0)
+        .max("Answer".len());
+    let variant_width = runs
+        .iter()
+        .map(|r| r.variant.len())
+        .max()
+        .unwrap_or(0)
+        .max(7);
+    let disagreements = disagreements(runs);
+
+    println!("{}", day_runs.header());
+    println!(
+        "{:<4}  {:<vw$}  {:<w$}  {:>12}",
+        "Part",
+        "Variant",
+        "Answer",
+        "Time",
+        vw = variant_width,
+        w = width
+    );
+
+    for run in runs.iter() {
+        let shown = if single_line {
+            answer(run)
+        } else {
+            String::new()
+        };
+        let flag = if disagreements.contains(&run.part) {
+            "  MISMATCH"
+        } else {
+            ""
+        };
+
+        println!(
+            "{:<4}  {:<vw$}  {:<w$}  {:>9} µs{}",
+            run.part,
+            run.variant,
+            shown,
+            run.time.as_micros(),
+            flag,
+            vw = variant_width,
+            w = width
+        );
+    }
+
+    if !single_line {
+        for run in runs.iter() {
+            println!("\nPart {} ({}): {}", run.part, run.variant, answer(run));
+        }
+    }
+
+    println!();
+}
+
+fn print_text(results: &[DayRuns], answers: Option<&Answers>) {
+    for day_runs in results.iter() {
+        println!("{}", day_runs.header());
+        let input_set = day_runs.input_set.as_deref();
+
+        match &day_runs.runs {
+            Ok(runs) => {
+                for run in runs.iter() {
+                    let mut note = String::new();
+                    if run.cached {
+                        note.push_str(" (cached)");
+                    }
+                    if let (Some(answers), Ok(answer)) = (answers, &run.answer) {
+                        note.push_str(
+                            match answers.verdict(run.day, input_set, run.part, answer.as_deref())
+                            {
+                                Verdict::Correct => " (correct)".to_string(),
+                                Verdict::Wrong(expected) => {
+                                    format!(" (wrong, expected {})", expected)
+                                }
+                                Verdict::Unknown => " (no expected answer)".to_string(),
+                            }
+                            .as_str(),
+                        );
+                    }
+
+                    match &run.answer {
+                        Ok(Some(x)) => println!("Part {}: {}{}", run.part, x, note),
+                        Ok(None) => println!("Part {}: -{}", run.part, note),
+                        Err(e) if timed_out(run) => println!("Part {}: TIMEOUT ({})", run.part, e),
+                        Err(e) => println!("Part {}: error: {}", run.part, e),
+                    }
+
+                    for line in run.explanation.iter() {
+                        println!("  {}", line);
+                    }
+                }
+
+                let time: Duration = runs.iter().map(|r| r.time).sum();
+                println!("Time elapsed: {} µs", time.as_micros());
+            }
+            Err(e) => println!("Error: {}", e),
+        }
+
+        println!();
+    }
+
+    let failures = failures(results);
+    if !failures.is_empty() {
+        println!("# ---- FAILURES ---- #");
+        for failure in failures.iter() {
+            let mut what = format!("Day {}", failure.day);
+            if let Some(name) = failure.input_set {
+                what.push_str(&format!(" ({})", name));
+            }
+            if let Some(part) = failure.part {
+                what.push_str(&format!(", part {}", part));
+            }
+            println!("{}: {}", what, failure.error);
+        }
+        println!("{} failure(s)", failures.len());
+        println!();
+    }
+}
+
+fn print_json(results: &[DayRuns], answers: Option<&Answers>) {
+    let mut reports: Vec<RunReport> = Vec::new();
+
+    for day_runs in results.iter() {
+        match &day_runs.runs {
+            Ok(runs) => {
+                for run in runs.iter() {
+                    reports.push(RunReport::new(run, day_runs.input_set.as_deref(), answers));
+                }
+            }
+            Err(e) => reports.push(RunReport {
+                day: day_runs.day,
+                input_set: day_runs.input_set.as_deref(),
+                part: None,
+                variant: None,
+                answer: None,
+                time_us: None,
+                error: Some(e.to_string()),
+                explanation: &[],
+                cached: false,
+                timeout: false,
+                panicked: false,
+                verdict: None,
+                expected: None,
+            }),
+        }
+    }
+
+    println!("{}", serde_json::to_string_pretty(&reports).unwrap());
+}
@@ -0,0 +1,302 @@
+use std::io;
+use std::io::prelude::*;
+use std::io::BufReader;
+use std::net::{TcpListener, TcpStream};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::registry;
+use crate::runner::{self, RunOptions, RunReport};
+
+/// Address the server listens on unless told otherwise.
+pub const DEFAULT_ADDR: &str = "127.0.0.1:8022";
+
+/// Largest request body accepted, a few times the size of the largest puzzle input (about 11 KB).
+const MAX_BODY: usize = 256 * 1024;
+
+/// Largest request line and headers accepted, all together.
+const MAX_HEAD: u64 = 16 * 1024;
+
+/// Connections handled at the same time; any more are turned away with a 503.
+const MAX_CONNECTIONS: usize = 16;
+
+/// How long a client has to send its whole request, counted from when the connection is
+/// accepted, and how long a write of the response may stall.
+const TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Reads from a connection until a deadline, however slowly the client trickles its bytes in.
+struct Deadline<'a> {
+    stream: &'a TcpStream,
+    deadline: Instant,
+}
+
+impl Read for Deadline<'_> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        // a read timeout only bounds a single read, so it is shortened to the time left
+        let left = self.deadline.saturating_duration_since(Instant::now());
+        if left.is_zero() {
+            return Err(io::ErrorKind::TimedOut.into());
+        }
+        self.stream.set_read_timeout(Some(left))?;
+        self.stream.read(buf)
+    }
+}
+
+struct Request {
+    method: String,
+    path: String,
+    query: Vec<(String, String)>,
+    body: Vec<u8>,
+}
+
+struct Response {
+    status: u16,
+    body: String,
+}
+
+impl Response {
+    fn json(status: u16, body: String) -> Self {
+        Response { status, body }
+    }
+
+    fn error(status: u16, message: impl Into<String>) -> Self {
+        let body = serde_json::json!({ "error": message.into() });
+        Response::json(status, serde_json::to_string_pretty(&body).unwrap())
+    }
+
+    fn reason(&self) -> &'static str {
+        match self.status {
+            200 => "OK",
+            400 => "Bad Request",
+            404 => "Not Found",
+            405 => "Method Not Allowed",
+            408 => "Request Timeout",
+            413 => "Payload Too Large",
+            422 => "Unprocessable Entity",
+            503 => "Service Unavailable",
+            _ => "Internal Server Error",
+        }
+    }
+}
+
+/// Serve the solvers over HTTP until the process is stopped. Every connection is handled on a
+/// thread of its own, up to `MAX_CONNECTIONS` at a time, and carries a single request:
+///
+/// `POST /2022/day/{day}/part/{part}[?variant=name&explain=true]` with the puzzle input as body
+///
+/// The response holds the same JSON report as `--format json` gives for a single run.
+pub fn serve(addr: &str, options: RunOptions) -> io::Result<()> {
+    let listener = TcpListener::bind(addr)?;
+    eprintln!("Listening on http://{}", listener.local_addr()?);
+
+    let options = Arc::new(options);
+    let active = Arc::new(AtomicUsize::new(0));
+    for stream in listener.incoming() {
+        let stream = match stream {
+            Ok(stream) => stream,
+            Err(e) => {
+                eprintln!("Could not accept a connection: {}", e);
+                continue;
+            }
+        };
+
+        // a stalled client must not hold on to a thread
+        let deadline = Instant::now() + TIMEOUT;
+        if let Err(e) = stream.set_write_timeout(Some(TIMEOUT)) {
+            eprintln!("Could not set up a connection: {}", e);
+            continue;
+        }
+
+        if active.fetch_add(1, Ordering::SeqCst) >= MAX_CONNECTIONS {
+            active.fetch_sub(1, Ordering::SeqCst);
+            let busy = Response::error(503, "Too many connections, try again later");
+            if let Err(e) = write_response(&stream, &busy) {
+                eprintln!("Could not turn a connection away: {}", e);
+            }
+            continue;
+        }
+
+        let options = Arc::clone(&options);
+        let active = Arc::clone(&active);
+        thread::spawn(move || {
+            if let Err(e) = handle(stream, deadline, &options) {
+                eprintln!("Could not handle a request: {}", e);
+            }
+            active.fetch_sub(1, Ordering::SeqCst);
+        });
+    }
+
+    Ok(())
+}
+
+fn handle(stream: TcpStream, deadline: Instant, options: &RunOptions) -> io::Result<()> {
+    let reader = Deadline {
+        stream: &stream,
+        deadline,
+    };
+    let response = match read_request(reader) {
+        Ok(request) => {
+            let response = respond(&request, options);
+            eprintln!("{} {} -> {}", request.method, request.path, response.status);
+            response
+        }
+        Err(e) if e.kind() == io::ErrorKind::InvalidData => Response::error(400, e.to_string()),
+        Err(e) if e.kind() == io::ErrorKind::OutOfMemory => Response::error(413, e.to_string()),
+        Err(e)
+            if matches!(
+                e.kind(),
+                io::ErrorKind::WouldBlock | io::ErrorKind::TimedOut
+            ) =>
+        {
+            Response::error(408, "The request was not received in time")
+        }
+        Err(e) => return Err(e),
+    };
+
+    write_response(&stream, &response)
+}
+
+fn write_response(mut stream: &TcpStream, response: &Response) -> io::Result<()> {
+    write!(
+        stream,
+        "HTTP/1.1 {} {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\n",
+        response.status,
+        response.reason(),
+        response.body.len()
+    )?;
+    write!(stream, "Connection: close\r\n\r\n{}", response.body)?;
+    stream.flush()
+}
+
+fn read_request(stream: impl Read) -> io::Result<Request> {
+    let invalid = |msg: &str| io::Error::new(io::ErrorKind::InvalidData, msg.to_string());
+    let mut reader = BufReader::new(stream);
+
+    // the request line and headers share one budget, so that an endless line fails early
+    let mut head = (&mut reader).take(MAX_HEAD);
+    let too_large = || {
+        io::Error::new(
+            io::ErrorKind::OutOfMemory,
+            format!(
+                "The request line and headers are larger than {} bytes",
+                MAX_HEAD
+            ),
+        )
+    };
+
+    let mut request_line = String::new();
+    head.read_line(&mut request_line)?;
+    if !request_line.ends_with('\n') && head.limit() == 0 {
+        return Err(too_large());
+    }
+    let mut words = request_line.split_whitespace();
+    let (method, target) = match (words.next(), words.next()) {
+        (Some(method), Some(target)) => (method.to_string(), target.to_string()),
+        _ => return Err(invalid("Malformed request line")),
+    };
+
+    // only the length of the body matters among the headers
+    let mut content_length: usize = 0;
+    loop {
+        let mut header = String::new();
+        if head.read_line(&mut header)? == 0 || header.trim().is_empty() {
+            break;
+        }
+        if !header.ends_with('\n') && head.limit() == 0 {
+            return Err(too_large());
+        }
+
+        if let Some((name, value)) = header.split_once(':') {
+            if name.trim().eq_ignore_ascii_case("content-length") {
+                content_length = value
+                    .trim()
+                    .parse()
+                    .map_err(|_| invalid("Invalid Content-Length"))?;
+            }
+        }
+    }
+
+    if content_length > MAX_BODY {
+        return Err(io::Error::new(
+            io::ErrorKind::OutOfMemory,
+            format!("The body is larger than {} bytes", MAX_BODY),
+        ));
+    }
+
+    let mut body = vec![0; content_length];
+    reader.read_exact(&mut body)?;
+
+    let (path, query) = match target.split_once('?') {
+        Some((path, query)) => (path.to_string(), query),
+        None => (target.clone(), ""),
+    };
+    let query = query
+        .split('&')
+        .filter(|pair| !pair.is_empty())
+        .map(|pair| match pair.split_once('=') {
+            Some((k, v)) => (k.to_string(), v.to_string()),
+            None => (pair.to_string(), String::new()),
+        })
+        .collect();
+
+    Ok(Request {
+        method,
+        path,
+        query,
+        body,
+    })
+}
+
+fn respond(request: &Request, options: &RunOptions) -> Response {
+    let segments: Vec<&str> = request.path.trim_matches('/').split('/').collect();
+    let (day, part) = match segments.as_slice() {
+        ["2022", "day", day, "part", part] => match (day.parse::<u8>(), part.parse::<u8>()) {
+            (Ok(day), Ok(part)) => (day, part),
+            _ => return Response::error(404, format!("No such puzzle: {}", request.path)),
+        },
+        _ => return Response::error(404, format!("No such route: {}", request.path)),
+    };
+
+    if request.method != "POST" {
+        return Response::error(405, "Post the puzzle input to this route");
+    }
+
+    let query = |key: &str| {
+        request
+            .query
+            .iter()
+            .find(|(k, _)| k == key)
+            .map(|(_, v)| v.as_str())
+    };
+    let variant = query("variant");
+
+    let solver = match registry::find(day, part, variant).into_iter().next() {
+        Some(solver) if part > 0 => solver,
+        _ => {
+            let mut message = format!("No solver for day {} part {}", day, part);
+            if let Some(variant) = variant {
+                message.push_str(&format!(" (variant \"{}\")", variant));
+            }
+            return Response::error(404, message);
+        }
+    };
+
+    let input = match String::from_utf8(request.body.clone()) {
+        Ok(input) => input,
+        Err(_) => return Response::error(400, "The puzzle input is not valid UTF-8"),
+    };
+
+    let options = RunOptions {
+        explain: matches!(query("explain"), Some("" | "1" | "true")),
+        ..options.clone()
+    };
+    let run = runner::run(&solver, &input, &options);
+    let body = serde_json::to_string_pretty(&RunReport::new(&run, None, None)).unwrap();
+
+    match run.answer {
+        Ok(_) => Response::json(200, body),
+        Err(_) => Response::json(422, body),
+    }
+}
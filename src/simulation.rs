@@ -0,0 +1,176 @@
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+/// A puzzle that advances its state in discrete steps, such as the rounds of the monkeys of day
+/// 11 or the cycles of the CPU of day 10.
+pub trait Simulation {
+    /// Everything needed to put the simulation back to where it was.
+    type Snapshot: Clone + Serialize + DeserializeOwned;
+
+    /// Advance by a single step, or return `false` without changing anything once the
+    /// simulation has ended.
+    fn step(&mut self) -> bool;
+
+    fn snapshot(&self) -> Self::Snapshot;
+
+    fn restore(&mut self, snapshot: Self::Snapshot);
+}
+
+/// Drives a simulation, counting its steps and keeping the snapshots of the last ones, so that
+/// it can be rewound, and saved to disk to be resumed later.
+#[derive(Debug)]
+pub struct Stepper<S: Simulation> {
+    simulation: S,
+    steps: usize,
+    /// Snapshots taken before each of the last steps, the oldest first.
+    history: VecDeque<S::Snapshot>,
+    /// How many snapshots to keep, i.e. how far the simulation can be rewound.
+    capacity: usize,
+}
+
+/// The content of a checkpoint file.
+#[derive(Serialize, Deserialize)]
+struct Checkpoint<T> {
+    /// Identifies what the simulation was started from (e.g. a hash of the input and the
+    /// settings), so that a checkpoint is never resumed by another run. Files written before
+    /// there was a key read as 0, and are discarded like any other mismatch.
+    #[serde(default)]
+    key: u64,
+    steps: usize,
+    snapshot: T,
+}
+
+impl<S: Simulation> Stepper<S> {
+    /// A stepper without history, for simulations that only ever go forward.
+    pub fn new(simulation: S) -> Self {
+        Stepper::with_history(simulation, 0)
+    }
+
+    /// A stepper able to rewind up to `capacity` steps.
+    pub fn with_history(simulation: S, capacity: usize) -> Self {
+        Stepper {
+            simulation,
+            steps: 0,
+            history: VecDeque::with_capacity(capacity),
+            capacity,
+        }
+    }
+
+    /// The number of steps taken since the start.
+    pub fn steps(&self) -> usize {
+        self.steps
+    }
+
+    pub fn simulation(&self) -> &S {
+        &self.simulation
+    }
+
+    pub fn into_inner(self) -> S {
+        self.simulation
+    }
+
+    /// Take a single step, returning `false` if the simulation has ended.
+    pub fn step(&mut self) -> bool {
+        let snapshot = match self.capacity {
+            0 => None,
+            _ => Some(self.simulation.snapshot()),
+        };
+
+        if !self.simulation.step() {
+            return false;
+        }
+        self.steps += 1;
+
+        if let Some(snapshot) = snapshot {
+            if self.history.len() == self.capacity {
+                self.history.pop_front();
+            }
+            self.history.push_back(snapshot);
+        }
+
+        true
+    }
+
+    /// Take steps until `done` holds, returning `false` if the simulation ended first. `done` is
+    /// called before every step with the simulation and the steps taken so far, and can stop the
+    /// run with an error, e.g. once the deadline of the solver has passed.
+    pub fn run_until(
+        &mut self,
+        mut done: impl FnMut(&S, usize) -> io::Result<bool>,
+    ) -> io::Result<bool> {
+        loop {
+            if done(&self.simulation, self.steps)? {
+                return Ok(true);
+            }
+            if !self.step() {
+                return Ok(false);
+            }
+        }
+    }
+
+    /// Go back `n` steps, or as far as the history allows, returning how many steps were undone.
+    pub fn rewind(&mut self, n: usize) -> usize {
+        let mut rewound: usize = 0;
+        while rewound < n {
+            let Some(snapshot) = self.history.pop_back() else {
+                break;
+            };
+            self.simulation.restore(snapshot);
+            self.steps -= 1;
+            rewound += 1;
+        }
+
+        rewound
+    }
+
+    /// Save the current state to `path`, from which `resume` can carry on later given the same
+    /// `key`.
+    pub fn checkpoint(&self, path: &Path, key: u64) -> io::Result<()> {
+        let checkpoint = Checkpoint {
+            key,
+            steps: self.steps,
+            snapshot: self.simulation.snapshot(),
+        };
+
+        if let Some(dir) = path.parent().filter(|d| !d.as_os_str().is_empty()) {
+            fs::create_dir_all(dir)?;
+        }
+
+        // write to a temporary file first, so that an interrupted write keeps the last checkpoint
+        let tmp = path.with_extension(format!("tmp{}", std::process::id()));
+        fs::write(&tmp, serde_json::to_string(&checkpoint)?)?;
+        fs::rename(tmp, path)
+    }
+
+    /// Restore the state saved at `path`, if there is a checkpoint there that was made with the
+    /// same `key`. A checkpoint made with another key belongs to another run and is discarded.
+    /// The history starts afresh, so the simulation cannot be rewound past the checkpoint.
+    pub fn resume(&mut self, path: &Path, key: u64) -> io::Result<bool> {
+        let content = match fs::read_to_string(path) {
+            Ok(content) => content,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(false),
+            Err(e) => return Err(e),
+        };
+        let checkpoint: Checkpoint<S::Snapshot> = serde_json::from_str(&content).map_err(|e| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("Invalid checkpoint {}: {}", path.display(), e),
+            )
+        })?;
+
+        if checkpoint.key != key {
+            fs::remove_file(path)?;
+            return Ok(false);
+        }
+
+        self.simulation.restore(checkpoint.snapshot);
+        self.steps = checkpoint.steps;
+        self.history.clear();
+
+        Ok(true)
+    }
+}
@@ -0,0 +1,144 @@
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use crate::runner::{DayRuns, Run};
+
+/// Default directory of the golden files, kept in the repository next to the sources.
+pub const SNAPSHOT_DIR: &str = "snapshots";
+
+/// Whether golden files are compared with, or replaced by, the actual output.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Mode {
+    Check,
+    Update,
+}
+
+/// The result of comparing an output with its golden file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Outcome {
+    Matched,
+    /// The output differs from the golden file, with a line-by-line diff of the two.
+    Mismatched(String),
+    /// There is no golden file yet.
+    Missing,
+    /// The golden file was written, either because it was missing or because it differed.
+    Written,
+}
+
+/// Compare `actual` with the golden file at `path`, or write it there in update mode.
+pub fn check(path: &Path, actual: &str, mode: Mode) -> io::Result<Outcome> {
+    let expected = match fs::read_to_string(path) {
+        Ok(expected) => Some(expected),
+        Err(e) if e.kind() == io::ErrorKind::NotFound => None,
+        Err(e) => return Err(e),
+    };
+
+    match (expected, mode) {
+        (Some(expected), _) if expected == actual => Ok(Outcome::Matched),
+        (_, Mode::Update) => {
+            if let Some(dir) = path.parent() {
+                fs::create_dir_all(dir)?;
+            }
+            fs::write(path, actual)?;
+            Ok(Outcome::Written)
+        }
+        (Some(expected), Mode::Check) => Ok(Outcome::Mismatched(diff(&expected, actual))),
+        (None, Mode::Check) => Ok(Outcome::Missing),
+    }
+}
+
+/// The golden file of a part, e.g. `day10_part2.txt`, or `day10_part2_alice.txt` for a named
+/// input. Variants of a part share their golden file, as they should agree on the output.
+pub fn path(dir: &Path, day: u8, part: u8, input_set: Option<&str>) -> PathBuf {
+    match input_set {
+        Some(name) => dir.join(format!("day{:0>2}_part{}_{}.txt", day, part, name)),
+        None => dir.join(format!("day{:0>2}_part{}.txt", day, part)),
+    }
+}
+
+/// How many lines of an explanation a golden file keeps. Explanations that list every line of the
+/// input would otherwise make for golden files about as large as (and as private as) the input.
+pub const MAX_EXPLANATION_LINES: usize = 20;
+
+/// The output of a run as stored in its golden file: the answer followed by the start of the
+/// explanation.
+pub fn render(run: &Run) -> String {
+    let mut s = match &run.answer {
+        Ok(Some(answer)) => answer.clone(),
+        Ok(None) => "-".to_string(),
+        Err(e) => format!("error: {}", e),
+    };
+    s.push('\n');
+
+    if !run.explanation.is_empty() {
+        s.push_str("--- explanation ---\n");
+        for line in run.explanation.iter().take(MAX_EXPLANATION_LINES) {
+            s.push_str(line);
+            s.push('\n');
+        }
+        if run.explanation.len() > MAX_EXPLANATION_LINES {
+            s.push_str(&format!(
+                "... {} more line(s)\n",
+                run.explanation.len() - MAX_EXPLANATION_LINES
+            ));
+        }
+    }
+
+    s
+}
+
+/// Check every run among the results against its golden file, returning the outcomes in order.
+pub fn check_all(
+    dir: &Path,
+    results: &[DayRuns],
+    mode: Mode,
+) -> io::Result<Vec<(PathBuf, Outcome)>> {
+    let mut outcomes: Vec<(PathBuf, Outcome)> = Vec::new();
+
+    for day_runs in results.iter() {
+        for run in day_runs.runs.iter().flatten() {
+            let path = path(dir, run.day, run.part, day_runs.input_set.as_deref());
+            let outcome = check(&path, &render(run), mode)?;
+            outcomes.push((path, outcome));
+        }
+    }
+
+    Ok(outcomes)
+}
+
+/// A line-by-line diff in the style of `diff -u`, without the hunk headers: removed lines start
+/// with `-`, added lines with `+` and common lines with a space.
+pub fn diff(expected: &str, actual: &str) -> String {
+    let old: Vec<&str> = expected.lines().collect();
+    let new: Vec<&str> = actual.lines().collect();
+
+    // lengths of the longest common subsequences of the suffixes of both sides
+    let mut lcs: Vec<Vec<usize>> = vec![vec![0; new.len() + 1]; old.len() + 1];
+    for i in (0..old.len()).rev() {
+        for j in (0..new.len()).rev() {
+            lcs[i][j] = match old[i] == new[j] {
+                true => lcs[i + 1][j + 1] + 1,
+                false => lcs[i + 1][j].max(lcs[i][j + 1]),
+            };
+        }
+    }
+
+    let mut s = String::new();
+    let (mut i, mut j) = (0, 0);
+    while i < old.len() || j < new.len() {
+        if i < old.len() && j < new.len() && old[i] == new[j] {
+            s.push_str(&format!("  {}\n", old[i]));
+            i += 1;
+            j += 1;
+        } else if i < old.len() && (j == new.len() || lcs[i + 1][j] >= lcs[i][j + 1]) {
+            s.push_str(&format!("- {}\n", old[i]));
+            i += 1;
+        } else {
+            s.push_str(&format!("+ {}\n", new[j]));
+            j += 1;
+        }
+    }
+
+    s
+}
@@ -0,0 +1,181 @@
+use serde::Serialize;
+use std::fmt;
+use std::path::Path;
+
+use crate::answers::{Answers, Verdict};
+use crate::registry;
+use crate::runner::DayRuns;
+use crate::{embedded, input_sets};
+
+/// Number of days in the Advent of Code calendar.
+pub const CALENDAR_DAYS: u8 = 25;
+
+/// The state of a single part, from not started to verified.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PartStatus {
+    /// No solver is registered for the part.
+    Missing,
+    /// The registered solver is a placeholder.
+    Stub,
+    /// There is a solver, but no input to run it on.
+    NoInput,
+    /// There is a solver and an input, but no expected answer to check against.
+    Unverified,
+    /// The expected answer of the part is known, but the solver has not been run against it.
+    Unchecked,
+    /// The solver was run and gave the expected answer.
+    Verified,
+    /// The solver was run and gave another answer than the expected one, or failed.
+    Wrong,
+}
+
+impl fmt::Display for PartStatus {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let s = match self {
+            PartStatus::Missing => "-",
+            PartStatus::Stub => "stub",
+            PartStatus::NoInput => "no input",
+            PartStatus::Unverified => "unverified",
+            PartStatus::Unchecked => "unchecked",
+            PartStatus::Verified => "verified",
+            PartStatus::Wrong => "WRONG",
+        };
+
+        f.pad(s)
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct DayStatus {
+    pub day: u8,
+    pub input: bool,
+    pub parts: [PartStatus; 2],
+}
+
+/// The status of every day of the calendar, as far as it is known without running the solvers:
+/// parts with an expected answer are `Unchecked` until `check` compares them with actual runs.
+pub fn calendar(input_dir: &Path, answers: &Answers) -> Vec<DayStatus> {
+    let solvers = registry::solvers();
+
+    (1..=CALENDAR_DAYS)
+        .map(|day| {
+            let input = input_sets(input_dir, day)
+                .map(|sets| sets.iter().any(|s| embedded::exists(input_dir, &s.path)))
+                .unwrap_or(false);
+
+            let part_status = |part: u8| {
+                let mut variants = solvers.iter().filter(|s| s.day == day && s.part == part);
+
+                match variants.next() {
+                    None => PartStatus::Missing,
+                    Some(solver) if solver.stub => PartStatus::Stub,
+                    Some(_) if !input => PartStatus::NoInput,
+                    Some(_) if answers.has_any(day, part) => PartStatus::Unchecked,
+                    Some(_) => PartStatus::Unverified,
+                }
+            };
+
+            DayStatus {
+                day,
+                input,
+                parts: [part_status(1), part_status(2)],
+            }
+        })
+        .collect()
+}
+
+/// Check the runs among `results` against the expected answers, marking the parts of the calendar
+/// they belong to as `Verified` or `Wrong`.
+pub fn check(calendar: &mut [DayStatus], results: &[DayRuns], answers: &Answers) {
+    for day in calendar.iter_mut() {
+        check_parts(day.day, &mut day.parts, results, answers);
+    }
+}
+
+/// Check the runs of a single day, which verify a part only if every run with an expected answer
+/// (on any of the inputs) gave it.
+pub fn check_parts(day: u8, parts: &mut [PartStatus; 2], results: &[DayRuns], answers: &Answers) {
+    for (i, status) in parts.iter_mut().enumerate() {
+        let part = i as u8 + 1;
+        if !matches!(
+            status,
+            PartStatus::Unchecked | PartStatus::Verified | PartStatus::Wrong
+        ) {
+            continue;
+        }
+
+        let verdicts: Vec<Verdict> =
+            results
+                .iter()
+                .filter(|r| r.day == day)
+                .flat_map(|r| {
+                    let input_set = r.input_set.as_deref();
+                    r.runs.iter().flatten().filter(|run| run.part == part).map(
+                        move |run| match &run.answer {
+                            Ok(answer) => answers.verdict(day, input_set, part, answer.as_deref()),
+                            Err(e) => match answers.get_for(day, input_set, part) {
+                                Some(_) => Verdict::Wrong(e.to_string()),
+                                None => Verdict::Unknown,
+                            },
+                        },
+                    )
+                })
+                .collect();
+
+        if verdicts.iter().any(|v| matches!(v, Verdict::Wrong(_))) {
+            *status = PartStatus::Wrong;
+        } else if verdicts.contains(&Verdict::Correct) {
+            *status = PartStatus::Verified;
+        }
+    }
+}
+
+pub fn render(calendar: &[DayStatus]) -> String {
+    let mut s = String::new();
+    s.push_str(&format!(
+        "{:>3}  {:<5}  {:<10}  {:<10}\n",
+        "Day", "Input", "Part 1", "Part 2"
+    ));
+
+    for day in calendar.iter() {
+        let stars: String = day
+            .parts
+            .iter()
+            .map(|&p| if p == PartStatus::Verified { '*' } else { ' ' })
+            .collect();
+
+        s.push_str(&format!(
+            "{:>3}  {:<5}  {:<10}  {:<10}  {}\n",
+            day.day,
+            if day.input { "yes" } else { "-" },
+            day.parts[0],
+            day.parts[1],
+            stars
+        ));
+    }
+
+    let count = |status: PartStatus| {
+        calendar
+            .iter()
+            .flat_map(|d| d.parts.iter())
+            .filter(|&&p| p == status)
+            .count()
+    };
+    let implemented = count(PartStatus::Verified)
+        + count(PartStatus::Wrong)
+        + count(PartStatus::Unchecked)
+        + count(PartStatus::Unverified)
+        + count(PartStatus::NoInput);
+
+    s.push_str(&format!(
+        "\n{} of {} parts implemented, {} verified, {} wrong, {} stub(s)\n",
+        implemented,
+        2 * CALENDAR_DAYS as usize,
+        count(PartStatus::Verified),
+        count(PartStatus::Wrong),
+        count(PartStatus::Stub)
+    ));
+
+    s
+}
@@ -0,0 +1,383 @@
+use chacha20poly1305::aead::{Aead, AeadCore, KeyInit, OsRng};
+use chacha20poly1305::{ChaCha20Poly1305, Key, Nonce};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::config::user_config_dir;
+
+/// Extension appended to the name of an encrypted file, e.g. `day_01.txt.enc`.
+pub const EXTENSION: &str = "enc";
+
+/// Marks (and versions) the format of an encrypted file: the magic, a random 96-bit nonce and the
+/// ChaCha20-Poly1305 ciphertext of the content.
+const MAGIC: &[u8] = b"AOCENC1\n";
+const NONCE_LEN: usize = 12;
+
+/// The key file used to decrypt inputs, set once from the configuration.
+static KEY_FILE: OnceLock<PathBuf> = OnceLock::new();
+
+/// Default location of the key, outside of the checkout so it is never committed by accident.
+pub fn default_key_file() -> PathBuf {
+    match user_config_dir() {
+        Some(dir) => dir.join("input.key"),
+        None => PathBuf::from(".aoc-input.key"),
+    }
+}
+
+/// Use `path` as the key to decrypt inputs with from now on.
+pub fn set_key_file(path: &Path) {
+    let _ = KEY_FILE.set(path.to_path_buf());
+}
+
+fn key_file() -> PathBuf {
+    KEY_FILE.get().cloned().unwrap_or_else(default_key_file)
+}
+
+/// The encrypted counterpart of a file, e.g. `day_01.txt.enc` for `day_01.txt`.
+pub fn encrypted_path(path: &Path) -> PathBuf {
+    with_suffix(path, EXTENSION)
+}
+
+/// The path with another extension appended, keeping the one it has.
+fn with_suffix(path: &Path, suffix: &str) -> PathBuf {
+    let mut name = path.as_os_str().to_os_string();
+    name.push(".");
+    name.push(suffix);
+
+    PathBuf::from(name)
+}
+
+/// Read the key from a file holding it as 64 hexadecimal digits.
+pub fn load_key(path: &Path) -> io::Result<Key> {
+    let invalid = || {
+        io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!(
+                "Invalid key file {} (expected 64 hex digits)",
+                path.display()
+            ),
+        )
+    };
+
+    let hex = fs::read_to_string(path).map_err(|e| {
+        io::Error::new(
+            e.kind(),
+            format!("Could not read the input key {}: {}", path.display(), e),
+        )
+    })?;
+    let hex = hex.trim();
+    if hex.len() != 64 || !hex.is_ascii() {
+        return Err(invalid());
+    }
+
+    let bytes = (0..32)
+        .map(|i| u8::from_str_radix(&hex[2 * i..2 * i + 2], 16).map_err(|_| invalid()))
+        .collect::<io::Result<Vec<u8>>>()?;
+
+    Ok(*Key::from_slice(&bytes))
+}
+
+/// Write a new random key to `path`, readable by the owner only.
+pub fn write_new_key(path: &Path) -> io::Result<Key> {
+    let key = ChaCha20Poly1305::generate_key(&mut OsRng);
+    let hex: String = key.iter().map(|b| format!("{:02x}", b)).collect();
+
+    if let Some(dir) = path.parent().filter(|d| !d.as_os_str().is_empty()) {
+        fs::create_dir_all(dir)?;
+    }
+
+    let mut options = fs::OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+    io::Write::write_all(&mut options.open(path)?, format!("{}\n", hex).as_bytes())?;
+
+    Ok(key)
+}
+
+pub fn encrypt(key: &Key, content: &[u8]) -> Vec<u8> {
+    let nonce = ChaCha20Poly1305::generate_nonce(&mut OsRng);
+    let ciphertext = ChaCha20Poly1305::new(key)
+        .encrypt(&nonce, content)
+        .expect("encryption of an in-memory buffer does not fail");
+
+    [MAGIC, nonce.as_slice(), &ciphertext].concat()
+}
+
+/// Decrypt the content of an encrypted file; `path` only serves the error messages.
+pub fn decrypt(key: &Key, data: &[u8], path: &Path) -> io::Result<String> {
+    let invalid = |msg: &str| {
+        io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("Could not decrypt {}: {}", path.display(), msg),
+        )
+    };
+
+    let rest = data
+        .strip_prefix(MAGIC)
+        .ok_or_else(|| invalid("not an encrypted input"))?;
+    if rest.len() < NONCE_LEN {
+        return Err(invalid("truncated file"));
+    }
+    let (nonce, ciphertext) = rest.split_at(NONCE_LEN);
+
+    let content = ChaCha20Poly1305::new(key)
+        .decrypt(Nonce::from_slice(nonce), ciphertext)
+        .map_err(|_| invalid("wrong key, or the file was modified"))?;
+
+    String::from_utf8(content).map_err(|_| invalid("the content is not valid UTF-8"))
+}
+
+/// Decrypt with the configured key.
+pub fn decrypt_with_key_file(data: &[u8], path: &Path) -> io::Result<String> {
+    decrypt(&load_key(&key_file())?, data, path)
+}
+
+/// Read the encrypted counterpart of `path` from disk, if there is one.
+pub fn read(path: &Path) -> io::Result<Option<String>> {
+    let encrypted = encrypted_path(path);
+    match fs::read(&encrypted) {
+        Ok(data) => decrypt_with_key_file(&data, &encrypted).map(Some),
+        Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(None),
+        Err(e) => Err(e),
+    }
+}
+
+/// All files below the input directory with the given extension, e.g. the plain text inputs
+/// (`day_XX.txt` and the named inputs) or their encrypted counterparts.
+fn files_with_extension(input_dir: &Path, extension: &str) -> io::Result<Vec<PathBuf>> {
+    let mut files: Vec<PathBuf> = Vec::new();
+    let mut dirs: Vec<PathBuf> = vec![input_dir.to_path_buf()];
+
+    while let Some(dir) = dirs.pop() {
+        for entry in fs::read_dir(dir)? {
+            let path = entry?.path();
+            if path.is_dir() {
+                dirs.push(path);
+            } else if path.extension().is_some_and(|ext| ext == extension) {
+                files.push(path);
+            }
+        }
+    }
+    files.sort();
+
+    Ok(files)
+}
+
+/// Encrypt every plain text input below the input directory next to it, creating the key if
+/// there is none yet. With `remove`, the plain text files are deleted once encrypted.
+pub fn encrypt_inputs(input_dir: &Path, key_file: &Path, remove: bool) -> io::Result<Vec<PathBuf>> {
+    let key = match key_file.is_file() {
+        true => load_key(key_file)?,
+        false => write_new_key(key_file)?,
+    };
+
+    let mut encrypted: Vec<PathBuf> = Vec::new();
+    for path in files_with_extension(input_dir, "txt")? {
+        let target = encrypted_path(&path);
+        write_atomically(&target, &encrypt(&key, &fs::read(&path)?))?;
+
+        // make sure the encrypted copy reads back before the original goes
+        if remove {
+            decrypt(&key, &fs::read(&target)?, &target)?;
+            fs::remove_file(&path)?;
+        }
+        encrypted.push(target);
+    }
+
+    Ok(encrypted)
+}
+
+/// Re-encrypt every encrypted input with a new key, telling how many there were and where the
+/// previous key was kept. Every rotation keeps its own backup next to the new key, suffixed with
+/// the time of the rotation (e.g. `input.key.1671062400.old`), for files encrypted with it
+/// elsewhere (e.g. in other checkouts).
+pub fn rotate_key(input_dir: &Path, key_file: &Path) -> io::Result<(usize, PathBuf)> {
+    let old_key = load_key(key_file)?;
+
+    // decrypt everything first, so that a file that cannot be decrypted leaves all as it was
+    let files = files_with_extension(input_dir, EXTENSION)?;
+    let contents = files
+        .iter()
+        .map(|path| decrypt(&old_key, &fs::read(path)?, path))
+        .collect::<io::Result<Vec<String>>>()?;
+
+    let stamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_secs());
+    let backup = with_suffix(key_file, &format!("{}.old", stamp));
+    if backup.exists() {
+        return Err(io::Error::new(
+            io::ErrorKind::AlreadyExists,
+            format!(
+                "The key backup {} already exists, not overwriting it",
+                backup.display()
+            ),
+        ));
+    }
+    fs::copy(key_file, &backup)?;
+    let new_key = write_new_key(key_file)?;
+
+    for (path, content) in files.iter().zip(contents) {
+        write_atomically(path, &encrypt(&new_key, content.as_bytes()))?;
+    }
+
+    Ok((files.len(), backup))
+}
+
+fn write_atomically(path: &Path, data: &[u8]) -> io::Result<()> {
+    let tmp = path.with_extension(format!("tmp{}", std::process::id()));
+    fs::write(&tmp, data)?;
+    fs::rename(tmp, path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::env;
+
+    /// A directory of its own for each test, with inputs in it and a subdirectory of named ones.
+    fn input_dir(name: &str) -> PathBuf {
+        let dir = env::temp_dir().join(format!("aoc2022-vault-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(dir.join("day_01")).unwrap();
+        fs::write(dir.join("day_01.txt"), "1000\n2000\n").unwrap();
+        fs::write(dir.join("day_01").join("alice.txt"), "3000\n").unwrap();
+
+        dir
+    }
+
+    #[test]
+    fn decrypts_what_it_encrypted() {
+        let key = ChaCha20Poly1305::generate_key(&mut OsRng);
+        let path = Path::new("day_01.txt.enc");
+        let data = encrypt(&key, b"1000\n2000\n");
+
+        assert!(data.starts_with(MAGIC));
+        assert_eq!(decrypt(&key, &data, path).unwrap(), "1000\n2000\n");
+        // every encryption has a nonce of its own
+        assert_ne!(encrypt(&key, b"1000\n2000\n"), data);
+    }
+
+    #[test]
+    fn rejects_what_it_cannot_decrypt() {
+        let key = ChaCha20Poly1305::generate_key(&mut OsRng);
+        let other = ChaCha20Poly1305::generate_key(&mut OsRng);
+        let path = Path::new("day_01.txt.enc");
+        let data = encrypt(&key, b"1000\n");
+
+        let mut modified = data.clone();
+        *modified.last_mut().unwrap() ^= 1;
+
+        for data in [
+            encrypt(&other, b"1000\n"),
+            modified,
+            data[..MAGIC.len() + NONCE_LEN - 1].to_vec(),
+            b"1000\n".to_vec(),
+            encrypt(&key, &[0xff, 0x0a]),
+        ] {
+            let e = decrypt(&key, &data, path).unwrap_err();
+            assert_eq!(e.kind(), io::ErrorKind::InvalidData);
+            assert!(e.to_string().contains("day_01.txt.enc"), "{}", e);
+        }
+    }
+
+    #[test]
+    fn loads_the_key_it_wrote() {
+        let dir = input_dir("key");
+        let path = dir.join("keys").join("input.key");
+
+        let key = write_new_key(&path).unwrap();
+        assert_eq!(load_key(&path).unwrap(), key);
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = fs::metadata(&path).unwrap().permissions().mode();
+            assert_eq!(mode & 0o777, 0o600);
+        }
+
+        for content in ["", "abcd", &"g".repeat(64), &"é".repeat(32)] {
+            fs::write(&path, content).unwrap();
+            let e = load_key(&path).unwrap_err();
+            assert_eq!(e.kind(), io::ErrorKind::InvalidData, "{:?}", content);
+        }
+
+        let e = load_key(&dir.join("missing.key")).unwrap_err();
+        assert_eq!(e.kind(), io::ErrorKind::NotFound);
+    }
+
+    #[test]
+    fn encrypts_every_input() {
+        let dir = input_dir("encrypt");
+        let key_file = dir.join("input.key");
+
+        let encrypted = encrypt_inputs(&dir, &key_file, false).unwrap();
+        assert_eq!(
+            encrypted,
+            vec![
+                dir.join("day_01").join("alice.txt.enc"),
+                dir.join("day_01.txt.enc")
+            ]
+        );
+        let key = load_key(&key_file).unwrap();
+        let data = fs::read(dir.join("day_01.txt.enc")).unwrap();
+        assert_eq!(decrypt(&key, &data, &dir).unwrap(), "1000\n2000\n");
+        assert!(dir.join("day_01.txt").is_file());
+
+        // the key written on the first run is used again
+        encrypt_inputs(&dir, &key_file, true).unwrap();
+        assert_eq!(load_key(&key_file).unwrap(), key);
+        assert!(!dir.join("day_01.txt").exists());
+        assert!(!dir.join("day_01").join("alice.txt").exists());
+        let data = fs::read(dir.join("day_01").join("alice.txt.enc")).unwrap();
+        assert_eq!(decrypt(&key, &data, &dir).unwrap(), "3000\n");
+    }
+
+    #[test]
+    fn rotates_the_key() {
+        let dir = input_dir("rotate");
+        let key_file = dir.join("input.key");
+        encrypt_inputs(&dir, &key_file, true).unwrap();
+        let old_key = load_key(&key_file).unwrap();
+
+        let (count, backup) = rotate_key(&dir, &key_file).unwrap();
+        assert_eq!(count, 2);
+        assert_eq!(load_key(&backup).unwrap(), old_key);
+
+        let new_key = load_key(&key_file).unwrap();
+        assert_ne!(new_key, old_key);
+        let data = fs::read(dir.join("day_01.txt.enc")).unwrap();
+        assert_eq!(decrypt(&new_key, &data, &dir).unwrap(), "1000\n2000\n");
+        assert!(decrypt(&old_key, &data, &dir).is_err());
+    }
+
+    #[test]
+    fn leaves_everything_as_it_was_when_a_file_cannot_be_decrypted() {
+        let dir = input_dir("rollback");
+        let key_file = dir.join("input.key");
+        encrypt_inputs(&dir, &key_file, true).unwrap();
+        let key = load_key(&key_file).unwrap();
+        let other = ChaCha20Poly1305::generate_key(&mut OsRng);
+        let foreign = dir.join("day_02.txt.enc");
+        fs::write(&foreign, encrypt(&other, b"A Y\n")).unwrap();
+        let before = fs::read(dir.join("day_01.txt.enc")).unwrap();
+
+        let e = rotate_key(&dir, &key_file).unwrap_err();
+        assert_eq!(e.kind(), io::ErrorKind::InvalidData);
+        assert!(e.to_string().contains("day_02.txt.enc"), "{}", e);
+
+        assert_eq!(load_key(&key_file).unwrap(), key);
+        assert_eq!(fs::read(dir.join("day_01.txt.enc")).unwrap(), before);
+        let backups = fs::read_dir(&dir)
+            .unwrap()
+            .filter(|entry| {
+                let name = entry.as_ref().unwrap().file_name();
+                name.to_string_lossy().ends_with(".old")
+            })
+            .count();
+        assert_eq!(backups, 0);
+    }
+}
@@ -0,0 +1,46 @@
+//! Replay the crashes saved under `fuzz/regressions/<target>/` through the parser of the matching
+//! fuzz target. A parser may reject these inputs, but it must not panic (or overflow the stack).
+
+use std::fs;
+use std::path::Path;
+
+use aoc2022::*;
+
+fn parse(target: &str, data: &[u8]) {
+    match target {
+        "day01" => drop(day01::parse(data)),
+        "day02" => drop(day02::parse(data, &day02::Rules::classic())),
+        "day03" => drop(day03::parse(data)),
+        "day04" => drop(day04::parse(data)),
+        "day05" => drop(day05::parse(data)),
+        "day06" => drop(day06::parse(data)),
+        "day07" => drop(day07::parse(data)),
+        "day08" => drop(day08::parse(data)),
+        "day09" => drop(day09::parse(data)),
+        "day10" => drop(day10::parse(data)),
+        "day11" => drop(day11::parse(data)),
+        "day12" => drop(day12::parse(data)),
+        "day13" => drop(day13::parse(data)),
+        _ => panic!("no fuzz target named {}", target),
+    }
+}
+
+#[test]
+fn saved_crashes_no_longer_panic() {
+    let regressions = Path::new(env!("CARGO_MANIFEST_DIR")).join("fuzz/regressions");
+    let mut replayed = 0;
+
+    for target in fs::read_dir(regressions).unwrap() {
+        let target = target.unwrap().path();
+        let name = target.file_name().unwrap().to_str().unwrap().to_string();
+
+        for input in fs::read_dir(&target).unwrap() {
+            let input = input.unwrap().path();
+            eprintln!("replaying {}", input.display());
+            parse(&name, &fs::read(&input).unwrap());
+            replayed += 1;
+        }
+    }
+
+    assert!(replayed > 0, "no saved crashes to replay");
+}
@@ -0,0 +1,113 @@
+//! The rebalancing plans of day 1 on small inventories: the exact plan is optimal (as found by
+//! trying every partition), the heuristic one is never better and stays within its guarantee, and
+//! both can be carried out.
+
+use aoc2022::day01::{rebalance, rebalance_with, Elf, Method, Plan};
+
+/// A small deterministic generator, so that failures can be reproduced.
+struct Lcg(u64);
+
+impl Lcg {
+    fn below(&mut self, n: u64) -> u64 {
+        self.0 = self
+            .0
+            .wrapping_mul(6364136223846793005)
+            .wrapping_add(1442695040888963407);
+        (self.0 >> 33) % n
+    }
+}
+
+/// Every small instance: between 1 and 4 elves holding between 1 and 8 items in total.
+fn instances() -> Vec<Vec<Elf>> {
+    let mut rng = Lcg(2022);
+
+    (0..300)
+        .map(|_| {
+            let n = 1 + rng.below(4) as usize;
+            let mut elves: Vec<Elf> = (1..=n)
+                .map(|id| Elf {
+                    id,
+                    inventory: Vec::new(),
+                })
+                .collect();
+            for _ in 0..1 + rng.below(8) {
+                let elf = rng.below(n as u64) as usize;
+                elves[elf].inventory.push(1 + rng.below(50) as i32);
+            }
+
+            elves
+        })
+        .collect()
+}
+
+/// The smallest maximum load over every assignment of the items to the elves.
+fn brute_force(elves: &[Elf]) -> i32 {
+    let items: Vec<i32> = elves.iter().flat_map(|e| e.inventory.clone()).collect();
+    let n = elves.len();
+
+    (0..n.pow(items.len() as u32))
+        .map(|mut code| {
+            let mut loads = vec![0; n];
+            for &c in items.iter() {
+                loads[code % n] += c;
+                code /= n;
+            }
+            loads.into_iter().max().unwrap()
+        })
+        .min()
+        .unwrap()
+}
+
+/// The most calories any elf carries once the plan is carried out.
+fn carried_out(plan: &Plan, elves: &[Elf]) -> i32 {
+    let after = plan
+        .apply(elves)
+        .expect("every transfer names an item of the giving elf");
+    let mut before: Vec<i32> = elves.iter().flat_map(|e| e.inventory.clone()).collect();
+    let mut moved: Vec<i32> = after.iter().flat_map(|e| e.inventory.clone()).collect();
+    before.sort_unstable();
+    moved.sort_unstable();
+    assert_eq!(before, moved, "the plan loses or makes up items");
+
+    after.iter().map(|elf| elf.total() as i32).max().unwrap()
+}
+
+#[test]
+fn exact_plans_are_optimal() {
+    for elves in instances() {
+        let plan = rebalance_with(&elves, Method::Exact);
+
+        assert!(plan.optimal);
+        assert_eq!(plan.after, brute_force(&elves), "{:?}", elves);
+        assert_eq!(carried_out(&plan, &elves), plan.after, "{:?}", elves);
+    }
+}
+
+#[test]
+fn heuristic_plans_stay_close_to_the_exact_ones() {
+    for elves in instances() {
+        let exact = rebalance_with(&elves, Method::Exact);
+        let heuristic = rebalance_with(&elves, Method::Heuristic);
+
+        // the longest processing time heuristic is within 4/3 - 1/(3n) of the optimum
+        let n = elves.len() as i32;
+        assert!(heuristic.after >= exact.after, "{:?}", elves);
+        assert!(
+            3 * n * heuristic.after <= (4 * n - 1) * exact.after,
+            "{:?}",
+            elves
+        );
+        assert!(heuristic.after >= heuristic.lower_bound);
+        assert!(carried_out(&heuristic, &elves) <= heuristic.after);
+        if heuristic.optimal {
+            assert_eq!(heuristic.after, exact.after, "{:?}", elves);
+        }
+    }
+}
+
+#[test]
+fn small_inventories_are_rebalanced_exactly() {
+    for elves in instances() {
+        assert_eq!(rebalance(&elves), rebalance_with(&elves, Method::Exact));
+    }
+}
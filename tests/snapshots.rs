@@ -0,0 +1,70 @@
+//! Compare the answers and explanations of every day with the golden files in `snapshots/`, as
+//! `aoc2022 snapshot` does. The inputs are not part of the repository, so a day without one on
+//! disk fails the test rather than letting it pass without checking anything, unless
+//! `AOC_SKIP_MISSING_INPUTS` is set (e.g. in a checkout without the inputs).
+
+use std::env;
+use std::path::Path;
+
+use aoc2022::registry;
+use aoc2022::runner::{self, RunOptions};
+use aoc2022::snapshot::{self, Mode, Outcome};
+
+/// Variants to run instead of the default one, which is too slow for a debug build. Variants of a
+/// part share their golden file.
+const FAST_VARIANTS: &[(u8, &str)] = &[(12, "bfs")];
+
+#[test]
+fn snapshots_match() {
+    let root = Path::new(env!("CARGO_MANIFEST_DIR"));
+    let mut failures: Vec<String> = Vec::new();
+    let mut skipped: Vec<String> = Vec::new();
+
+    for day in registry::days() {
+        let options = RunOptions {
+            variant: FAST_VARIANTS
+                .iter()
+                .find(|(d, _)| *d == day)
+                .map(|(_, v)| v.to_string()),
+            explain: true,
+            ..Default::default()
+        };
+
+        let results = runner::run_days(&[day], &root.join("inputs"), &options, 1);
+        for day_runs in results.iter() {
+            if let Err(e) = &day_runs.runs {
+                skipped.push(format!("day {}: {}", day, e));
+            }
+        }
+
+        let outcomes =
+            snapshot::check_all(&root.join(snapshot::SNAPSHOT_DIR), &results, Mode::Check).unwrap();
+        for (path, outcome) in outcomes {
+            match outcome {
+                Outcome::Matched => {}
+                Outcome::Mismatched(diff) => {
+                    failures.push(format!("{}: MISMATCH\n{}", path.display(), diff))
+                }
+                outcome => failures.push(format!("{}: {:?}", path.display(), outcome)),
+            }
+        }
+    }
+
+    if !skipped.is_empty() {
+        eprintln!(
+            "WARNING: {} of {} days were not checked:\n{}",
+            skipped.len(),
+            registry::days().len(),
+            skipped.join("\n")
+        );
+        if env::var_os("AOC_SKIP_MISSING_INPUTS").is_none() {
+            failures.push(format!(
+                "{} day(s) could not be run; put their inputs (or their encrypted copies and the key) in inputs/, or set \
+                 AOC_SKIP_MISSING_INPUTS=1 to skip them",
+                skipped.len()
+            ));
+        }
+    }
+
+    assert!(failures.is_empty(), "\n{}", failures.join("\n"));
+}